@@ -5,6 +5,7 @@ use sc_service::ChainType;
 use serde::{Deserialize, Serialize};
 use sp_core::{sr25519, Pair, Public};
 use sp_runtime::traits::{IdentifyAccount, Verify};
+use std::path::Path;
 
 /// Specialized `ChainSpec` for the normal parachain runtime.
 pub type ChainSpec =
@@ -61,7 +62,165 @@ pub fn template_session_keys(keys: AuraId) -> samos_parachain_runtime::SessionKe
 	samos_parachain_runtime::SessionKeys { aura: keys }
 }
 
-pub fn development_config() -> ChainSpec {
+/// Samaritans, DID documents, profiles, trust quorums and apps a test network starts with.
+///
+/// Owners are SS58 addresses and are endowed at genesis, every other value is a UTF-8 string.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct SamaritanSeed {
+	pub samaritans: Vec<SeedSamaritan>,
+	pub apps: Vec<SeedApp>,
+	/// Later DID document versions, the last one of a DID is active.
+	pub documents: Vec<SeedDocument>,
+	pub profiles: Vec<SeedProfile>,
+	pub quorums: Vec<SeedQuorum>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedSamaritan {
+	pub owner: AccountId,
+	pub name: String,
+	pub did: String,
+	pub document: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct SeedApp {
+	pub owner: AccountId,
+	pub did: String,
+	pub document: String,
+	pub name: String,
+	pub logo: String,
+	#[serde(default)]
+	pub redirect_uris: Vec<String>,
+	#[serde(default)]
+	pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedDocument {
+	pub did: String,
+	pub document: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedProfile {
+	pub did: String,
+	pub profile: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedQuorum {
+	pub did: String,
+	pub members: Vec<String>,
+}
+
+impl SamaritanSeed {
+	/// Read a seed from a JSON file.
+	pub fn from_json_file(path: &Path) -> Result<Self, String> {
+		let file = std::fs::File::open(path)
+			.map_err(|e| format!("Error opening samaritan seed `{}`: {}", path.display(), e))?;
+		serde_json::from_reader(std::io::BufReader::new(file))
+			.map_err(|e| format!("Error parsing samaritan seed `{}`: {}", path.display(), e))
+	}
+
+	/// The seed file passed with `--samaritan-seed`, or the dev seed without one.
+	pub fn load(path: Option<&Path>) -> Result<Self, String> {
+		match path {
+			Some(path) => Self::from_json_file(path),
+			None => Ok(Self::development()),
+		}
+	}
+
+	/// Accounts owning the seeded samaritans and apps, each listed once.
+	fn owners(&self) -> Vec<AccountId> {
+		let mut owners: Vec<AccountId> = Vec::new();
+		let all = self.samaritans.iter().map(|s| &s.owner).chain(self.apps.iter().map(|a| &a.owner));
+		for owner in all {
+			if !owners.contains(owner) {
+				owners.push(owner.clone());
+			}
+		}
+		owners
+	}
+
+	/// Alice and Bob as samaritans vouching for each other, and an app owned by Charlie.
+	pub fn development() -> Self {
+		let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+		let bob = get_account_id_from_seed::<sr25519::Public>("Bob");
+		let charlie = get_account_id_from_seed::<sr25519::Public>("Charlie");
+
+		Self {
+			samaritans: vec![
+				SeedSamaritan {
+					owner: alice,
+					name: "alice".into(),
+					did: "did:sam:alice".into(),
+					document: "did-doc:alice".into(),
+				},
+				SeedSamaritan {
+					owner: bob,
+					name: "bob".into(),
+					did: "did:sam:bob".into(),
+					document: "did-doc:bob".into(),
+				},
+			],
+			apps: vec![SeedApp {
+				owner: charlie,
+				did: "did:sam:app".into(),
+				document: "did-doc:app".into(),
+				name: "Samaritan Dev App".into(),
+				logo: "logo:app".into(),
+				redirect_uris: vec!["http://localhost:3000/callback".into()],
+				scopes: vec!["profile".into()],
+			}],
+			documents: Vec::new(),
+			profiles: Vec::new(),
+			quorums: vec![
+				SeedQuorum { did: "did:sam:alice".into(), members: vec!["did:sam:bob".into()] },
+				SeedQuorum { did: "did:sam:bob".into(), members: vec!["did:sam:alice".into()] },
+			],
+		}
+	}
+
+	fn into_genesis(self) -> samos_parachain_runtime::SamaritanConfig {
+		let bytes = |s: String| s.into_bytes();
+		let list = |l: Vec<String>| l.into_iter().map(bytes).collect::<Vec<_>>();
+
+		samos_parachain_runtime::SamaritanConfig {
+			samaritans: self
+				.samaritans
+				.into_iter()
+				.map(|s| (s.owner, bytes(s.name), bytes(s.did), bytes(s.document)))
+				.collect(),
+			apps: self
+				.apps
+				.into_iter()
+				.map(|a| {
+					(
+						a.owner,
+						bytes(a.did),
+						bytes(a.document),
+						bytes(a.name),
+						bytes(a.logo),
+						list(a.redirect_uris),
+						list(a.scopes),
+					)
+				})
+				.collect(),
+			documents: self.documents.into_iter().map(|d| (bytes(d.did), bytes(d.document))).collect(),
+			profiles: self.profiles.into_iter().map(|p| (bytes(p.did), bytes(p.profile))).collect(),
+			quorums: self.quorums.into_iter().map(|q| (bytes(q.did), list(q.members))).collect(),
+		}
+	}
+}
+
+pub fn development_config(samaritan_seed: SamaritanSeed) -> ChainSpec {
 	// Give your base currency a unit name and decimal places
 	let mut properties = sc_chain_spec::Properties::new();
 	properties.insert("tokenSymbol".into(), "UNIT".into());
@@ -102,6 +261,7 @@ pub fn development_config() -> ChainSpec {
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
 				1000.into(),
+				samaritan_seed.clone(),
 			)
 		},
 		Vec::new(),
@@ -116,7 +276,7 @@ pub fn development_config() -> ChainSpec {
 	)
 }
 
-pub fn local_testnet_config() -> ChainSpec {
+pub fn local_testnet_config(samaritan_seed: SamaritanSeed) -> ChainSpec {
 	// Give your base currency a unit name and decimal places
 	let mut properties = sc_chain_spec::Properties::new();
 	properties.insert("tokenSymbol".into(), "UNIT".into());
@@ -157,6 +317,7 @@ pub fn local_testnet_config() -> ChainSpec {
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
 				1000.into(),
+				samaritan_seed.clone(),
 			)
 		},
 		// Bootnodes
@@ -181,6 +342,7 @@ fn testnet_genesis(
 	invulnerables: Vec<(AccountId, AuraId)>,
 	endowed_accounts: Vec<AccountId>,
 	id: ParaId,
+	samaritan_seed: SamaritanSeed,
 ) -> samos_parachain_runtime::GenesisConfig {
 	samos_parachain_runtime::GenesisConfig {
 		system: samos_parachain_runtime::SystemConfig {
@@ -189,7 +351,13 @@ fn testnet_genesis(
 				.to_vec(),
 		},
 		balances: samos_parachain_runtime::BalancesConfig {
-			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
+			// seeded entries hold deposits, so their owners need funds
+			balances: endowed_accounts
+				.iter()
+				.cloned()
+				.chain(samaritan_seed.owners().into_iter().filter(|o| !endowed_accounts.contains(o)))
+				.map(|k| (k, 1 << 60))
+				.collect(),
 		},
 		parachain_info: samos_parachain_runtime::ParachainInfoConfig { parachain_id: id },
 		collator_selection: samos_parachain_runtime::CollatorSelectionConfig {
@@ -217,5 +385,6 @@ fn testnet_genesis(
 		polkadot_xcm: samos_parachain_runtime::PolkadotXcmConfig {
 			safe_xcm_version: Some(SAFE_XCM_VERSION),
 		},
+		samaritan: samaritan_seed.into_genesis(),
 	}
 }
@@ -62,6 +62,13 @@ pub struct Cli {
 	#[clap(long)]
 	pub no_hardware_benchmarks: bool,
 
+	/// JSON file with the samaritans, apps and quorums the `dev` and `local` chains start with.
+	///
+	/// Without it they start with Alice and Bob as samaritans and an app owned by Charlie.
+	/// Other chains take their samaritan genesis from the chain spec file.
+	#[clap(long, global = true, value_name = "PATH")]
+	pub samaritan_seed: Option<PathBuf>,
+
 	/// Relay chain arguments
 	#[clap(raw = true)]
 	pub relay_chain_args: Vec<String>,
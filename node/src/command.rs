@@ -23,11 +23,15 @@ use crate::{
 	service::{new_partial, TemplateRuntimeExecutor},
 };
 
-fn load_spec(id: &str) -> std::result::Result<Box<dyn ChainSpec>, String> {
+fn load_spec(
+	id: &str,
+	samaritan_seed: Option<&std::path::Path>,
+) -> std::result::Result<Box<dyn ChainSpec>, String> {
+	let seed = || chain_spec::SamaritanSeed::load(samaritan_seed);
 	Ok(match id {
-		"dev" => Box::new(chain_spec::development_config()),
-		"template-rococo" => Box::new(chain_spec::local_testnet_config()),
-		"" | "local" => Box::new(chain_spec::local_testnet_config()),
+		"dev" => Box::new(chain_spec::development_config(seed()?)),
+		"template-rococo" => Box::new(chain_spec::local_testnet_config(seed()?)),
+		"" | "local" => Box::new(chain_spec::local_testnet_config(seed()?)),
 		path => Box::new(chain_spec::ChainSpec::from_json_file(std::path::PathBuf::from(path))?),
 	})
 }
@@ -64,7 +68,7 @@ impl SubstrateCli for Cli {
 	}
 
 	fn load_spec(&self, id: &str) -> std::result::Result<Box<dyn sc_service::ChainSpec>, String> {
-		load_spec(id)
+		load_spec(id, self.samaritan_seed.as_deref())
 	}
 
 	fn native_runtime_version(_: &Box<dyn ChainSpec>) -> &'static RuntimeVersion {
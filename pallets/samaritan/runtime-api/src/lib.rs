@@ -0,0 +1,49 @@
+//! Runtime API for reading samaritan state from clients without
+//! submitting extrinsics.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_core::H256;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait SamaritanApi<AppInfo, ConsentGrant, Release, AccessRecord, DidSignature> where
+		AppInfo: Codec,
+		ConsentGrant: Codec,
+		Release: Codec,
+		AccessRecord: Codec,
+		DidSignature: Codec,
+	{
+		/// check a revealed signup token against the commitment stored for the app and user
+		fn verify_signup_token(app_did: Vec<u8>, did: Vec<u8>, token: H256) -> bool;
+
+		/// the registry entry of an app including its verification status, used by
+		/// login flows and wallets to validate it
+		fn app(app_did: Vec<u8>) -> Option<AppInfo>;
+
+		/// the consent receipt a user gave to an app, including revoked grants
+		fn consent(did: Vec<u8>, app_did: Vec<u8>) -> Option<ConsentGrant>;
+
+		/// whether an app may currently read a scope of the user's data
+		fn has_consent(did: Vec<u8>, app_did: Vec<u8>, scope: Vec<u8>) -> bool;
+
+		/// check a master DID signature opens the link committed for a pairwise DID
+		fn verify_pairwise_link(did: Vec<u8>, master_did: Vec<u8>, master_signature: DidSignature) -> bool;
+
+		/// the newest stable release of an app that has not been yanked
+		fn latest_stable_release(app_did: Vec<u8>) -> Option<Release>;
+
+		/// a page of the apps that accessed a user's data, oldest first from sequence number `from`
+		fn access_log(did: Vec<u8>, from: u64, limit: u32) -> Vec<AccessRecord>;
+
+		/// the nonce the next DID signed call relayed for the DID must carry
+		fn did_nonce(did: Vec<u8>) -> u64;
+
+		/// the compact index calls can take in place of the DID, if it was interned
+		fn did_index(did: Vec<u8>) -> Option<u64>;
+
+		/// the trust quorum of a samaritan DID
+		fn trust_quorum(did: Vec<u8>) -> Vec<Vec<u8>>;
+	}
+}
@@ -0,0 +1,824 @@
+//! Benchmarking setup for pallet-samaritan
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as Samaritan;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_support::traits::Hooks;
+use frame_system::RawOrigin as SystemOrigin;
+use sp_core::crypto::KeyTypeId;
+use sp_runtime::{traits::Bounded, DispatchError};
+use sp_std::{boxed::Box, vec};
+
+const SEED: u32 = 0;
+
+/// key type the DID signing key is generated under
+const DID_KEY: KeyTypeId = KeyTypeId(*b"sdid");
+
+fn bound<V, S: Get<u32>>(value: Vec<V>) -> BoundedVec<V, S> {
+	value.try_into().expect("benchmark input fits its bound")
+}
+
+/// the longest allowed DID, so keys and events are as large as they get
+fn did_of<T: Config>(tag: &[u8]) -> Did<T> {
+	let mut did = b"did:sam:".to_vec();
+	did.extend_from_slice(tag);
+	did.resize(T::MaxDIDLength::get() as usize, b'0');
+	bound(did)
+}
+
+fn bytes<S: Get<u32>>(len: u32) -> BoundedVec<u8, S> {
+	bound(vec![b'a'; len as usize])
+}
+
+/// `count` distinct entries of `len` bytes each
+fn list<S: Get<u32>, L: Get<u32>>(count: u32, len: u32) -> BoundedVec<BoundedVec<u8, S>, L> {
+	let entries = (0..count)
+		.map(|i| {
+			let mut entry = vec![b'a'; len as usize];
+			let tag = i.to_le_bytes();
+			let n = tag.len().min(entry.len());
+			entry[..n].copy_from_slice(&tag[..n]);
+			bound(entry)
+		})
+		.collect();
+	bound(entries)
+}
+
+fn funded<T: Config>(name: &'static str, index: u32) -> T::AccountId {
+	let who: T::AccountId = account(name, index, SEED);
+	T::Currency::make_free_balance_be(&who, BalanceOf::<T>::max_value() / 2u32.into());
+	who
+}
+
+fn funded_caller<T: Config>() -> T::AccountId {
+	let who: T::AccountId = whitelisted_caller();
+	T::Currency::make_free_balance_be(&who, BalanceOf::<T>::max_value() / 2u32.into());
+	who
+}
+
+fn signed<T: Config>(who: &T::AccountId) -> OriginFor<T> {
+	SystemOrigin::Signed(who.clone()).into()
+}
+
+fn add_samaritan<T: Config>(who: &T::AccountId, did: &Did<T>) -> DispatchResult {
+	Samaritan::<T>::create_samaritan(signed::<T>(who), bytes(T::MaxNameLength::get()), did.clone(), bytes(T::MaxCallHashLength::get()))
+}
+
+/// an app requesting as many redirect URIs and scopes as allowed
+fn add_app<T: Config>(owner: &T::AccountId, did: &Did<T>) -> DispatchResult {
+	Samaritan::<T>::create_app(
+		signed::<T>(owner),
+		did.clone(),
+		bytes(T::MaxCallHashLength::get()),
+		bytes(T::MaxNameLength::get()),
+		bytes(T::MaxCallHashLength::get()),
+		list(T::MaxRedirectUris::get(), T::MaxUriLength::get()),
+		list(T::MaxScopes::get(), T::MaxScopeLength::get()),
+	)
+}
+
+/// fill the DID document history up to `len` entries, each as long as a call allows
+fn fill_history<T: Config>(who: &T::AccountId, did: &Did<T>, len: u32) -> DispatchResult {
+	for _ in 1..len {
+		Samaritan::<T>::update_document(signed::<T>(who), did.clone(), bytes(T::MaxCallHashLength::get()))?;
+	}
+	Ok(())
+}
+
+/// fill a trust quorum with `count` members
+fn fill_quorum<T: Config>(who: &T::AccountId, did: &Did<T>, count: u32) -> Result<Vec<Did<T>>, DispatchError> {
+	// measure against the hard cap, the admin origin can raise the soft one up to it
+	Parameters::<T>::mutate(|p| p.quorum_cap = T::MaxQuorumMembersCount::get());
+	let members: Vec<Did<T>> = (0..count).map(|i| did_of::<T>(&i.to_le_bytes())).collect();
+	for member in &members {
+		Samaritan::<T>::update_quorum(signed::<T>(who), DidRef::Did(did.clone()), DidRef::Did(member.clone()))?;
+	}
+	Ok(members)
+}
+
+/// fill the release window of an app, returning the newest version
+fn fill_releases<T: Config>(
+	owner: &T::AccountId,
+	app_did: &Did<T>
+) -> Result<BoundedVec<u8, T::MaxVersionLength>, DispatchError> {
+	let versions: BoundedVec<BoundedVec<u8, T::MaxVersionLength>, T::MaxReleases> =
+		list(T::MaxReleases::get(), T::MaxVersionLength::get());
+	for version in &versions {
+		Samaritan::<T>::publish_release(
+			signed::<T>(owner),
+			app_did.clone(),
+			version.clone(),
+			bytes(T::MaxCallHashLength::get()),
+			list(T::MaxScopes::get(), T::MaxScopeLength::get()),
+			H256::repeat_byte(1),
+			ReleaseChannel::Stable,
+		)?;
+	}
+	Ok(versions.last().cloned().unwrap_or_default())
+}
+
+/// consent to every scope the app requested
+fn grant_all<T: Config>(who: &T::AccountId, did: &Did<T>, app_did: &Did<T>) -> DispatchResult {
+	Samaritan::<T>::grant_consent(
+		signed::<T>(who),
+		did.clone(),
+		app_did.clone(),
+		list(T::MaxScopes::get(), T::MaxScopeLength::get()),
+		None,
+	)
+}
+
+/// a subscription whose next charge is due at the returned block
+fn subscription<T: Config>(
+	owner: &T::AccountId,
+	user: &T::AccountId,
+	did: &Did<T>,
+	app_did: &Did<T>,
+) -> Result<T::BlockNumber, DispatchError> {
+	let period: T::BlockNumber = 10u32.into();
+	Samaritan::<T>::create_plan(signed::<T>(owner), app_did.clone(), 1_000u32.into(), period, period)?;
+	Samaritan::<T>::subscribe(signed::<T>(user), did.clone(), app_did.clone(), 0, BalanceOf::<T>::max_value())?;
+	Ok(frame_system::Pallet::<T>::block_number() + period)
+}
+
+benchmarks! {
+	where_clause {
+		where
+			T::DidSignature: From<sp_core::sr25519::Signature>,
+			T::AccountId: From<sp_core::sr25519::Public>,
+	}
+
+	create_samaritan {
+		let h in 1 .. T::MaxCallHashLength::get();
+		let caller = funded_caller::<T>();
+		// the account moves over from an earlier DID
+		add_samaritan::<T>(&caller, &did_of::<T>(b"previous"))?;
+		let did = did_of::<T>(b"user");
+	}: _(SystemOrigin::Signed(caller.clone()), bytes(T::MaxNameLength::get()), did.clone(), bytes(h))
+	verify {
+		assert_eq!(DidOwners::<T>::get(&did), Some(caller));
+	}
+
+	rename_samaritan {
+		let caller = funded_caller::<T>();
+		add_samaritan::<T>(&caller, &did_of::<T>(b"user"))?;
+		let name: BoundedVec<u8, T::MaxNameLength> = bound(vec![b'b'; T::MaxNameLength::get() as usize]);
+	}: _(SystemOrigin::Signed(caller.clone()), name.clone())
+	verify {
+		assert_eq!(SamaritanRegistry::<T>::get(&caller).map(|s| s.name), Some(name));
+	}
+
+	alter_state {
+		let c in 1 .. T::MaxCacheLength::get();
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&caller, &did)?;
+		fill_history::<T>(&caller, &did, c)?;
+	}: _(SystemOrigin::Signed(caller), did.clone(), false)
+	verify {
+		let history = DocMetaRegistry::<T>::get(&did).unwrap_or_default();
+		assert!(!history.last().map_or(true, |d| d.active));
+	}
+
+	update_document {
+		let h in 1 .. T::MaxCallHashLength::get();
+		let c in 1 .. T::MaxCacheLength::get();
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&caller, &did)?;
+		// a full history drops its oldest version
+		fill_history::<T>(&caller, &did, c)?;
+	}: _(SystemOrigin::Signed(caller), did.clone(), bytes(h))
+	verify {
+		let history = DocMetaRegistry::<T>::get(&did).unwrap_or_default();
+		assert_eq!(history.len() as u32, (c + 1).min(T::MaxCacheLength::get()));
+	}
+
+	update_quorum {
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&caller, &did)?;
+		fill_quorum::<T>(&caller, &did, T::MaxQuorumMembersCount::get() - 1)?;
+		let member = did_of::<T>(b"last");
+	}: _(SystemOrigin::Signed(caller), DidRef::Did(did.clone()), DidRef::Did(member))
+	verify {
+		assert_eq!(Samaritan::<T>::trust_quorum(did.to_vec()).len() as u32, T::MaxQuorumMembersCount::get());
+	}
+
+	filter_quorum {
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&caller, &did)?;
+		let members = fill_quorum::<T>(&caller, &did, T::MaxQuorumMembersCount::get())?;
+		let member = members.last().cloned().unwrap_or_default();
+	}: _(SystemOrigin::Signed(caller), DidRef::Did(did.clone()), DidRef::Did(member))
+	verify {
+		assert_eq!(Samaritan::<T>::trust_quorum(did.to_vec()).len() as u32, T::MaxQuorumMembersCount::get() - 1);
+	}
+
+	update_profile {
+		let h in 1 .. T::MaxCallHashLength::get();
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&caller, &did)?;
+		Samaritan::<T>::update_profile(signed::<T>(&caller), did.clone(), bytes(T::MaxCallHashLength::get()))?;
+	}: _(SystemOrigin::Signed(caller), did.clone(), bytes(h))
+	verify {
+		assert_eq!(ProfileRegistry::<T>::get(&did).map(|p| p.len() as u32), Some(h));
+	}
+
+	create_app {
+		let h in 1 .. T::MaxCallHashLength::get();
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"app");
+	}: _(
+		SystemOrigin::Signed(caller),
+		did.clone(),
+		bytes(h),
+		bytes(T::MaxNameLength::get()),
+		bytes(T::MaxCallHashLength::get()),
+		list(T::MaxRedirectUris::get(), T::MaxUriLength::get()),
+		list(T::MaxScopes::get(), T::MaxScopeLength::get())
+	)
+	verify {
+		assert!(AppRegistry::<T>::contains_key(&did));
+	}
+
+	update_app {
+		let h in 1 .. T::MaxCallHashLength::get();
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"app");
+		add_app::<T>(&caller, &did)?;
+		// rebranding a verified app drops its verification
+		Samaritan::<T>::verify_app(T::VerifierOrigin::successful_origin(), did.clone(), H256::repeat_byte(1))?;
+	}: _(
+		SystemOrigin::Signed(caller),
+		did.clone(),
+		bytes(T::MaxNameLength::get()),
+		bytes(h),
+		list(T::MaxRedirectUris::get(), T::MaxUriLength::get()),
+		list(T::MaxScopes::get(), T::MaxScopeLength::get())
+	)
+	verify {
+		let app = AppRegistry::<T>::get(&did).ok_or("app missing")?;
+		assert_eq!(app.verification, Verification::Unverified);
+	}
+
+	set_app_status {
+		let owner = funded::<T>("owner", 0);
+		let did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &did)?;
+		let origin = T::VerifierOrigin::successful_origin();
+	}: {
+		Samaritan::<T>::set_app_status(origin, did.clone(), AppStatus::Suspended)?;
+	}
+	verify {
+		let app = AppRegistry::<T>::get(&did).ok_or("app missing")?;
+		assert_eq!(app.status, AppStatus::Suspended);
+	}
+
+	retire_app {
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"app");
+		add_app::<T>(&caller, &did)?;
+		// a funded sponsorship makes retiring refund the budget
+		Samaritan::<T>::set_sponsorship(signed::<T>(&caller), did.clone(), bound(vec![bound(b"create_samaritan".to_vec())]), 1)?;
+		Samaritan::<T>::fund_sponsorship(signed::<T>(&caller), did.clone(), T::Currency::minimum_balance() * 10u32.into())?;
+	}: _(SystemOrigin::Signed(caller), did.clone())
+	verify {
+		assert!(!AppRegistry::<T>::contains_key(&did));
+		assert!(T::Currency::free_balance(&Samaritan::<T>::sponsor_account(&did)).is_zero());
+	}
+
+	generate_token {
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+		// pairwise DIDs take an extra read to check their scope
+		let user = funded::<T>("user", 0);
+		let did = did_of::<T>(b"pairwise");
+		Samaritan::<T>::register_pairwise_did(signed::<T>(&user), did.clone(), app_did.clone(), H256::repeat_byte(1), bytes(T::MaxCallHashLength::get()))?;
+		// the user DID controller takes the longer path past the app owner check
+		let commitment = Samaritan::<T>::token_commitment(&H256::repeat_byte(1), &app_did, &did);
+	}: _(SystemOrigin::Signed(user), app_did.clone(), did.clone(), commitment)
+	verify {
+		assert!(SignUpDataRegistry::<T>::contains_key(&app_did, &did));
+	}
+
+	redeem_token {
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&funded::<T>("user", 0), &did)?;
+		let token = H256::repeat_byte(1);
+		let commitment = Samaritan::<T>::token_commitment(&token, &app_did, &did);
+		Samaritan::<T>::generate_token(signed::<T>(&owner), app_did.clone(), did.clone(), commitment)?;
+		let caller = funded_caller::<T>();
+	}: _(SystemOrigin::Signed(caller), app_did.clone(), did.clone(), token)
+	verify {
+		assert!(!SignUpDataRegistry::<T>::contains_key(&app_did, &did));
+	}
+
+	reap_signup_token {
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&funded::<T>("user", 0), &did)?;
+		Samaritan::<T>::generate_token(signed::<T>(&owner), app_did.clone(), did.clone(), H256::repeat_byte(1))?;
+		Samaritan::<T>::retire_app(signed::<T>(&owner), app_did.clone())?;
+		let caller = funded::<T>("reaper", 0);
+	}: _(SystemOrigin::Signed(caller), app_did.clone(), did.clone())
+	verify {
+		assert!(!SignUpDataRegistry::<T>::contains_key(&app_did, &did));
+	}
+
+	grant_consent {
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&caller, &did)?;
+		// worst case replaces an earlier grant
+		grant_all::<T>(&caller, &did, &app_did)?;
+		let expiry = T::TimeProvider::now().as_secs() + 1_000;
+	}: _(
+		SystemOrigin::Signed(caller),
+		did.clone(),
+		app_did.clone(),
+		list(T::MaxScopes::get(), T::MaxScopeLength::get()),
+		Some(expiry)
+	)
+	verify {
+		assert!(ConsentGrants::<T>::contains_key(&did, &app_did));
+		assert!(ConsentHistory::<T>::contains_key(&did, &app_did));
+	}
+
+	revoke_consent {
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&caller, &did)?;
+		grant_all::<T>(&caller, &did, &app_did)?;
+	}: _(SystemOrigin::Signed(caller), did.clone(), app_did.clone())
+	verify {
+		let grant = ConsentGrants::<T>::get(&did, &app_did).ok_or("grant missing")?;
+		assert!(grant.revoked);
+	}
+
+	register_pairwise_did {
+		let h in 1 .. T::MaxCallHashLength::get();
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"pairwise");
+	}: _(SystemOrigin::Signed(caller.clone()), did.clone(), app_did, H256::repeat_byte(1), bytes(h))
+	verify {
+		assert_eq!(DidOwners::<T>::get(&did), Some(caller));
+	}
+
+	recover_pairwise_did {
+		let k in 1 .. T::MaxDidKeys::get();
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+
+		// a request opened by the last member of a full trust quorum
+		let caller = funded_caller::<T>();
+		let helper_did = did_of::<T>(b"helper");
+		add_samaritan::<T>(&caller, &helper_did)?;
+		let master_owner = funded::<T>("master", 0);
+		let master = did_of::<T>(b"master");
+		add_samaritan::<T>(&master_owner, &master)?;
+		fill_quorum::<T>(&master_owner, &master, T::MaxQuorumMembersCount::get() - 1)?;
+		Samaritan::<T>::update_quorum(signed::<T>(&master_owner), DidRef::Did(master.clone()), DidRef::Did(helper_did))?;
+
+		// the master key that signed the pairing is the last one tried
+		for i in 1..k {
+			Samaritan::<T>::add_did_key(signed::<T>(&master_owner), master.clone(), account("key", i, SEED))?;
+		}
+		let public = sp_io::crypto::sr25519_generate(DID_KEY, None);
+		Samaritan::<T>::add_did_key(signed::<T>(&master_owner), master.clone(), public.clone().into())?;
+
+		let did = did_of::<T>(b"pairwise");
+		let payload = Samaritan::<T>::pairwise_payload(&did, &app_did);
+		let signature: T::DidSignature = sp_io::crypto::sr25519_sign(DID_KEY, &public, &payload).ok_or("signing failed")?.into();
+		let link = Samaritan::<T>::pairwise_link(&did, &master, &signature);
+		let lost = funded::<T>("lost", 0);
+		Samaritan::<T>::register_pairwise_did(signed::<T>(&lost), did.clone(), app_did, link, bytes(T::MaxCallHashLength::get()))?;
+		let new_owner = funded::<T>("new", 0);
+	}: _(SystemOrigin::Signed(caller), did.clone(), master, signature, new_owner.clone())
+	verify {
+		let recovery = PairwiseRecoveries::<T>::get(&did).ok_or("recovery missing")?;
+		assert_eq!(recovery.new_owner, new_owner);
+	}
+
+	publish_release {
+		let h in 1 .. T::MaxCallHashLength::get();
+		let caller = funded_caller::<T>();
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&caller, &app_did)?;
+		// a full window drops the oldest release, passing over the latest stable one
+		fill_releases::<T>(&caller, &app_did)?;
+		let version: BoundedVec<u8, T::MaxVersionLength> = bytes(T::MaxVersionLength::get());
+	}: _(
+		SystemOrigin::Signed(caller),
+		app_did.clone(),
+		version,
+		bytes(h),
+		list(T::MaxScopes::get(), T::MaxScopeLength::get()),
+		H256::repeat_byte(1),
+		ReleaseChannel::Beta
+	)
+	verify {
+		assert_eq!(AppReleases::<T>::get(&app_did).len() as u32, T::MaxReleases::get());
+	}
+
+	yank_release {
+		let caller = funded_caller::<T>();
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&caller, &app_did)?;
+		let version = fill_releases::<T>(&caller, &app_did)?;
+	}: _(SystemOrigin::Signed(caller), app_did.clone(), version)
+	verify {
+		let releases = AppReleases::<T>::get(&app_did);
+		assert!(releases.last().map_or(false, |r| r.yanked));
+	}
+
+	set_release_channel {
+		let caller = funded_caller::<T>();
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&caller, &app_did)?;
+		let version = fill_releases::<T>(&caller, &app_did)?;
+	}: _(SystemOrigin::Signed(caller), app_did.clone(), version, ReleaseChannel::Beta)
+	verify {
+		let releases = AppReleases::<T>::get(&app_did);
+		assert_eq!(releases.last().map(|r| r.channel), Some(ReleaseChannel::Beta));
+	}
+
+	verify_app {
+		let owner = funded::<T>("owner", 0);
+		let did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &did)?;
+		let origin = T::VerifierOrigin::successful_origin();
+	}: {
+		Samaritan::<T>::verify_app(origin, did.clone(), H256::repeat_byte(1))?;
+	}
+	verify {
+		let app = AppRegistry::<T>::get(&did).ok_or("app missing")?;
+		assert!(matches!(app.verification, Verification::Verified { .. }));
+	}
+
+	revoke_app_verification {
+		let owner = funded::<T>("owner", 0);
+		let did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &did)?;
+		Samaritan::<T>::verify_app(T::VerifierOrigin::successful_origin(), did.clone(), H256::repeat_byte(1))?;
+		let origin = T::VerifierOrigin::successful_origin();
+	}: {
+		Samaritan::<T>::revoke_app_verification(origin, did.clone(), H256::repeat_byte(2))?;
+	}
+	verify {
+		let app = AppRegistry::<T>::get(&did).ok_or("app missing")?;
+		assert!(matches!(app.verification, Verification::Revoked { .. }));
+	}
+
+	set_vault_pointer {
+		let h in 1 .. T::MaxCallHashLength::get();
+		let caller = funded_caller::<T>();
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&caller, &app_did)?;
+		let user = funded::<T>("user", 0);
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&user, &did)?;
+		grant_all::<T>(&user, &did, &app_did)?;
+		Samaritan::<T>::set_vault_pointer(signed::<T>(&caller), app_did.clone(), did.clone(), bytes(T::MaxCallHashLength::get()))?;
+	}: _(SystemOrigin::Signed(caller), app_did.clone(), did.clone(), bytes(h))
+	verify {
+		let vault = DataVaults::<T>::get(&app_did, &did).ok_or("vault missing")?;
+		assert_eq!(vault.version, 1);
+	}
+
+	erase_vault_pointer {
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&caller, &did)?;
+		grant_all::<T>(&caller, &did, &app_did)?;
+		Samaritan::<T>::set_vault_pointer(signed::<T>(&owner), app_did.clone(), did.clone(), bytes(T::MaxCallHashLength::get()))?;
+	}: _(SystemOrigin::Signed(caller), did.clone(), app_did.clone())
+	verify {
+		assert!(!DataVaults::<T>::contains_key(&app_did, &did));
+	}
+
+	log_access {
+		let caller = funded_caller::<T>();
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&caller, &app_did)?;
+		let user = funded::<T>("user", 0);
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&user, &did)?;
+		grant_all::<T>(&user, &did, &app_did)?;
+		// the last granted scope is found last
+		let scope = list::<T::MaxScopeLength, T::MaxScopes>(T::MaxScopes::get(), T::MaxScopeLength::get())
+			.last()
+			.cloned()
+			.ok_or("no scopes")?;
+		// a full log drops the oldest record
+		for purpose in 0..T::MaxAccessRecords::get() {
+			Samaritan::<T>::log_access(signed::<T>(&caller), app_did.clone(), did.clone(), scope.clone(), purpose)?;
+		}
+	}: _(SystemOrigin::Signed(caller), app_did, did.clone(), scope, 0)
+	verify {
+		assert_eq!(AccessLog::<T>::get(&did).len() as u32, T::MaxAccessRecords::get());
+	}
+
+	authorize_session_key {
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"pairwise");
+		Samaritan::<T>::register_pairwise_did(signed::<T>(&caller), did.clone(), app_did.clone(), H256::repeat_byte(1), bytes(T::MaxCallHashLength::get()))?;
+		let key: T::AccountId = account("key", 0, SEED);
+		let expires = frame_system::Pallet::<T>::block_number() + 100u32.into();
+	}: _(
+		SystemOrigin::Signed(caller),
+		did,
+		app_did,
+		key.clone(),
+		list(T::MaxSessionCalls::get(), T::MaxCallNameLength::get()),
+		expires,
+		u32::MAX
+	)
+	verify {
+		assert!(SessionKeys::<T>::contains_key(&key));
+	}
+
+	revoke_session_key {
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&caller, &did)?;
+		let key: T::AccountId = account("key", 0, SEED);
+		let expires = frame_system::Pallet::<T>::block_number() + 100u32.into();
+		Samaritan::<T>::authorize_session_key(
+			signed::<T>(&caller),
+			did,
+			app_did,
+			key.clone(),
+			list(T::MaxSessionCalls::get(), T::MaxCallNameLength::get()),
+			expires,
+			u32::MAX,
+		)?;
+	}: _(SystemOrigin::Signed(caller), key.clone())
+	verify {
+		assert!(!SessionKeys::<T>::contains_key(&key));
+	}
+
+	session_call {
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+		let user = funded::<T>("user", 0);
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&user, &did)?;
+		grant_all::<T>(&user, &did, &app_did)?;
+
+		// the allowed call is the last one checked
+		let mut calls: BoundedVec<BoundedVec<u8, T::MaxCallNameLength>, T::MaxSessionCalls> =
+			list(T::MaxSessionCalls::get() - 1, T::MaxCallNameLength::get());
+		calls.try_push(bound(b"revoke_consent".to_vec())).map_err(|_| "too many session calls")?;
+		let key = funded_caller::<T>();
+		let expires = frame_system::Pallet::<T>::block_number() + 100u32.into();
+		Samaritan::<T>::authorize_session_key(signed::<T>(&user), did.clone(), app_did.clone(), key.clone(), calls, expires, u32::MAX)?;
+
+		let call: <T as Config>::RuntimeCall =
+			Call::<T>::revoke_consent { did: did.clone(), app_did: app_did.clone() }.into();
+	}: _(SystemOrigin::Signed(key), Box::new(call))
+	verify {
+		let grant = ConsentGrants::<T>::get(&did, &app_did).ok_or("grant missing")?;
+		assert!(grant.revoked);
+	}
+
+	create_plan {
+		let caller = funded_caller::<T>();
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&caller, &app_did)?;
+	}: _(SystemOrigin::Signed(caller), app_did.clone(), 1_000u32.into(), 10u32.into(), 10u32.into())
+	verify {
+		assert!(SubscriptionPlans::<T>::contains_key(&app_did, 0));
+	}
+
+	retire_plan {
+		let caller = funded_caller::<T>();
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&caller, &app_did)?;
+		Samaritan::<T>::create_plan(signed::<T>(&caller), app_did.clone(), 1_000u32.into(), 10u32.into(), 10u32.into())?;
+	}: _(SystemOrigin::Signed(caller), app_did.clone(), 0)
+	verify {
+		let plan = SubscriptionPlans::<T>::get(&app_did, 0).ok_or("plan missing")?;
+		assert!(!plan.active);
+	}
+
+	subscribe {
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+		let period: T::BlockNumber = 10u32.into();
+		Samaritan::<T>::create_plan(signed::<T>(&owner), app_did.clone(), 1_000u32.into(), period, period)?;
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&caller, &did)?;
+
+		// the charge joins an agenda with one free slot left
+		let due = frame_system::Pallet::<T>::block_number() + period;
+		for i in 1..T::MaxChargesPerBlock::get() {
+			Samaritan::<T>::schedule_charge(due, &did_of::<T>(&i.to_le_bytes()), &app_did)
+				.map_err(|_| "charge agenda full")?;
+		}
+	}: _(SystemOrigin::Signed(caller), did.clone(), app_did.clone(), 0, BalanceOf::<T>::max_value())
+	verify {
+		assert!(Subscriptions::<T>::contains_key(&did, &app_did));
+	}
+
+	cancel_subscription {
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&caller, &did)?;
+		subscription::<T>(&owner, &caller, &did, &app_did)?;
+	}: _(SystemOrigin::Signed(caller), did.clone(), app_did.clone())
+	verify {
+		let sub = Subscriptions::<T>::get(&did, &app_did).ok_or("subscription missing")?;
+		assert!(sub.cancelled);
+	}
+
+	set_sponsorship {
+		let caller = funded_caller::<T>();
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&caller, &app_did)?;
+	}: _(SystemOrigin::Signed(caller), app_did.clone(), list(T::MaxSponsoredCalls::get(), T::MaxCallNameLength::get()), u32::MAX)
+	verify {
+		assert!(Sponsorships::<T>::contains_key(&app_did));
+	}
+
+	fund_sponsorship {
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+		Samaritan::<T>::set_sponsorship(signed::<T>(&owner), app_did.clone(), bound(vec![bound(b"create_samaritan".to_vec())]), 1)?;
+		let caller = funded_caller::<T>();
+		let amount = T::Currency::minimum_balance() * 10u32.into();
+	}: _(SystemOrigin::Signed(caller), app_did.clone(), amount)
+	verify {
+		assert_eq!(T::Currency::free_balance(&Samaritan::<T>::sponsor_account(&app_did)), amount);
+	}
+
+	withdraw_sponsorship {
+		let caller = funded_caller::<T>();
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&caller, &app_did)?;
+		Samaritan::<T>::set_sponsorship(signed::<T>(&caller), app_did.clone(), bound(vec![bound(b"create_samaritan".to_vec())]), 1)?;
+		let amount = T::Currency::minimum_balance() * 10u32.into();
+		Samaritan::<T>::fund_sponsorship(signed::<T>(&caller), app_did.clone(), amount)?;
+	}: _(SystemOrigin::Signed(caller), app_did.clone(), amount)
+	verify {
+		assert!(T::Currency::free_balance(&Samaritan::<T>::sponsor_account(&app_did)).is_zero());
+	}
+
+	add_did_key {
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&caller, &did)?;
+		for i in 1..T::MaxDidKeys::get() {
+			Samaritan::<T>::add_did_key(signed::<T>(&caller), did.clone(), account("key", i, SEED))?;
+		}
+		let key: T::AccountId = account("key", 0, SEED);
+	}: _(SystemOrigin::Signed(caller), did.clone(), key)
+	verify {
+		assert_eq!(DidKeys::<T>::get(&did).len() as u32, T::MaxDidKeys::get());
+	}
+
+	remove_did_key {
+		let caller = funded_caller::<T>();
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&caller, &did)?;
+		for i in 0..T::MaxDidKeys::get() {
+			Samaritan::<T>::add_did_key(signed::<T>(&caller), did.clone(), account("key", i, SEED))?;
+		}
+		let key: T::AccountId = account("key", T::MaxDidKeys::get() - 1, SEED);
+	}: _(SystemOrigin::Signed(caller), did.clone(), key)
+	verify {
+		assert_eq!(DidKeys::<T>::get(&did).len() as u32, T::MaxDidKeys::get() - 1);
+	}
+
+	submit_did_call {
+		let k in 1 .. T::MaxDidKeys::get();
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+		let user = funded::<T>("user", 0);
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&user, &did)?;
+		grant_all::<T>(&user, &did, &app_did)?;
+
+		// the signing key is the last one tried
+		for i in 1..k {
+			Samaritan::<T>::add_did_key(signed::<T>(&user), did.clone(), account("key", i, SEED))?;
+		}
+		let public = sp_io::crypto::sr25519_generate(DID_KEY, None);
+		Samaritan::<T>::add_did_key(signed::<T>(&user), did.clone(), public.clone().into())?;
+
+		let call: <T as Config>::RuntimeCall =
+			Call::<T>::revoke_consent { did: did.clone(), app_did: app_did.clone() }.into();
+		let expires = frame_system::Pallet::<T>::block_number() + 100u32.into();
+		let payload = Samaritan::<T>::did_call_payload(&did, 0, expires, &call);
+		let signature = sp_io::crypto::sr25519_sign(DID_KEY, &public, &payload).ok_or("signing failed")?;
+		let caller = funded_caller::<T>();
+	}: _(SystemOrigin::Signed(caller), did.clone(), Box::new(call), 0, expires, signature.into())
+	verify {
+		assert_eq!(DidNonces::<T>::get(&did), 1);
+		let grant = ConsentGrants::<T>::get(&did, &app_did).ok_or("grant missing")?;
+		assert!(grant.revoked);
+	}
+
+	charge_subscription {
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+		let user = funded::<T>("user", 0);
+		let did = did_of::<T>(b"user");
+		add_samaritan::<T>(&user, &did)?;
+		let due = subscription::<T>(&owner, &user, &did, &app_did)?;
+		frame_system::Pallet::<T>::set_block_number(due);
+	}: {
+		Samaritan::<T>::on_initialize(due);
+	}
+	verify {
+		let sub = Subscriptions::<T>::get(&did, &app_did).ok_or("subscription missing")?;
+		assert!(sub.next_charge > due);
+	}
+
+	set_parameters {
+		let mut parameters = Parameters::<T>::get();
+		parameters.quorum_cap = T::MaxQuorumMembersCount::get();
+		parameters.signup_token_ttl = Some(100u32.into());
+		let origin = T::AdminOrigin::successful_origin();
+	}: {
+		Samaritan::<T>::set_parameters(origin, parameters.clone())?;
+	}
+	verify {
+		assert_eq!(Parameters::<T>::get(), parameters);
+	}
+
+	approve_pairwise_recovery {
+		let owner = funded::<T>("owner", 0);
+		let app_did = did_of::<T>(b"app");
+		add_app::<T>(&owner, &app_did)?;
+
+		// the last approval a full trust quorum needs hands the DID over
+		let caller = funded_caller::<T>();
+		let helper_did = did_of::<T>(b"helper");
+		add_samaritan::<T>(&caller, &helper_did)?;
+		let master_owner = funded::<T>("master", 0);
+		let master = did_of::<T>(b"master");
+		add_samaritan::<T>(&master_owner, &master)?;
+		let members = fill_quorum::<T>(&master_owner, &master, T::MaxQuorumMembersCount::get() - 1)?;
+		Samaritan::<T>::update_quorum(signed::<T>(&master_owner), DidRef::Did(master.clone()), DidRef::Did(helper_did))?;
+
+		let did = did_of::<T>(b"pairwise");
+		let lost = funded::<T>("lost", 0);
+		Samaritan::<T>::register_pairwise_did(signed::<T>(&lost), did.clone(), app_did, H256::repeat_byte(1), bytes(T::MaxCallHashLength::get()))?;
+		let new_owner = funded::<T>("new", 0);
+		let approvals = members
+			.iter()
+			.take(T::MaxQuorumMembersCount::get() as usize / 2)
+			.filter_map(DidIndices::<T>::get)
+			.collect::<Vec<_>>();
+		PairwiseRecoveries::<T>::insert(&did, PairwiseRecovery {
+			master_did: master,
+			new_owner: new_owner.clone(),
+			approvals: approvals.try_into().map_err(|_| "too many approvals")?
+		});
+	}: _(SystemOrigin::Signed(caller), did.clone())
+	verify {
+		assert_eq!(DidOwners::<T>::get(&did), Some(new_owner));
+		assert!(!PairwiseRecoveries::<T>::contains_key(&did));
+	}
+
+	impl_benchmark_test_suite!(Samaritan, crate::mock::new_test_ext(), crate::mock::Test);
+}
@@ -0,0 +1,281 @@
+//! Random sequences of samaritan calls against the mock runtime.
+//!
+//! `run` reads its input four bytes at a time (operation, signer, DID, argument), dispatches the
+//! call and checks the pallet invariants after every step. It backs both the proptest suite and
+//! the cargo-fuzz target in `fuzz/`. Failed calls are expected, panics and broken invariants are not.
+
+use crate::{
+	mock::*, AppStatus, Call, ChargeAgenda, DidRef, DocMetaRegistry, Parameters, Quorums, ReleaseChannel,
+	SamaritanParameters, StorageDeposits, Subscriptions,
+};
+use frame_support::{traits::Get, BoundedVec};
+use sp_core::{sr25519, Pair, H256};
+use sp_runtime::{traits::Dispatchable, MultiSignature};
+use std::collections::BTreeMap;
+
+const ACCOUNTS: [AccountId; 5] = [ALICE, BOB, CHARLIE, DAVE, EVE];
+
+/// a small pool so calls keep hitting the same entries, the last DID is too long
+const DIDS: [&[u8]; 6] = [
+	b"did:sam:alice",
+	b"did:sam:bob",
+	b"did:sam:app",
+	b"did:sam:other-app",
+	b"did:sam:pairwise",
+	&[b'd'; 65],
+];
+
+const SCOPES: [&[u8]; 2] = [b"profile", b"email"];
+
+fn account(byte: u8) -> AccountId {
+	ACCOUNTS[byte as usize % ACCOUNTS.len()].clone()
+}
+
+fn pick_did(byte: u8) -> Vec<u8> {
+	DIDS[byte as usize % DIDS.len()].to_vec()
+}
+
+/// content identifiers of varying length, some over `MaxCallHashLength`
+fn hash(byte: u8) -> Vec<u8> {
+	vec![b'h'; byte as usize % 70]
+}
+
+/// `None` when the input is over its bound, such a call does not decode
+fn bound<V, S: Get<u32>>(value: Vec<V>) -> Option<BoundedVec<V, S>> {
+	value.try_into().ok()
+}
+
+fn scopes<S: Get<u32>, L: Get<u32>>(byte: u8) -> Option<BoundedVec<BoundedVec<u8, S>, L>> {
+	let scopes = SCOPES.iter().take(byte as usize % (SCOPES.len() + 1)).map(|s| bound(s.to_vec()));
+	bound(scopes.collect::<Option<Vec<_>>>()?)
+}
+
+/// a master DID signature for a pairwise link, the same `arg` opens the commitment made with it
+fn master_signature(byte: u8) -> MultiSignature {
+	MultiSignature::Sr25519(sr25519::Signature::from_raw([byte; 64]))
+}
+
+/// a small pool of DID keys, so signed DID calls sometimes verify
+fn did_key(byte: u8) -> sr25519::Pair {
+	sr25519::Pair::from_seed(&[byte % 3 + 1; 32])
+}
+
+/// the current parameters with one of them changed, sometimes past its hard cap
+fn parameters(arg: u8) -> SamaritanParameters<Test> {
+	let mut parameters = Parameters::<Test>::get();
+	let value = (arg / 4) as u32;
+	match arg % 4 {
+		0 => parameters.quorum_cap = value % 5,
+		1 => parameters.history_window = value % 6,
+		2 => parameters.signup_token_ttl = (value % 4 != 0).then(|| (value % 16) as u64),
+		_ => parameters.deposit_per_byte = (value % 3) as u64,
+	}
+	parameters
+}
+
+/// the DID itself, or an index that may or may not be interned
+fn did_ref(byte: u8, did: BoundedVec<u8, <Test as crate::Config>::MaxDIDLength>) -> DidRef<Test> {
+	if byte % 4 == 0 {
+		DidRef::Index((byte / 4 % 8) as u64)
+	} else {
+		DidRef::Did(did)
+	}
+}
+
+/// the samaritan call for an operation byte, `None` when part of its input is over a bound
+fn call(op: u8, did_byte: u8, arg: u8) -> Option<Call<Test>> {
+	let did = bound(pick_did(did_byte))?;
+	let other = bound(pick_did(arg))?;
+
+	let call = match op % 41 {
+		0 => Call::create_samaritan { name: bound(b"samaritan".to_vec())?, did, meta_hash: bound(hash(arg))? },
+		1 => Call::rename_samaritan { name: bound(vec![b'n'; arg as usize % 40])? },
+		2 => Call::alter_state { did, state: arg % 2 == 0 },
+		3 => Call::update_document { did, doc: bound(hash(arg))? },
+		4 => Call::update_quorum { did: did_ref(arg, did), trust_did: DidRef::Did(other) },
+		5 => Call::filter_quorum { did: DidRef::Did(did), trust_did: did_ref(arg / 2, other) },
+		6 => Call::update_profile { did, profile_hash: bound(hash(arg))? },
+		7 => Call::create_app {
+			did,
+			meta_hash: bound(hash(arg))?,
+			name: bound(b"app".to_vec())?,
+			logo: bound(hash(arg.wrapping_add(1)))?,
+			redirect_uris: bound(vec![bound(b"https://app.example/cb".to_vec())?])?,
+			scopes: scopes(SCOPES.len() as u8)?,
+		},
+		8 => Call::update_app {
+			did,
+			name: bound(b"app".to_vec())?,
+			logo: bound(hash(arg))?,
+			redirect_uris: Default::default(),
+			scopes: scopes(arg)?,
+		},
+		9 => Call::set_app_status {
+			did,
+			status: if arg % 2 == 0 { AppStatus::Active } else { AppStatus::Suspended },
+		},
+		10 => Call::retire_app { did },
+		11 => Call::generate_token {
+			commitment: Samaritan::token_commitment(&H256::repeat_byte(arg), &did, &other),
+			app_did: did,
+			did: other,
+		},
+		// the same `arg` reveals the token committed to above
+		12 => Call::redeem_token { app_did: did, did: other, token: H256::repeat_byte(arg) },
+		13 => Call::grant_consent {
+			did,
+			app_did: other,
+			scopes: scopes(arg)?,
+			expires_at: (arg % 3 == 0).then(|| Now::get() + arg as u64),
+		},
+		14 => Call::revoke_consent { did, app_did: other },
+		15 => Call::register_pairwise_did {
+			link_commitment: Samaritan::pairwise_link(&did, &other, &master_signature(arg)),
+			did,
+			app_did: bound(pick_did(arg / 8))?,
+			recovery_escrow: bound(hash(arg))?,
+		},
+		16 if arg % 2 == 0 => Call::recover_pairwise_did {
+			did,
+			master_did: other,
+			master_signature: master_signature(arg),
+			new_owner: account(arg),
+		},
+		16 => Call::approve_pairwise_recovery { did },
+		17 => Call::publish_release {
+			app_did: did,
+			version: bound(vec![b'0' + arg % 8])?,
+			manifest: bound(hash(arg))?,
+			permissions: scopes(arg)?,
+			signing_key: H256::repeat_byte(arg),
+			channel: if arg % 2 == 0 { ReleaseChannel::Stable } else { ReleaseChannel::Beta },
+		},
+		18 => Call::yank_release { app_did: did, version: bound(vec![b'0' + arg % 8])? },
+		19 => Call::set_vault_pointer { app_did: did, did: other, cid: bound(hash(arg))? },
+		20 => Call::erase_vault_pointer { did, app_did: other },
+		21 => Call::log_access { app_did: did, did: other, scope: bound(b"profile".to_vec())?, purpose: arg as u32 },
+		22 => Call::authorize_session_key {
+			did,
+			app_did: other,
+			key: account(arg),
+			calls: bound(vec![bound(b"update_profile".to_vec())?, bound(b"update_document".to_vec())?])?,
+			expires: System::block_number() + (arg % 10) as u64,
+			max_calls: (arg % 3) as u32,
+		},
+		23 => Call::session_call {
+			call: Box::new(RuntimeCall::Samaritan(Call::update_profile { did, profile_hash: bound(hash(arg))? })),
+		},
+		24 => Call::create_plan { app_did: did, price: arg as u64 * 10, period: (arg % 5) as u64, grace: (arg % 3) as u64 },
+		25 => Call::subscribe { did, app_did: other, plan_id: (arg % 2) as u32, cap: arg as u64 * 20 },
+		26 => Call::cancel_subscription { did, app_did: other },
+		27 => Call::set_sponsorship {
+			app_did: did,
+			calls: bound(vec![bound(b"update_profile".to_vec())?])?,
+			max_per_account: arg as u32,
+		},
+		28 => Call::fund_sponsorship { app_did: did, amount: arg as u64 * 10 },
+		29 => Call::add_did_key { did, key: did_key(arg).public().into() },
+		30 => Call::remove_did_key { did, key: did_key(arg).public().into() },
+		// 31 moves time forward in `run`
+		32 => Call::set_parameters { parameters: parameters(arg) },
+		33 => {
+			let call = RuntimeCall::Samaritan(Call::update_profile { did: did.clone(), profile_hash: bound(hash(arg))? });
+			// sometimes a stale nonce or an expired call
+			let nonce = Samaritan::did_nonce(&did) + (arg % 5 == 0) as u64;
+			let expires = (System::block_number() + (arg % 4) as u64).saturating_sub((arg % 7 == 0) as u64);
+			let signature = did_key(arg / 2).sign(&Samaritan::did_call_payload(&did, nonce, expires, &call)).into();
+			Call::submit_did_call { did, call: Box::new(call), nonce, expires, signature }
+		},
+		34 => Call::revoke_session_key { key: account(arg) },
+		35 => Call::withdraw_sponsorship { app_did: did, amount: arg as u64 * 10 },
+		36 => Call::retire_plan { app_did: did, plan_id: (arg % 2) as u32 },
+		37 => Call::set_release_channel {
+			app_did: did,
+			version: bound(vec![b'0' + arg % 8])?,
+			channel: if arg % 2 == 0 { ReleaseChannel::Stable } else { ReleaseChannel::Beta },
+		},
+		38 => Call::verify_app { did, evidence: H256::repeat_byte(arg) },
+		39 => Call::revoke_app_verification { did, evidence: H256::repeat_byte(arg) },
+		40 => Call::reap_signup_token { app_did: did, did: other },
+		_ => return None,
+	};
+
+	Some(call)
+}
+
+/// dispatch the calls encoded in `data`, panicking on the first broken invariant
+pub fn run(data: &[u8]) {
+	new_test_ext().execute_with(|| {
+		for step in data.chunks_exact(4) {
+			let (op, signer, did_byte, arg) = (step[0], step[1], step[2], step[3]);
+
+			match op % 41 {
+				31 if arg % 2 == 0 => run_to_block(System::block_number() + 1 + (arg % 8) as u64),
+				31 => Now::set(Now::get() + arg as u64),
+				// failed calls are rolled back like on chain, oversized input never reaches the pallet
+				_ => if let Some(call) = call(op, did_byte, arg) {
+					// app status and verification are set by the verifier origin, parameters by the admin origin
+					let origin = match call {
+						Call::set_app_status { .. }
+						| Call::verify_app { .. }
+						| Call::revoke_app_verification { .. }
+						| Call::set_parameters { .. } => RuntimeOrigin::root(),
+						_ => RuntimeOrigin::signed(account(signer)),
+					};
+					let _ = RuntimeCall::Samaritan(call).dispatch(origin);
+				},
+			}
+
+			if let Err(e) = check_invariants() {
+				panic!("invariant broken after step {:?}: {}", step, e);
+			}
+		}
+	});
+}
+
+/// invariants that must hold after every call, on top of the pallet's own `try_state` checks
+pub fn check_invariants() -> Result<(), &'static str> {
+	Samaritan::do_try_state()?;
+
+	for (_, docs) in DocMetaRegistry::<Test>::iter() {
+		// the oldest versions roll out of the history window
+		if docs.windows(2).any(|w| w[1].version != w[0].version + 1) {
+			return Err("DID document versions are not contiguous");
+		}
+		// the latest document may be disabled through `alter_state`, older ones are never active
+		if docs.iter().rev().skip(1).any(|doc| doc.active) {
+			return Err("superseded DID document is active");
+		}
+	}
+
+	for (_, quorum) in Quorums::<Test>::iter() {
+		if quorum.len() > <Test as crate::Config>::MaxQuorumMembersCount::get() as usize {
+			return Err("trust quorum over its bound");
+		}
+	}
+
+	let mut held: BTreeMap<AccountId, Balance> = BTreeMap::new();
+	for (_, (who, deposit)) in StorageDeposits::<Test>::iter() {
+		*held.entry(who).or_default() += deposit;
+	}
+	for who in ACCOUNTS {
+		if Balances::reserved_balance(&who) != held.get(&who).copied().unwrap_or_default() {
+			return Err("reserved balance does not match storage deposits");
+		}
+	}
+
+	let now = System::block_number();
+	for (did, app_did, sub) in Subscriptions::<Test>::iter() {
+		if sub.spent > sub.cap {
+			return Err("subscription spent over its cap");
+		}
+		if sub.next_charge <= now {
+			return Err("subscription charge is overdue");
+		}
+		if !ChargeAgenda::<Test>::get(sub.next_charge).contains(&(did, app_did)) {
+			return Err("subscription is missing from the charge agenda");
+		}
+	}
+
+	Ok(())
+}
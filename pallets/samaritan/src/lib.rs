@@ -2,21 +2,65 @@
 
 pub use pallet::*;
 
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod mock;
+
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzzing;
+
+#[cfg(test)]
+mod tests;
+
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+pub mod migrations;
+
+mod sponsor;
+pub use sponsor::ChargeSponsoredTransactionPayment;
+
+pub mod weights;
+pub use weights::WeightInfo;
+
 use scale_info::prelude::vec::Vec;
 use scale_info::prelude::string::String;
+use codec::Encode;
+use frame_support::{
+	ensure,
+	pallet_prelude::DispatchResult,
+	traits::{Currency, ExistenceRequirement, Get, ReservableCurrency},
+	BoundedVec,
+};
+use frame_system::{ensure_signed, pallet_prelude::OriginFor};
+use sp_core::H256;
+use sp_runtime::traits::{AccountIdConversion, One, Saturating, Verify, Zero};
 
 #[frame_support::pallet]
 pub mod pallet {
-	use frame_support::{pallet_prelude::{*, DispatchResult}, BoundedVec};
+	use frame_support::{
+		dispatch::{GetDispatchInfo, PostDispatchInfo},
+		pallet_prelude::{*, DispatchResult},
+		traits::{GetCallName, IsSubType},
+		BoundedVec, CloneNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound,
+	};
 	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::{Dispatchable, IdentifyAccount, Saturating, Verify, Zero};
+	use sp_std::boxed::Box;
+	use crate::weights::WeightInfo;
 
 	use scale_info::prelude::vec::Vec;
 	use sp_core::H256;
 
-	use frame_support::traits::UnixTime;
+	use frame_support::{traits::{Currency, ExistenceRequirement, ReservableCurrency, UnixTime}, PalletId};
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	/// a DID, longer input fails to decode
+	pub type Did<T> = BoundedVec<u8, <T as Config>::MaxDIDLength>;
+
+	/// content identifier or hash of off-chain data, longer input fails to decode
+	pub type ContentHash<T> = BoundedVec<u8, <T as Config>::MaxCallHashLength>;
 
 	// important structs
 	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
@@ -34,13 +78,269 @@ pub mod pallet {
 		pub version: u64,
 		pub hl: BoundedVec<u8, T::MaxHashLength>,
 		pub created: u64,
+		pub active: bool,
+		/// last time the document was enabled or disabled
+		pub updated: u64,
+		/// block the document was published in
+		pub block: T::BlockNumber
+	}
+
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum AppStatus {
+		Active,
+		Suspended
+	}
+
+	/// publisher verification granted by governance
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum Verification {
+		Unverified,
+		Verified { evidence: H256, at: u64 },
+		Revoked { evidence: H256, at: u64 }
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct AppInfo<T: Config> {
+		pub owner: T::AccountId,
+		pub name: BoundedVec<u8, T::MaxNameLength>,
+		/// content identifier of the app logo
+		pub logo: BoundedVec<u8, T::MaxHashLength>,
+		/// redirect URIs and origins the login flow may return to
+		pub redirect_uris: BoundedVec<BoundedVec<u8, T::MaxUriLength>, T::MaxRedirectUris>,
+		/// data scopes the app asks users for
+		pub scopes: BoundedVec<BoundedVec<u8, T::MaxScopeLength>, T::MaxScopes>,
+		pub status: AppStatus,
+		pub verification: Verification,
+		pub created: u64
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct SignUpToken<T: Config> {
+		/// blake2(token ++ app DID ++ user DID)
+		pub commitment: H256,
+		/// block the token was issued in
+		pub issued: T::BlockNumber
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct ConsentGrant<T: Config> {
+		/// profile fields and credential types the user agreed to share
+		pub scopes: BoundedVec<BoundedVec<u8, T::MaxScopeLength>, T::MaxScopes>,
+		pub granted_at: u64,
+		pub expires_at: Option<u64>,
+		/// revoked grants are kept as a receipt
+		pub revoked: bool
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct PairwiseRecord<T: Config> {
+		/// the only app this DID may be used with
+		pub app_did: BoundedVec<u8, T::MaxDIDLength>,
+		/// blake2 of the pairwise DID, master DID and the master's signature over the pairing,
+		/// the hidden link to the master identity
+		pub link_commitment: H256,
+		/// content identifier of the link proof encrypted to the trust quorum
+		pub recovery_escrow: BoundedVec<u8, T::MaxHashLength>,
+		pub created: u64
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct PairwiseRecovery<T: Config> {
+		/// master DID whose trust quorum approves the handover
+		pub master_did: BoundedVec<u8, T::MaxDIDLength>,
+		pub new_owner: T::AccountId,
+		/// quorum members that approved so far, by interned index
+		pub approvals: BoundedVec<DidIndex, T::MaxQuorumMembersCount>
+	}
+
+	/// compact identifier of an interned DID. Only trust quorums are stored and listed by index,
+	/// the other maps, events and calls keep the full DID.
+	pub type DidIndex = u64;
+
+	/// a DID given in full or by its interned index
+	#[derive(CloneNoBound, Encode, Decode, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub enum DidRef<T: Config> {
+		Did(Did<T>),
+		Index(DidIndex)
+	}
+
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum ReleaseChannel {
+		Stable,
+		Beta
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct Release<T: Config> {
+		pub version: BoundedVec<u8, T::MaxVersionLength>,
+		/// content identifier of the release manifest
+		pub manifest: BoundedVec<u8, T::MaxHashLength>,
+		/// permissions the release declares
+		pub permissions: BoundedVec<BoundedVec<u8, T::MaxScopeLength>, T::MaxScopes>,
+		/// public key the release artifacts are signed with
+		pub signing_key: H256,
+		pub channel: ReleaseChannel,
+		pub yanked: bool,
+		pub published: u64
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct VaultPointer<T: Config> {
+		/// content identifier of the encrypted user data blob
+		pub cid: BoundedVec<u8, T::MaxHashLength>,
+		pub version: u64,
+		pub updated: u64
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct AccessRecord<T: Config> {
+		/// position in the user's access history, keeps counting when old records are dropped
+		pub seq: u64,
+		pub app_did: BoundedVec<u8, T::MaxDIDLength>,
+		pub scope: BoundedVec<u8, T::MaxScopeLength>,
+		pub purpose: u32,
+		pub timestamp: u64
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct SessionKey<T: Config> {
+		/// user DID the key acts for
+		pub did: BoundedVec<u8, T::MaxDIDLength>,
+		/// app the key was issued to
+		pub app_did: BoundedVec<u8, T::MaxDIDLength>,
+		/// names of the samaritan calls the key may make
+		pub calls: BoundedVec<BoundedVec<u8, T::MaxCallNameLength>, T::MaxSessionCalls>,
+		pub expires: T::BlockNumber,
+		/// calls left before the key is used up
+		pub remaining: u32
+	}
+
+	/// origin of calls dispatched on behalf of a DID
+	#[derive(CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, Encode, Decode, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub enum RawOrigin<T: Config> {
+		Did(BoundedVec<u8, T::MaxDIDLength>),
+		/// a DID acting through a session key, limited to the app the key was issued to
+		Session {
+			did: BoundedVec<u8, T::MaxDIDLength>,
+			app_did: BoundedVec<u8, T::MaxDIDLength>
+		}
+	}
+
+	#[pallet::origin]
+	pub type Origin<T> = RawOrigin<T>;
+
+	/// a recurring price an app charges its subscribers
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct SubscriptionPlan<T: Config> {
+		pub price: BalanceOf<T>,
+		/// blocks between charges
+		pub period: T::BlockNumber,
+		/// blocks a failed charge is retried after before the subscription ends
+		pub grace: T::BlockNumber,
+		/// retired plans take no new subscribers and end existing ones at their next charge
 		pub active: bool
 	}
 
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct Subscription<T: Config> {
+		pub plan_id: u32,
+		/// account charged every period
+		pub payer: T::AccountId,
+		/// most the subscription may ever charge in total
+		pub cap: BalanceOf<T>,
+		pub spent: BalanceOf<T>,
+		pub next_charge: T::BlockNumber,
+		/// the last charge failed and is being retried after the grace period
+		pub past_due: bool,
+		/// the subscription ends at the close of the paid period
+		pub cancelled: bool
+	}
+
+	/// calls an app pays the fees of for its users
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct Sponsorship<T: Config> {
+		/// names of the samaritan calls that are sponsored
+		pub calls: BoundedVec<BoundedVec<u8, T::MaxCallNameLength>, T::MaxSponsoredCalls>,
+		/// sponsored transactions allowed per account
+		pub max_per_account: u32
+	}
+
+	/// free samaritan calls an account has made in the current quota period
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct FreeQuota<T: Config> {
+		pub period_start: T::BlockNumber,
+		pub used: u32
+	}
+
+	/// soft limits the admin origin can tune without a runtime upgrade, capped by the `Config` bounds
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct SamaritanParameters<T: Config> {
+		/// members a trust quorum can have, at most `MaxQuorumMembersCount`
+		pub quorum_cap: u32,
+		/// latest DID document versions kept per DID, at most `MaxCacheLength`
+		pub history_window: u32,
+		/// blocks an unredeemed signup token stays valid for, `None` for no expiry
+		pub signup_token_ttl: Option<T::BlockNumber>,
+		/// seconds a consent given without an expiry lasts, `None` for no expiry
+		pub consent_ttl: Option<u64>,
+		/// deposit held for every samaritan storage entry
+		pub deposit_base: BalanceOf<T>,
+		/// deposit held for every encoded byte of a samaritan storage entry
+		pub deposit_per_byte: BalanceOf<T>
+	}
+
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+		/// the overarching origin, able to carry DID origins
+		type RuntimeOrigin: From<RawOrigin<Self>>
+			+ IsType<<Self as frame_system::Config>::RuntimeOrigin>
+			+ Into<Result<RawOrigin<Self>, <Self as Config>::RuntimeOrigin>>;
+		/// the overarching call, dispatched for users by their session keys
+		type RuntimeCall: Parameter
+			+ Dispatchable<RuntimeOrigin = <Self as frame_system::Config>::RuntimeOrigin, PostInfo = PostDispatchInfo>
+			+ GetDispatchInfo
+			+ From<Call<Self>>
+			+ IsSubType<Call<Self>>
+			+ IsType<<Self as frame_system::Config>::RuntimeCall>;
 		type TimeProvider: UnixTime;
+		/// origin allowed to grant and revoke app publisher verification
+		type VerifierOrigin: EnsureOrigin<<Self as frame_system::Config>::RuntimeOrigin>;
+		/// origin allowed to update the soft limits in `Parameters`
+		type AdminOrigin: EnsureOrigin<<Self as frame_system::Config>::RuntimeOrigin>;
+		/// weight information for the calls of this pallet
+		type WeightInfo: WeightInfo;
 
 		#[pallet::constant]
 		type MaxDIDLength: Get<u32>;
@@ -48,19 +348,111 @@ pub mod pallet {
 		#[pallet::constant]
 		type MaxNameLength: Get<u32>;
 		
+		/// longest content hash kept in storage, entries written under earlier bounds included
 		#[pallet::constant]
 		type MaxHashLength: Get<u32>;
 
+		/// longest content hash the calls accept, at most `MaxHashLength`
+		#[pallet::constant]
+		type MaxCallHashLength: Get<u32>;
+
+		/// upper cap of the DID document history window
 		#[pallet::constant]
 		type MaxCacheLength: Get<u32>;
 
+		/// upper cap of the trust quorum size
 		#[pallet::constant]
 		type MaxQuorumMembersCount: Get<u32>;
 
+		/// trust quorum size until the admin origin sets `Parameters`
+		#[pallet::constant]
+		type DefaultQuorumCap: Get<u32>;
+
+		#[pallet::constant]
+		type MaxUriLength: Get<u32>;
+
+		#[pallet::constant]
+		type MaxRedirectUris: Get<u32>;
+
+		#[pallet::constant]
+		type MaxScopeLength: Get<u32>;
+
+		#[pallet::constant]
+		type MaxScopes: Get<u32>;
+
+		#[pallet::constant]
+		type MaxVersionLength: Get<u32>;
+
+		/// number of releases kept per app, the oldest is dropped when full
+		#[pallet::constant]
+		type MaxReleases: Get<u32>;
+
+		/// number of access records kept per user, the oldest is dropped when full
+		#[pallet::constant]
+		type MaxAccessRecords: Get<u32>;
+
+		#[pallet::constant]
+		type MaxCallNameLength: Get<u32>;
+
+		/// number of calls a session key can be allowed to make
+		#[pallet::constant]
+		type MaxSessionCalls: Get<u32>;
+
+		/// currency app subscriptions are paid and storage deposits are held in
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// deposit held for every samaritan storage entry until the admin origin sets `Parameters`
+		#[pallet::constant]
+		type DepositBase: Get<BalanceOf<Self>>;
+
+		/// deposit held for every encoded byte of a samaritan storage entry until the admin origin sets `Parameters`
+		#[pallet::constant]
+		type DepositPerByte: Get<BalanceOf<Self>>;
+
+		/// subscription charges that can fall due in a single block
+		#[pallet::constant]
+		type MaxChargesPerBlock: Get<u32>;
+
+		/// id sponsorship accounts are derived from
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// number of calls an app can sponsor
+		#[pallet::constant]
+		type MaxSponsoredCalls: Get<u32>;
+
+		/// fee-less samaritan calls a registered samaritan gets every quota period
+		#[pallet::constant]
+		type FreeCallsPerPeriod: Get<u32>;
+
+		/// blocks after which the free call quota refills
+		#[pallet::constant]
+		type FreeCallPeriod: Get<Self::BlockNumber>;
+
+		/// heaviest call that can be made from the free quota
+		#[pallet::constant]
+		type MaxFreeCallWeight: Get<Weight>;
+
+		/// longest encoded transaction that can be made from the free quota
+		#[pallet::constant]
+		type MaxFreeCallLength: Get<u32>;
+
+		/// signature made with a DID verification key
+		type DidSignature: Parameter + Verify<Signer = Self::DidSigner>;
+		/// signer of DID signatures, identified by the account id of its public key
+		type DidSigner: IdentifyAccount<AccountId = Self::AccountId>;
+
+		/// number of verification keys a DID can register
+		#[pallet::constant]
+		type MaxDidKeys: Get<u32>;
 	}
 
+	/// the in-code storage version
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(6);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::storage]
@@ -69,19 +461,282 @@ pub mod pallet {
 
 	#[pallet::storage]
 	#[pallet::getter(fn doc_metareg)]
-	pub(super) type DocMetaRegistry<T: Config> = StorageMap<_, Twox64Concat, BoundedVec<u8, T::MaxDIDLength>, BoundedVec<DocMetadata<T>, T::MaxCacheLength>>;
+	pub(super) type DocMetaRegistry<T: Config> = StorageMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, BoundedVec<DocMetadata<T>, T::MaxCacheLength>>;
 
 	#[pallet::storage]
 	#[pallet::getter(fn prof_reg)]
-	pub(super) type ProfileRegistry<T: Config> = StorageMap<_, Twox64Concat, BoundedVec<u8, T::MaxDIDLength>, BoundedVec<u8, T::MaxHashLength>>;
+	pub(super) type ProfileRegistry<T: Config> = StorageMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, BoundedVec<u8, T::MaxHashLength>>;
+
+	/// trust quorum of each samaritan, keyed and listed by interned DID
+	#[pallet::storage]
+	#[pallet::getter(fn quorum)]
+	pub(super) type Quorums<T: Config> = StorageMap<_, Twox64Concat, DidIndex, BoundedVec<DidIndex, T::MaxQuorumMembersCount>>;
+
+	/// index of each interned DID
+	#[pallet::storage]
+	#[pallet::getter(fn did_index)]
+	pub(super) type DidIndices<T: Config> = StorageMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, DidIndex>;
+
+	/// the DID behind each index, indices are never reused
+	#[pallet::storage]
+	#[pallet::getter(fn indexed_did)]
+	pub(super) type IndexedDids<T: Config> = StorageMap<_, Twox64Concat, DidIndex, BoundedVec<u8, T::MaxDIDLength>>;
+
+	/// index the next interned DID gets
+	#[pallet::storage]
+	#[pallet::getter(fn next_did_index)]
+	pub(super) type NextDidIndex<T: Config> = StorageValue<_, DidIndex, ValueQuery>;
 
 	#[pallet::storage]
-	#[pallet::getter(fn trust_quorum)]
-	pub(super) type TrustQuorum<T: Config> = StorageMap<_, Twox64Concat, BoundedVec<u8, T::MaxDIDLength>, BoundedVec<BoundedVec<u8, T::MaxDIDLength>, T::MaxQuorumMembersCount>>;
+	#[pallet::getter(fn app_reg)]
+	pub(super) type AppRegistry<T: Config> = StorageMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, AppInfo<T>>;
 
+	/// salted commitments to signup tokens, keyed by app DID then user DID
 	#[pallet::storage]
 	#[pallet::getter(fn signup_data)]
-	pub(super) type SignUpDataRegistry<T: Config> = StorageDoubleMap<_, Twox64Concat, BoundedVec<u8, T::MaxDIDLength>, Twox64Concat, BoundedVec<u8, T::MaxDIDLength>, H256, ValueQuery>;
+	pub(super) type SignUpDataRegistry<T: Config> = StorageDoubleMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, SignUpToken<T>>;
+
+	/// the account controlling each samaritan DID
+	#[pallet::storage]
+	#[pallet::getter(fn did_owner)]
+	pub(super) type DidOwners<T: Config> = StorageMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, T::AccountId>;
+
+	/// consent given by a user to an app, keyed by user DID then app DID
+	#[pallet::storage]
+	#[pallet::getter(fn consent_grants)]
+	pub(super) type ConsentGrants<T: Config> = StorageDoubleMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, ConsentGrant<T>>;
+
+	/// head of a hash chain over the grants replaced by a new consent, keyed by user DID then app DID
+	#[pallet::storage]
+	#[pallet::getter(fn consent_history)]
+	pub(super) type ConsentHistory<T: Config> = StorageDoubleMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, H256>;
+
+	/// per-app pseudonymous DIDs
+	#[pallet::storage]
+	#[pallet::getter(fn pairwise_dids)]
+	pub(super) type PairwiseDids<T: Config> = StorageMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, PairwiseRecord<T>>;
+
+	/// pairwise DID recoveries waiting for trust quorum approvals
+	#[pallet::storage]
+	#[pallet::getter(fn pairwise_recoveries)]
+	pub(super) type PairwiseRecoveries<T: Config> = StorageMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, PairwiseRecovery<T>>;
+
+	/// release history of each app, oldest first
+	#[pallet::storage]
+	#[pallet::getter(fn app_releases)]
+	pub(super) type AppReleases<T: Config> = StorageMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, BoundedVec<Release<T>, T::MaxReleases>, ValueQuery>;
+
+	/// every version an app has published, kept after the release leaves the window so it can't be published again
+	#[pallet::storage]
+	#[pallet::getter(fn published_version)]
+	pub(super) type PublishedVersions<T: Config> = StorageDoubleMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, Blake2_128Concat, BoundedVec<u8, T::MaxVersionLength>, (), OptionQuery>;
+
+	/// where apps keep encrypted user data off-chain, keyed by app DID then user DID
+	#[pallet::storage]
+	#[pallet::getter(fn data_vaults)]
+	pub(super) type DataVaults<T: Config> = StorageDoubleMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, VaultPointer<T>>;
+
+	/// ring buffer of app data accesses per user DID, oldest first
+	#[pallet::storage]
+	#[pallet::getter(fn access_records)]
+	pub(super) type AccessLog<T: Config> = StorageMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, BoundedVec<AccessRecord<T>, T::MaxAccessRecords>, ValueQuery>;
+
+	/// app session keys acting on behalf of user DIDs, keyed by the key's account
+	#[pallet::storage]
+	#[pallet::getter(fn session_keys)]
+	pub(super) type SessionKeys<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, SessionKey<T>>;
+
+	/// session epoch of a DID, bumped to revoke every session key issued for it
+	#[pallet::storage]
+	#[pallet::getter(fn session_epoch)]
+	pub(super) type SessionEpochs<T: Config> = StorageMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, u32, ValueQuery>;
+
+	/// session epoch of the DID a session key was issued in, the key only acts within it
+	#[pallet::storage]
+	pub(super) type SessionKeyEpochs<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+	/// subscription plans, keyed by app DID then plan id
+	#[pallet::storage]
+	#[pallet::getter(fn subscription_plan)]
+	pub(super) type SubscriptionPlans<T: Config> = StorageDoubleMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, Blake2_128Concat, u32, SubscriptionPlan<T>>;
+
+	/// id given to the next plan an app creates
+	#[pallet::storage]
+	#[pallet::getter(fn next_plan_id)]
+	pub(super) type NextPlanId<T: Config> = StorageMap<_, Twox64Concat, BoundedVec<u8, T::MaxDIDLength>, u32, ValueQuery>;
+
+	/// number of plans of each app that still take subscribers
+	#[pallet::storage]
+	#[pallet::getter(fn active_plans)]
+	pub(super) type ActivePlans<T: Config> = StorageMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, u32, ValueQuery>;
+
+	/// user subscriptions, keyed by user DID then app DID
+	#[pallet::storage]
+	#[pallet::getter(fn subscription)]
+	pub(super) type Subscriptions<T: Config> = StorageDoubleMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, Subscription<T>>;
+
+	/// (user DID, app DID) pairs whose subscription is due at a block
+	#[pallet::storage]
+	#[pallet::getter(fn charges_due)]
+	pub(super) type ChargeAgenda<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, BoundedVec<(BoundedVec<u8, T::MaxDIDLength>, BoundedVec<u8, T::MaxDIDLength>), T::MaxChargesPerBlock>, ValueQuery>;
+
+	/// fee sponsorships set up by apps
+	#[pallet::storage]
+	#[pallet::getter(fn sponsorship)]
+	pub(super) type Sponsorships<T: Config> = StorageMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, Sponsorship<T>>;
+
+	/// sponsored transactions made by each account, keyed by app DID then account
+	#[pallet::storage]
+	#[pallet::getter(fn sponsorship_usage)]
+	pub(super) type SponsorshipUsage<T: Config> = StorageDoubleMap<_, Twox64Concat, BoundedVec<u8, T::MaxDIDLength>, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+	/// free call usage of each account
+	#[pallet::storage]
+	#[pallet::getter(fn free_quota)]
+	pub(super) type FreeQuotas<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, FreeQuota<T>>;
+
+	/// keys able to sign operations for a DID
+	#[pallet::storage]
+	#[pallet::getter(fn did_keys)]
+	pub(super) type DidKeys<T: Config> = StorageMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, BoundedVec<T::AccountId, T::MaxDidKeys>, ValueQuery>;
+
+	/// nonce the next DID signed call must carry
+	#[pallet::storage]
+	#[pallet::getter(fn did_nonce)]
+	pub(super) type DidNonces<T: Config> = StorageMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxDIDLength>, u64, ValueQuery>;
+
+	/// deposits held for storage entries, keyed by the hash of the entry's storage key
+	#[pallet::storage]
+	#[pallet::getter(fn storage_deposit)]
+	pub(super) type StorageDeposits<T: Config> = StorageMap<_, Twox64Concat, H256, (T::AccountId, BalanceOf<T>)>;
+
+	/// progress of the v1 document translation, samaritan calls are paused while it is set
+	#[pallet::storage]
+	#[pallet::getter(fn doc_translate_progress)]
+	pub(super) type DocTranslateProgress<T: Config> = StorageValue<_, crate::migrations::v2::Cursor<T>>;
+
+	/// progress of the v2 re-keying, samaritan calls are paused while it is set
+	#[pallet::storage]
+	#[pallet::getter(fn rekey_progress)]
+	pub(super) type RekeyProgress<T: Config> = StorageValue<_, crate::migrations::v2::Cursor<T>>;
+
+	/// whether trust quorums still wait to be moved to interned DIDs by the v3 migration
+	#[pallet::storage]
+	#[pallet::getter(fn interning_quorums)]
+	pub(super) type InterningQuorums<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// whether signup entries of earlier layouts still wait to be removed by the v4 migration
+	#[pallet::storage]
+	#[pallet::getter(fn purging_signups)]
+	pub(super) type PurgingSignUps<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// progress of the v5 owner backfill, samaritan calls are paused while it is set
+	#[pallet::storage]
+	#[pallet::getter(fn backfill_progress)]
+	pub(super) type BackfillProgress<T: Config> = StorageValue<_, crate::migrations::v2::Cursor<T>>;
+
+	/// progress of the v6 deposit backfill, samaritan calls are paused while it is set
+	#[pallet::storage]
+	#[pallet::getter(fn deposit_backfill_progress)]
+	pub(super) type DepositBackfillProgress<T: Config> = StorageValue<_, crate::migrations::v2::Cursor<T>>;
+
+	#[pallet::type_value]
+	pub fn DefaultParameters<T: Config>() -> SamaritanParameters<T> {
+		SamaritanParameters {
+			quorum_cap: T::DefaultQuorumCap::get().min(T::MaxQuorumMembersCount::get()),
+			history_window: T::MaxCacheLength::get(),
+			signup_token_ttl: None,
+			consent_ttl: None,
+			deposit_base: T::DepositBase::get(),
+			deposit_per_byte: T::DepositPerByte::get()
+		}
+	}
+
+	/// soft limits set by the admin origin, the `Config` values until then
+	#[pallet::storage]
+	#[pallet::getter(fn parameters)]
+	pub(super) type Parameters<T: Config> = StorageValue<_, SamaritanParameters<T>, ValueQuery, DefaultParameters<T>>;
+
+	/// identities and apps a network starts with, stored as if their owners had made the calls
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// samaritans as (owner, name, DID, document hash)
+		pub samaritans: Vec<(T::AccountId, Vec<u8>, Vec<u8>, Vec<u8>)>,
+		/// apps as (owner, DID, document hash, name, logo, redirect URIs, scopes)
+		pub apps: Vec<(T::AccountId, Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>, Vec<Vec<u8>>, Vec<Vec<u8>>)>,
+		/// later DID document versions as (DID, document hash), the last one per DID is active
+		pub documents: Vec<(Vec<u8>, Vec<u8>)>,
+		/// profiles as (DID, profile hash)
+		pub profiles: Vec<(Vec<u8>, Vec<u8>)>,
+		/// trust quorums as (DID, member DIDs)
+		pub quorums: Vec<(Vec<u8>, Vec<Vec<u8>>)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self {
+				samaritans: Default::default(),
+				apps: Default::default(),
+				documents: Default::default(),
+				profiles: Default::default(),
+				quorums: Default::default(),
+			}
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			// going through the calls keeps deposits, DID ownership and interning in line with the chain
+			let signed = |who: &T::AccountId| -> OriginFor<T> { frame_system::RawOrigin::Signed(who.clone()).into() };
+			let owner = |did: &Did<T>| DidOwners::<T>::get(did).expect("genesis DID belongs to a samaritan or app");
+
+			for (who, name, did, doc) in &self.samaritans {
+				Pallet::<T>::create_samaritan(signed(who), genesis_bound(name), genesis_bound(did), genesis_bound(doc))
+					.expect("genesis samaritan can be created");
+			}
+
+			for (who, did, doc, name, logo, redirect_uris, scopes) in &self.apps {
+				Pallet::<T>::create_app(
+					signed(who),
+					genesis_bound(did),
+					genesis_bound(doc),
+					genesis_bound(name),
+					genesis_bound(logo),
+					genesis_bound(&redirect_uris.iter().map(|uri| genesis_bound(uri)).collect::<Vec<_>>()),
+					genesis_bound(&scopes.iter().map(|scope| genesis_bound(scope)).collect::<Vec<_>>()),
+				)
+				.expect("genesis app can be created");
+			}
+
+			for (did, doc) in &self.documents {
+				let did: Did<T> = genesis_bound(did);
+				Pallet::<T>::update_document(signed(&owner(&did)), did, genesis_bound(doc))
+					.expect("genesis DID document fits the history");
+			}
+
+			for (did, profile) in &self.profiles {
+				let did: Did<T> = genesis_bound(did);
+				Pallet::<T>::update_profile(signed(&owner(&did)), did, genesis_bound(profile))
+					.expect("genesis profile can be stored");
+			}
+
+			for (did, members) in &self.quorums {
+				let did: Did<T> = genesis_bound(did);
+				for member in members {
+					Pallet::<T>::update_quorum(signed(&owner(&did)), DidRef::Did(did.clone()), DidRef::Did(genesis_bound(member)))
+						.expect("genesis trust quorum has no duplicates and fits its bound");
+				}
+			}
+		}
+	}
+
+	/// genesis input as its bounded type, a chain spec over the bounds is rejected
+	#[cfg(feature = "std")]
+	fn genesis_bound<V: Clone, S: Get<u32>>(value: &[V]) -> BoundedVec<V, S> {
+		value.to_vec().try_into().expect("genesis input fits its bound")
+	}
 
 
 	#[pallet::event]
@@ -101,21 +756,87 @@ pub mod pallet {
 		ProfileUpdated { did: Vec<u8> },
 		/// app created
 		AppCreated { did: Vec<u8> },
-		/// get signup auth token
-		SignUpTokenGenerated { token: H256 } 
+		/// app metadata updated
+		AppUpdated { did: Vec<u8> },
+		/// app suspended or reactivated
+		AppStatusChanged { did: Vec<u8>, status: AppStatus },
+		/// app removed from the registry
+		AppRetired { did: Vec<u8> },
+		/// signup token committed to, the token itself never goes on chain
+		SignUpTokenGenerated { app_did: Vec<u8>, did: Vec<u8> },
+		/// signup token revealed and consumed by the app
+		SignUpTokenRedeemed { app_did: Vec<u8>, did: Vec<u8> },
+		/// unusable signup token removed
+		SignUpTokenReaped { app_did: Vec<u8>, did: Vec<u8> },
+		/// user granted an app access to data scopes
+		ConsentGranted { did: Vec<u8>, app_did: Vec<u8> },
+		/// user revoked consent given to an app
+		ConsentRevoked { did: Vec<u8>, app_did: Vec<u8> },
+		/// pairwise DID registered for an app
+		PairwiseDidRegistered { did: Vec<u8>, app_did: Vec<u8> },
+		/// control of a pairwise DID recovered through its master identity
+		PairwiseDidRecovered { did: Vec<u8> },
+		/// trust quorum member approved a pairwise DID recovery
+		PairwiseRecoveryApproved { did: Vec<u8>, approvals: u32 },
+		/// app release published
+		ReleasePublished { app_did: Vec<u8>, version: Vec<u8>, channel: ReleaseChannel },
+		/// app release withdrawn
+		ReleaseYanked { app_did: Vec<u8>, version: Vec<u8> },
+		/// app release moved to another channel
+		ReleaseChannelChanged { app_did: Vec<u8>, version: Vec<u8>, channel: ReleaseChannel },
+		/// app publisher verified
+		AppVerified { did: Vec<u8>, evidence: H256 },
+		/// app publisher verification withdrawn
+		AppVerificationRevoked { did: Vec<u8>, evidence: H256 },
+		/// app anchored a new version of a user's data
+		VaultPointerUpdated { app_did: Vec<u8>, did: Vec<u8>, cid: Vec<u8>, version: u64 },
+		/// user erased the pointer to their data held by an app
+		VaultPointerErased { app_did: Vec<u8>, did: Vec<u8> },
+		/// app recorded an access to a user's data
+		DataAccessed { app_did: Vec<u8>, did: Vec<u8>, seq: u64 },
+		/// user authorized an app session key
+		SessionKeyAuthorized { did: Vec<u8>, app_did: Vec<u8>, key: T::AccountId },
+		/// session key removed
+		SessionKeyRevoked { did: Vec<u8>, key: T::AccountId },
+		/// session key dispatched a call as the user DID
+		SessionCallDispatched { did: Vec<u8>, key: T::AccountId },
+		/// app created a subscription plan
+		PlanCreated { app_did: Vec<u8>, plan_id: u32 },
+		/// app retired a subscription plan
+		PlanRetired { app_did: Vec<u8>, plan_id: u32 },
+		/// user subscribed to an app plan
+		Subscribed { did: Vec<u8>, app_did: Vec<u8>, plan_id: u32 },
+		/// subscription period paid
+		SubscriptionCharged { did: Vec<u8>, app_did: Vec<u8>, amount: BalanceOf<T> },
+		/// subscription charge could not be collected
+		SubscriptionChargeFailed { did: Vec<u8>, app_did: Vec<u8> },
+		/// user cancelled, the subscription ends with the paid period
+		SubscriptionCancelled { did: Vec<u8>, app_did: Vec<u8> },
+		/// subscription ended
+		SubscriptionEnded { did: Vec<u8>, app_did: Vec<u8> },
+		/// app changed the calls it sponsors
+		SponsorshipUpdated { app_did: Vec<u8> },
+		/// sponsorship budget topped up
+		SponsorshipFunded { app_did: Vec<u8>, amount: BalanceOf<T> },
+		/// app took funds out of its sponsorship budget
+		SponsorshipWithdrawn { app_did: Vec<u8>, amount: BalanceOf<T> },
+		/// app paid the transaction fee of an account
+		TransactionSponsored { app_did: Vec<u8>, who: T::AccountId },
+		/// verification key added to a DID
+		DidKeyAdded { did: Vec<u8>, key: T::AccountId },
+		/// verification key removed from a DID
+		DidKeyRemoved { did: Vec<u8>, key: T::AccountId },
+		/// DID signed call relayed and dispatched
+		DidCallDispatched { did: Vec<u8>, nonce: u64, relayer: T::AccountId, result: DispatchResult },
+		/// soft limits changed by the admin origin
+		ParametersUpdated { parameters: SamaritanParameters<T> }
 	}
 
 	// Errors inform users that something went wrong.
 	#[pallet::error]
 	pub enum Error<T> {
-		/// DID length overflow
-		DIDLengthOverflow,
-		/// Hash Length overflow
-		HashLengthOverflow,
 		/// Cache Oveflow
 		CacheOverflow,
-		/// Samaritan too long
-		NameOverflow,
 		/// Hash didn't match any DID
 		DIDNotFound,
 		/// Samaritan not found
@@ -127,64 +848,195 @@ pub mod pallet {
 		/// Duplicate member
 		DuplicateQuorumMember,
 		/// Quorum not set up
-		QuorumUninitialized
+		QuorumUninitialized,
+		/// No signup token committed for the app and user
+		SignUpTokenNotFound,
+		/// Revealed token does not match the stored commitment
+		InvalidSignUpToken,
+		/// App DID already registered
+		AppAlreadyExists,
+		/// App not found
+		AppNotFound,
+		/// Caller does not own the app
+		NotAppOwner,
+		/// App has been suspended
+		AppSuspended,
+		/// DID is controlled by another account
+		DIDAlreadyRegistered,
+		/// Caller does not control the DID
+		NotDIDOwner,
+		/// Scope was not requested by the app
+		ScopeNotRequested,
+		/// Expiry is not in the future
+		InvalidExpiry,
+		/// No consent grant between the user and app
+		ConsentNotFound,
+		/// Pairwise DID not found
+		PairwiseDidNotFound,
+		/// Pairwise DID belongs to another app
+		PairwiseAppMismatch,
+		/// Link proof does not match the commitment or was not signed by the master DID
+		InvalidPairwiseLink,
+		/// Caller is neither the master DID owner nor in its trust quorum
+		NotRecoveryAuthority,
+		/// Version already published
+		DuplicateRelease,
+		/// Release not found
+		ReleaseNotFound,
+		/// App has no current consent from the user
+		ConsentRequired,
+		/// No vault pointer for the app and user
+		VaultPointerNotFound,
+		/// Account already used as a session key
+		SessionKeyInUse,
+		/// Session key not found
+		SessionKeyNotFound,
+		/// Session key has expired or is used up
+		SessionKeyExpired,
+		/// Call is not allowed for the session key
+		CallNotAllowed,
+		/// Plan period is zero
+		InvalidPeriod,
+		/// Plan not found or retired
+		PlanNotFound,
+		/// DID already subscribes to the app
+		AlreadySubscribed,
+		/// No subscription between the user and app
+		SubscriptionNotFound,
+		/// Charge would take the subscription over its spending cap
+		SpendingCapExceeded,
+		/// No room left for charges due in the block
+		ChargeAgendaFull,
+		/// App does not sponsor any calls
+		SponsorshipNotFound,
+		/// DID has as many verification keys as it can hold
+		DidKeysOverflow,
+		/// Key is already registered for the DID
+		DuplicateDidKey,
+		/// Key is not registered for the DID
+		DidKeyNotFound,
+		/// Signed call is past its expiry block
+		DidCallExpired,
+		/// Nonce is not the one the DID expects next
+		InvalidDidNonce,
+		/// Signature was not made by a key of the DID
+		BadDidSignature,
+		/// No DID is interned under the index
+		UnknownDidIndex,
+		/// Parameter outside the bounds set in the runtime
+		ParameterOutOfBounds,
+		/// Signup token was not redeemed in time
+		SignUpTokenExpired,
+		/// Signup token can still be redeemed
+		SignUpTokenInUse,
+		/// App has plans that still take subscribers
+		AppHasActivePlans,
+		/// Session key was issued to another app
+		SessionAppMismatch,
+		/// No room for another release without dropping the latest stable one
+		ReleaseOverflow,
+		/// No recovery is open for the pairwise DID
+		RecoveryNotFound,
+		/// Quorum member already approved the recovery
+		AlreadyApproved,
+		/// No room for another record in the access log
+		AccessLogOverflow
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(n: T::BlockNumber) -> Weight {
+			let due = ChargeAgenda::<T>::take(n);
+			let charges = due.len() as u64;
+
+			// agendas tried past the first when a charge has to be pushed back
+			let mut deferrals = 0u64;
+			for (did, app_did) in due {
+				deferrals = deferrals.saturating_add(Self::process_charge(n, did, app_did).into());
+			}
+
+			// migrate what fits in the block while a multi-block migration is running
+			let migration = crate::migrations::step::<T>(crate::migrations::block_budget::<T>());
+
+			T::DbWeight::get().reads_writes(1, 1)
+				.saturating_add(T::WeightInfo::charge_subscription().saturating_mul(charges))
+				.saturating_add(T::DbWeight::get().reads(deferrals))
+				.saturating_add(migration)
+		}
+
+		fn integrity_test() {
+			// hashes from calls are widened to the storage bound
+			assert!(
+				T::MaxCallHashLength::get() <= T::MaxHashLength::get(),
+				"MaxCallHashLength must not exceed MaxHashLength"
+			);
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn try_state(_n: T::BlockNumber) -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::create_samaritan(meta_hash.len() as u32))]
 		/// function to create a new Samaritan 
-		pub fn create_samaritan(origin: OriginFor<T>, name: Vec<u8>, did_str: Vec<u8>, meta_hash: Vec<u8>) -> DispatchResult {
+		pub fn create_samaritan(origin: OriginFor<T>, name: BoundedVec<u8, T::MaxNameLength>, did: Did<T>, meta_hash: ContentHash<T>) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			let meta_hash = Self::stored_hash(meta_hash)?;
 
-			let sn: BoundedVec<_, T::MaxNameLength> =
-				name.clone().try_into().map_err(|()| Error::<T>::NameOverflow)?;
-
-			let did: BoundedVec<_, T::MaxDIDLength> = 
-				did_str.clone().try_into().map_err(|()| Error::<T>::DIDLengthOverflow)?;
-
-			let hash: BoundedVec<_, T::MaxHashLength> = 
-				meta_hash.clone().try_into().map_err(|()| Error::<T>::HashLengthOverflow)?;
+			// a DID can only be claimed by one account, and pairwise DIDs can change hands on recovery
+			if let Some(owner) = DidOwners::<T>::get(&did) {
+				ensure!(owner == who, Error::<T>::DIDAlreadyRegistered);
+			}
+			ensure!(!PairwiseDids::<T>::contains_key(&did), Error::<T>::DIDAlreadyRegistered);
 
+			// an account is registered under a single samaritan DID. It keeps controlling the previous
+			// one, whose documents, grants and tokens would otherwise pass to whoever claims it next.
 			let sam: Samaritan<T> = Samaritan {
 				did: did.clone(),
-				name: sn
+				name: name.clone()
 			};
 
 			// register Samaritan
+			Self::sync_deposit(&who, &SamaritanRegistry::<T>::hashed_key_for(&who), sam.encoded_size())?;
 			SamaritanRegistry::<T>::insert(&who, sam);
+			DidOwners::<T>::insert(&did, &who);
+			Self::intern_with_deposit(&who, &did)?;
 
 			// register Document
 			let doc: DocMetadata<T> = DocMetadata {
 				version: 0,
-				hl: hash,
+				hl: meta_hash,
 				created: T::TimeProvider::now().as_secs(),
-				active: true
+				active: true,
+				updated: T::TimeProvider::now().as_secs(),
+				block: frame_system::Pallet::<T>::block_number()
 			};
 
 			let mut cache: BoundedVec<DocMetadata<T>, T::MaxCacheLength> = Default::default();
 			cache.try_push(doc).map_err(|()| Error::<T>::CacheOverflow)?;
 
 			// insert into storage 
+			Self::sync_deposit(&who, &DocMetaRegistry::<T>::hashed_key_for(&did), cache.encoded_size())?;
 			DocMetaRegistry::<T>::insert(&did, cache);
 
 			// emit event
-			Self::deposit_event(Event::SamaritanCreated { name, did: did_str } );
+			Self::deposit_event(Event::SamaritanCreated { name: name.to_vec(), did: did.to_vec() } );
 
 			Ok(())
 		}
 
-		#[pallet::weight(0)] 
+		#[pallet::weight(T::WeightInfo::rename_samaritan())]
 		/// rename a Samaritan
-		pub fn rename_samaritan(origin: OriginFor<T>, name: Vec<u8>) -> DispatchResult {
+		pub fn rename_samaritan(origin: OriginFor<T>, name: BoundedVec<u8, T::MaxNameLength>) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 
-			let sn: BoundedVec<_, T::MaxNameLength> =
-				name.clone().try_into().map_err(|()| Error::<T>::NameOverflow)?;
-
 			match SamaritanRegistry::<T>::get(&who) {
 				Some(mut sam) => {
-					sam.name = sn.clone();
+					sam.name = name.clone();
+					Self::sync_deposit(&who, &SamaritanRegistry::<T>::hashed_key_for(&who), sam.encoded_size())?;
 					SamaritanRegistry::<T>::insert(&who, sam);
 				},
 				None => {
@@ -194,29 +1046,23 @@ pub mod pallet {
 			}
 
 			// emit event
-			Self::deposit_event(Event::SamaritanNameChanged { name: sn.to_vec() } );
+			Self::deposit_event(Event::SamaritanNameChanged { name: name.to_vec() } );
 
 			Ok(())
 		}
 		
-		#[pallet::weight(0)] 
+		#[pallet::weight(T::WeightInfo::alter_state(T::MaxCacheLength::get()))]
 		/// enable/disable Samaritan
-		pub fn alter_state(origin: OriginFor<T>, did_str: Vec<u8>, state: bool) -> DispatchResult {
-			let _who = ensure_signed(origin)?;
-
-			let did: BoundedVec<_, T::MaxDIDLength> = 
-				did_str.clone().try_into().map_err(|()| Error::<T>::DIDLengthOverflow)?;
+		pub fn alter_state(origin: OriginFor<T>, did: Did<T>, state: bool) -> DispatchResult {
+			Self::ensure_did_controller(origin, &did)?;
 
 			// select the latest DID document 
 			match DocMetaRegistry::<T>::get(&did) {
 				Some(doc) => {
-					let mut index = 0;
-					for mut _d in &doc {
-						index += 1;
-					}
-
 					let mut d_vec = doc.into_inner();
-					d_vec[index - 1].active = state;
+					let latest = d_vec.last_mut().ok_or(Error::<T>::DIDMetaNotFound)?;
+					latest.active = state;
+					latest.updated = T::TimeProvider::now().as_secs();
 
 					let mut meta: BoundedVec<DocMetadata<T>, T::MaxCacheLength> = Default::default();
 
@@ -225,6 +1071,7 @@ pub mod pallet {
 					}
 
 					// save to storage
+					Self::sync_deposit(&Self::did_payer(&did)?, &DocMetaRegistry::<T>::hashed_key_for(&did), meta.encoded_size())?;
 					DocMetaRegistry::<T>::insert(&did, meta);
 
 				},
@@ -236,41 +1083,43 @@ pub mod pallet {
 			}
 
 			// emit event
-			Self::deposit_event(Event::SamaritanScopeChanged { did: did_str, state: state });
+			Self::deposit_event(Event::SamaritanScopeChanged { did: did.to_vec(), state: state });
 
 			Ok(())
 		}
 
-		#[pallet::weight(0)] 
+		#[pallet::weight(T::WeightInfo::update_document(doc.len() as u32, T::MaxCacheLength::get()))]
 		/// update DID document
-		pub fn update_document(origin: OriginFor<T>, did_str: Vec<u8>, doc: Vec<u8>,) -> DispatchResult {
-			let _who = ensure_signed(origin)?;
-
-			let did: BoundedVec<_, T::MaxDIDLength> = 
-				did_str.clone().try_into().map_err(|()| Error::<T>::DIDLengthOverflow)?;
-			
-			let hl: BoundedVec<_, T::MaxHashLength> =
-				doc.clone().try_into().map_err(|()| Error::<T>::HashLengthOverflow)?;
+		pub fn update_document(origin: OriginFor<T>, did: Did<T>, doc: ContentHash<T>,) -> DispatchResult {
+			Self::ensure_did_controller(origin, &did)?;
+			let doc = Self::stored_hash(doc)?;
 
 			// create metadata
 			let ndoc: DocMetadata<T> = DocMetadata {
-				version: DocMetaRegistry::<T>::get(&did).unwrap_or_default().len() as u64,
-				hl,
+				version: DocMetaRegistry::<T>::get(&did)
+					.and_then(|docs| docs.last().map(|latest| latest.version + 1))
+					.unwrap_or(0),
+				hl: doc,
 				created: T::TimeProvider::now().as_secs(),
-				active: true
+				active: true,
+				updated: T::TimeProvider::now().as_secs(),
+				block: frame_system::Pallet::<T>::block_number()
 			};
 
 			// select the latest DID document 
 			match DocMetaRegistry::<T>::get(&did) {
 				Some(doc) => {
-					let mut index = 0;
-					for mut _d in &doc {
-						index += 1;
-					}
-
 					// disable the current active DID doc, there can be only one
 					let mut d_vec = doc.into_inner();
-					d_vec[index - 1].active = false;
+					if let Some(latest) = d_vec.last_mut() {
+						latest.active = false;
+						latest.updated = T::TimeProvider::now().as_secs();
+					}
+
+					// the oldest versions roll out of the history window to make room
+					let window = Parameters::<T>::get().history_window.max(1) as usize;
+					let excess = (d_vec.len() + 1).saturating_sub(window).min(d_vec.len());
+					d_vec.drain(..excess);
 
 					let mut meta: BoundedVec<DocMetadata<T>, T::MaxCacheLength> = Default::default();
 
@@ -282,178 +1131,1650 @@ pub mod pallet {
 					meta.try_push(ndoc).map_err(|()| Error::<T>::CacheOverflow)?;
 
 					// save to storage
+					Self::sync_deposit(&Self::did_payer(&did)?, &DocMetaRegistry::<T>::hashed_key_for(&did), meta.encoded_size())?;
 					DocMetaRegistry::<T>::insert(&did, meta);
 				},
 
-				None => { }
+				// pairwise DIDs have no document history
+				None => return Err(Error::<T>::DIDNotFound.into()),
 			}
 
 			// emit event
-			Self::deposit_event(Event::DIDDocumentUpdated { did: did_str });
+			Self::deposit_event(Event::DIDDocumentUpdated { did: did.to_vec() });
 
 			Ok(())
 		}
 
-		#[pallet::weight(0)] 
+		#[pallet::weight(T::WeightInfo::update_quorum())]
 		/// update Samaritan trust quorum
-		pub fn update_quorum(origin: OriginFor<T>, did_str: Vec<u8>, trust_did: Vec<u8>) -> DispatchResult {
-			let _who = ensure_signed(origin)?;
+		pub fn update_quorum(origin: OriginFor<T>, did: DidRef<T>, trust_did: DidRef<T>) -> DispatchResult {
+			let did = Self::resolve(did)?;
 
-			let did: BoundedVec<_, T::MaxDIDLength> = 
-				did_str.clone().try_into().map_err(|()| Error::<T>::DIDLengthOverflow)?;
-
-			let t_did: BoundedVec<_, T::MaxDIDLength> = 
-				trust_did.clone().try_into().map_err(|()| Error::<T>::DIDLengthOverflow)?;
-
-			match TrustQuorum::<T>::get(&did) {
-				Some(mut quorum) => {
-					// first check the length of the quorum
-					if quorum.len() < T::MaxQuorumMembersCount::get() as usize {
-						// check for duplicate 
-						if quorum.contains(&t_did) {
-							// throw error, quorum full
-							return Err(Error::<T>::DuplicateQuorumMember.into());
-						}
-
-						// insert DID
-						quorum.try_push(t_did).map_err(|()| Error::<T>::QuorumOverflow)?;
-
-						// commit
-						TrustQuorum::<T>::insert(&did, quorum);
-					} else {
-						// throw error, quorum full
-						return Err(Error::<T>::QuorumOverflow.into());
-					}
-				},
+			Self::ensure_did_controller(origin, &did)?;
 
-				None => {
-					// create new quorum instance
-					let mut quorum: BoundedVec<_, T::MaxQuorumMembersCount> = Default::default();
-					
-					// insert DID
-					quorum.try_push(t_did).map_err(|()| Error::<T>::QuorumOverflow)?;
-
-					// commit
-					TrustQuorum::<T>::insert(&did, quorum);
-				}
-			}
+			let t_did = Self::resolve(trust_did)?;
+			let payer = Self::did_payer(&did)?;
+			let (index, member) = (Self::intern_with_deposit(&payer, &did)?, Self::intern_with_deposit(&payer, &t_did)?);
+
+			let mut quorum = Quorums::<T>::get(index).unwrap_or_default();
+
+			// first check the length of the quorum, then for duplicates
+			ensure!(quorum.len() < Parameters::<T>::get().quorum_cap as usize, Error::<T>::QuorumOverflow);
+			ensure!(!quorum.contains(&member), Error::<T>::DuplicateQuorumMember);
+
+			// insert DID
+			quorum.try_push(member).map_err(|()| Error::<T>::QuorumOverflow)?;
+
+			// commit
+			Self::sync_deposit(&payer, &Quorums::<T>::hashed_key_for(index), quorum.encoded_size())?;
+			Quorums::<T>::insert(index, quorum);
 
 			// emit event
-			Self::deposit_event(Event::TrustQuorumUpdated { did: did_str, trust_did });
+			Self::deposit_event(Event::TrustQuorumUpdated { did: did.to_vec(), trust_did: t_did.to_vec() });
 
 			Ok(())
 		}
 
-		#[pallet::weight(0)] 
+		#[pallet::weight(T::WeightInfo::filter_quorum())]
 		/// remove samaritan from quorum
-		pub fn filter_quorum(origin: OriginFor<T>, did_str: Vec<u8>, trust_did: Vec<u8>) -> DispatchResult {
-			let _who = ensure_signed(origin)?;
+		pub fn filter_quorum(origin: OriginFor<T>, did: DidRef<T>, trust_did: DidRef<T>) -> DispatchResult {
+			let did = Self::resolve(did)?;
 
-			let did: BoundedVec<_, T::MaxDIDLength> = 
-				did_str.clone().try_into().map_err(|()| Error::<T>::DIDLengthOverflow)?;
+			Self::ensure_did_controller(origin, &did)?;
 
-			match TrustQuorum::<T>::get(&did) {
-				Some(quorum) => {
-					let mut nq: BoundedVec<BoundedVec<u8, T::MaxDIDLength>, T::MaxQuorumMembersCount> = Default::default();
-					for i in &quorum {
-						let t_did: BoundedVec<_, T::MaxDIDLength> = 
-							trust_did.clone().try_into().map_err(|()| Error::<T>::DIDLengthOverflow)?;
-
-						if *i != t_did {
-							nq.try_push(i.clone()).map_err(|()| Error::<T>::QuorumOverflow)?;
-						}
-					}
+			let t_did = Self::resolve(trust_did)?;
 
-					// save the new quorum
-					TrustQuorum::<T>::insert(&did, nq);
-				},
+			let index = DidIndices::<T>::get(&did).ok_or(Error::<T>::QuorumUninitialized)?;
+			let mut quorum = Quorums::<T>::get(index).ok_or(Error::<T>::QuorumUninitialized)?;
 
-				None => {
-					// throw error, quorum full
-					return Err(Error::<T>::QuorumUninitialized.into());
-				}
+			// a DID that was never interned cannot be a member
+			if let Some(member) = DidIndices::<T>::get(&t_did) {
+				quorum.retain(|m| *m != member);
 			}
 
+			// save the new quorum
+			Self::sync_deposit(&Self::did_payer(&did)?, &Quorums::<T>::hashed_key_for(index), quorum.encoded_size())?;
+			Quorums::<T>::insert(index, quorum);
+
 			// emit event
-			Self::deposit_event(Event::TrustQuorumUpdated { did: did_str, trust_did });
+			Self::deposit_event(Event::TrustQuorumUpdated { did: did.to_vec(), trust_did: t_did.to_vec() });
 
 			Ok(())
 		}
  
-		#[pallet::weight(0)] 
+		#[pallet::weight(T::WeightInfo::update_profile(profile_hash.len() as u32))]
 		/// update profile
-		pub fn update_profile(origin: OriginFor<T>, did_str: Vec<u8>, profile_hash: Vec<u8>) -> DispatchResult {
-			let _who = ensure_signed(origin)?;
-
-			let did: BoundedVec<_, T::MaxDIDLength> = 
-				did_str.clone().try_into().map_err(|()| Error::<T>::DIDLengthOverflow)?;
+		pub fn update_profile(origin: OriginFor<T>, did: Did<T>, profile_hash: ContentHash<T>) -> DispatchResult {
+			Self::ensure_did_controller(origin, &did)?;
+			let profile_hash = Self::stored_hash(profile_hash)?;
 
-			let ph: BoundedVec<_, T::MaxHashLength> =
-				profile_hash.clone().try_into().map_err(|()| Error::<T>::HashLengthOverflow)?;
-
-			ProfileRegistry::<T>::insert(&did, ph);
+			Self::sync_deposit(&Self::did_payer(&did)?, &ProfileRegistry::<T>::hashed_key_for(&did), profile_hash.encoded_size())?;
+			ProfileRegistry::<T>::insert(&did, profile_hash);
 
 			// emit event
-			Self::deposit_event(Event::ProfileUpdated { did: did_str });
+			Self::deposit_event(Event::ProfileUpdated { did: did.to_vec() });
 
 			Ok(())
 		}
 
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::create_app((meta_hash.len() + logo.len()) as u32))]
 		/// function to create a new app
-		pub fn create_app(origin: OriginFor<T>, did_str: Vec<u8>, meta_hash: Vec<u8>) -> DispatchResult {
-			let _who = ensure_signed(origin)?;
-
-			let did: BoundedVec<_, T::MaxDIDLength> = 
-				did_str.clone().try_into().map_err(|()| Error::<T>::DIDLengthOverflow)?;
+		pub fn create_app(
+			origin: OriginFor<T>,
+			did: Did<T>,
+			meta_hash: ContentHash<T>,
+			name: BoundedVec<u8, T::MaxNameLength>,
+			logo: ContentHash<T>,
+			redirect_uris: BoundedVec<BoundedVec<u8, T::MaxUriLength>, T::MaxRedirectUris>,
+			scopes: BoundedVec<BoundedVec<u8, T::MaxScopeLength>, T::MaxScopes>
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let (meta_hash, logo) = (Self::stored_hash(meta_hash)?, Self::stored_hash(logo)?);
 
-			let hash: BoundedVec<_, T::MaxHashLength> = 
-				meta_hash.clone().try_into().map_err(|()| Error::<T>::HashLengthOverflow)?;
+			ensure!(!AppRegistry::<T>::contains_key(&did), Error::<T>::AppAlreadyExists);
+			if let Some(owner) = DidOwners::<T>::get(&did) {
+				ensure!(owner == who, Error::<T>::DIDAlreadyRegistered);
+			}
+			ensure!(!PairwiseDids::<T>::contains_key(&did), Error::<T>::DIDAlreadyRegistered);
+
+			let app: AppInfo<T> = AppInfo {
+				owner: who.clone(),
+				name,
+				logo,
+				redirect_uris,
+				scopes,
+				status: AppStatus::Active,
+				verification: Verification::Unverified,
+				created: T::TimeProvider::now().as_secs()
+			};
 
 			// register Document
 			let doc: DocMetadata<T> = DocMetadata {
 				version: 0,
-				hl: hash,
+				hl: meta_hash,
 				created: T::TimeProvider::now().as_secs(),
-				active: true
+				active: true,
+				updated: T::TimeProvider::now().as_secs(),
+				block: frame_system::Pallet::<T>::block_number()
 			};
 
 			let mut cache: BoundedVec<DocMetadata<T>, T::MaxCacheLength> = Default::default();
 			cache.try_push(doc).map_err(|()| Error::<T>::CacheOverflow)?;
 
 			// insert into storage 
+			Self::sync_deposit(&who, &DocMetaRegistry::<T>::hashed_key_for(&did), cache.encoded_size())?;
 			DocMetaRegistry::<T>::insert(&did, cache);
+			DidOwners::<T>::insert(&did, &who);
+			Self::intern_with_deposit(&who, &did)?;
+			AppRegistry::<T>::insert(&did, app);
+
+			// emit event
+			Self::deposit_event(Event::AppCreated { did: did.to_vec() } );
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::update_app(logo.len() as u32))]
+		/// update the registered metadata of an app
+		pub fn update_app(
+			origin: OriginFor<T>,
+			did: Did<T>,
+			name: BoundedVec<u8, T::MaxNameLength>,
+			logo: ContentHash<T>,
+			redirect_uris: BoundedVec<BoundedVec<u8, T::MaxUriLength>, T::MaxRedirectUris>,
+			scopes: BoundedVec<BoundedVec<u8, T::MaxScopeLength>, T::MaxScopes>
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let logo = Self::stored_hash(logo)?;
+
+			let mut app = Self::owned_app(&who, &did)?;
+
+			// a verified identity has to be checked again after rebranding or moving its login flow
+			if app.name != name || app.logo != logo || app.redirect_uris != redirect_uris {
+				app.verification = Verification::Unverified;
+			}
+
+			app.name = name;
+			app.logo = logo;
+			app.redirect_uris = redirect_uris;
+			app.scopes = scopes;
+
+			AppRegistry::<T>::insert(&did, app);
+
+			// emit event
+			Self::deposit_event(Event::AppUpdated { did: did.to_vec() });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::set_app_status())]
+		/// suspend or reactivate an app, callable by the verifier origin
+		pub fn set_app_status(origin: OriginFor<T>, did: Did<T>, status: AppStatus) -> DispatchResult {
+			T::VerifierOrigin::ensure_origin(origin)?;
+
+			AppRegistry::<T>::try_mutate(&did, |app| -> DispatchResult {
+				let app = app.as_mut().ok_or(Error::<T>::AppNotFound)?;
+				app.status = status;
+				Ok(())
+			})?;
+
+			// emit event
+			Self::deposit_event(Event::AppStatusChanged { did: did.to_vec(), status });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::retire_app())]
+		/// remove an app from the registry, its DID document history is kept. Plans have to be
+		/// retired first, the sponsorship budget is refunded to the owner and pending signup
+		/// tokens can be reaped by anyone afterwards.
+		pub fn retire_app(origin: OriginFor<T>, did: Did<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			Self::owned_app(&who, &did)?;
+			ensure!(ActivePlans::<T>::get(&did) == 0, Error::<T>::AppHasActivePlans);
+
+			// what is left of the sponsorship budget goes back to the owner
+			Sponsorships::<T>::remove(&did);
+			let sponsor = Self::sponsor_account(&did);
+			let budget = T::Currency::free_balance(&sponsor);
+			if !budget.is_zero() {
+				T::Currency::transfer(&sponsor, &who, budget, ExistenceRequirement::AllowDeath)?;
+				Self::deposit_event(Event::SponsorshipWithdrawn { app_did: did.to_vec(), amount: budget });
+			}
+
+			AppRegistry::<T>::remove(&did);
+
+			// emit event
+			Self::deposit_event(Event::AppRetired { did: did.to_vec() });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::generate_token())]
+		/// commit to an app login key generated off-chain, `commitment` is the salted
+		/// `token_commitment` of the token. Anything the chain derives is public, so the token has
+		/// to come from a secure RNG on the client. The user DID has to be registered, callable by
+		/// the app owner or whoever controls the user DID.
+		pub fn generate_token(origin: OriginFor<T>, app_did: Did<T>, did: Did<T>, commitment: H256) -> DispatchResult {
+			// only active apps can sign users up
+			let app = AppRegistry::<T>::get(&app_did).ok_or(Error::<T>::AppNotFound)?;
+			ensure!(app.status == AppStatus::Active, Error::<T>::AppSuspended);
+			// tokens are only issued to registered DIDs, so a later claimant can't redeem them
+			ensure!(DidOwners::<T>::contains_key(&did), Error::<T>::DIDNotFound);
+			Self::ensure_pairwise_scope(&did, &app_did)?;
+
+			// anyone else could replace a pending token and the deposit held for it
+			let who = match ensure_signed(origin.clone()) {
+				Ok(who) if who == app.owner => who,
+				_ => {
+					Self::ensure_app_controller(origin, &did, &app_did)?;
+					Self::did_payer(&did)?
+				},
+			};
+
+			let entry: SignUpToken<T> = SignUpToken {
+				commitment,
+				issued: frame_system::Pallet::<T>::block_number()
+			};
+
+			// insert into storage, the caller holds the deposit until the token is redeemed
+			Self::sync_deposit(&who, &SignUpDataRegistry::<T>::hashed_key_for(&app_did, &did), entry.encoded_size())?;
+			SignUpDataRegistry::<T>::insert(&app_did, &did, entry);
 
 			// emit event
-			Self::deposit_event(Event::AppCreated { did: did_str } );
+			Self::deposit_event(Event::SignUpTokenGenerated { app_did: app_did.to_vec(), did: did.to_vec() } );
 
 			Ok(())
 		}
 
-		#[pallet::weight(0)]
-		/// create app login key
-		pub fn generate_token(origin: OriginFor<T>, app_did_str: Vec<u8>, did_str: Vec<u8>, token: H256) -> DispatchResult {
+		#[pallet::weight(T::WeightInfo::redeem_token())]
+		/// reveal a signup token to prove possession, consuming it
+		pub fn redeem_token(origin: OriginFor<T>, app_did: Did<T>, did: Did<T>, token: H256) -> DispatchResult {
 			let _who = ensure_signed(origin)?;
 
-			let udid: BoundedVec<_, T::MaxDIDLength> = 
-				did_str.clone().try_into().map_err(|()| Error::<T>::DIDLengthOverflow)?;
+			let entry = SignUpDataRegistry::<T>::get(&app_did, &did)
+				.ok_or(Error::<T>::SignUpTokenNotFound)?;
+			ensure!(Self::token_live(&entry), Error::<T>::SignUpTokenExpired);
 
-			let adid: BoundedVec<_, T::MaxDIDLength> = 
-				app_did_str.clone().try_into().map_err(|()| Error::<T>::DIDLengthOverflow)?;
+			// check the preimage
+			ensure!(
+				Self::token_commitment(&token, &app_did, &did) == entry.commitment,
+				Error::<T>::InvalidSignUpToken
+			);
 
-			// insert into storage 
-			SignUpDataRegistry::<T>::set(adid, udid, token.clone());
+			// a token can only be used once
+			Self::release_deposit(&SignUpDataRegistry::<T>::hashed_key_for(&app_did, &did));
+			SignUpDataRegistry::<T>::remove(&app_did, &did);
+
+			// emit event
+			Self::deposit_event(Event::SignUpTokenRedeemed { app_did: app_did.to_vec(), did: did.to_vec() } );
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::reap_signup_token())]
+		/// remove a signup token of a retired app or one that has expired, refunding its deposit
+		pub fn reap_signup_token(origin: OriginFor<T>, app_did: Did<T>, did: Did<T>) -> DispatchResult {
+			let _who = ensure_signed(origin)?;
+
+			let entry = SignUpDataRegistry::<T>::get(&app_did, &did)
+				.ok_or(Error::<T>::SignUpTokenNotFound)?;
+			ensure!(
+				!AppRegistry::<T>::contains_key(&app_did) || !Self::token_live(&entry),
+				Error::<T>::SignUpTokenInUse
+			);
+
+			Self::release_deposit(&SignUpDataRegistry::<T>::hashed_key_for(&app_did, &did));
+			SignUpDataRegistry::<T>::remove(&app_did, &did);
+
+			// emit event
+			Self::deposit_event(Event::SignUpTokenReaped { app_did: app_did.to_vec(), did: did.to_vec() } );
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::grant_consent())]
+		/// consent to share data scopes with an app. An earlier grant is replaced and folded
+		/// into the consent history, so what was agreed to before stays provable.
+		pub fn grant_consent(
+			origin: OriginFor<T>,
+			did: Did<T>,
+			app_did: Did<T>,
+			scopes: BoundedVec<BoundedVec<u8, T::MaxScopeLength>, T::MaxScopes>,
+			expires_at: Option<u64>
+		) -> DispatchResult {
+			Self::ensure_app_controller(origin, &did, &app_did)?;
+			Self::ensure_pairwise_scope(&did, &app_did)?;
+
+			let app = AppRegistry::<T>::get(&app_did).ok_or(Error::<T>::AppNotFound)?;
+
+			// users can only consent to what the app asked for
+			for scope in &scopes {
+				ensure!(app.scopes.contains(scope), Error::<T>::ScopeNotRequested);
+			}
+
+			let now = T::TimeProvider::now().as_secs();
+			if let Some(expiry) = expires_at {
+				ensure!(expiry > now, Error::<T>::InvalidExpiry);
+			}
+
+			// grants without an expiry get the default lifetime, if there is one
+			let expires_at = expires_at.or_else(|| Parameters::<T>::get().consent_ttl.map(|ttl| now.saturating_add(ttl)));
+
+			let grant: ConsentGrant<T> = ConsentGrant {
+				scopes,
+				granted_at: now,
+				expires_at,
+				revoked: false
+			};
+
+			if let Some(previous) = ConsentGrants::<T>::get(&did, &app_did) {
+				ConsentHistory::<T>::mutate(&did, &app_did, |head| *head = Some(Self::consent_receipt(head.as_ref(), &previous)));
+			}
+			ConsentGrants::<T>::insert(&did, &app_did, grant);
+
+			// emit event
+			Self::deposit_event(Event::ConsentGranted { did: did.to_vec(), app_did: app_did.to_vec() });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::revoke_consent())]
+		/// revoke consent given to an app, the grant is kept as a receipt
+		pub fn revoke_consent(origin: OriginFor<T>, did: Did<T>, app_did: Did<T>) -> DispatchResult {
+			Self::ensure_app_controller(origin, &did, &app_did)?;
+
+			ConsentGrants::<T>::try_mutate(&did, &app_did, |grant| -> DispatchResult {
+				let grant = grant.as_mut().ok_or(Error::<T>::ConsentNotFound)?;
+				grant.revoked = true;
+				Ok(())
+			})?;
+
+			// emit event
+			Self::deposit_event(Event::ConsentRevoked { did: did.to_vec(), app_did: app_did.to_vec() });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::register_pairwise_did(recovery_escrow.len() as u32))]
+		/// register a pseudonymous DID for use with a single app. It should be submitted
+		/// from an account that is not linked to the master identity.
+		pub fn register_pairwise_did(
+			origin: OriginFor<T>,
+			did: Did<T>,
+			app_did: Did<T>,
+			link_commitment: H256,
+			recovery_escrow: ContentHash<T>
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(!DidOwners::<T>::contains_key(&did), Error::<T>::DIDAlreadyRegistered);
+			ensure!(AppRegistry::<T>::contains_key(&app_did), Error::<T>::AppNotFound);
+
+			let record: PairwiseRecord<T> = PairwiseRecord {
+				app_did: app_did.clone(),
+				link_commitment,
+				recovery_escrow: Self::stored_hash(recovery_escrow)?,
+				created: T::TimeProvider::now().as_secs()
+			};
+
+			PairwiseDids::<T>::insert(&did, record);
+			DidOwners::<T>::insert(&did, &who);
+			Self::intern_with_deposit(&who, &did)?;
+
+			// emit event
+			Self::deposit_event(Event::PairwiseDidRegistered { did: did.to_vec(), app_did: app_did.to_vec() });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::recover_pairwise_did(T::MaxDidKeys::get()))]
+		/// hand a pairwise DID to a new account by revealing its link to the master DID. The master
+		/// owner recovers it at once, a trust quorum member opens a request the quorum has to approve.
+		pub fn recover_pairwise_did(
+			origin: OriginFor<T>,
+			did: Did<T>,
+			master_did: Did<T>,
+			master_signature: T::DidSignature,
+			new_owner: T::AccountId
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let record = PairwiseDids::<T>::get(&did).ok_or(Error::<T>::PairwiseDidNotFound)?;
+			ensure!(
+				Self::pairwise_link(&did, &master_did, &master_signature) == record.link_commitment &&
+					Self::signed_by_did(&master_did, &Self::pairwise_payload(&did, &record.app_did), &master_signature),
+				Error::<T>::InvalidPairwiseLink
+			);
+
+			if DidOwners::<T>::get(&master_did) == Some(who.clone()) {
+				Self::finish_pairwise_recovery(&did, new_owner);
+				return Ok(());
+			}
+
+			// a new request replaces an open one and starts over
+			let member = Self::quorum_member(&who, &master_did).ok_or(Error::<T>::NotRecoveryAuthority)?;
+			let recovery: PairwiseRecovery<T> = PairwiseRecovery {
+				master_did,
+				new_owner,
+				approvals: BoundedVec::truncate_from(sp_std::vec![member])
+			};
+
+			Self::sync_deposit(&who, &PairwiseRecoveries::<T>::hashed_key_for(&did), PairwiseRecovery::<T>::max_encoded_len())?;
+			PairwiseRecoveries::<T>::insert(&did, recovery);
+
+			Self::approve_or_finish(&did)
+		}
+
+		#[pallet::weight(T::WeightInfo::publish_release(manifest.len() as u32))]
+		/// publish a versioned release manifest for an app
+		pub fn publish_release(
+			origin: OriginFor<T>,
+			app_did: Did<T>,
+			version: BoundedVec<u8, T::MaxVersionLength>,
+			manifest: ContentHash<T>,
+			permissions: BoundedVec<BoundedVec<u8, T::MaxScopeLength>, T::MaxScopes>,
+			signing_key: H256,
+			channel: ReleaseChannel
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			Self::owned_app(&who, &app_did)?;
+
+			let release: Release<T> = Release {
+				version: version.clone(),
+				manifest: Self::stored_hash(manifest)?,
+				permissions,
+				signing_key,
+				channel,
+				yanked: false,
+				published: T::TimeProvider::now().as_secs()
+			};
+
+			// versions can't be reused, even once their release has left the window
+			ensure!(!PublishedVersions::<T>::contains_key(&app_did, &version), Error::<T>::DuplicateRelease);
+
+			AppReleases::<T>::try_mutate(&app_did, |releases| -> DispatchResult {
+				// keep a rolling window of releases, the latest stable one stays unless this replaces it
+				if !releases.is_empty() && releases.len() >= T::MaxReleases::get() as usize {
+					let stable = match channel {
+						ReleaseChannel::Stable => None,
+						ReleaseChannel::Beta => releases.iter().rposition(|r| r.channel == ReleaseChannel::Stable && !r.yanked),
+					};
+					let oldest = (0..releases.len()).find(|i| Some(*i) != stable).ok_or(Error::<T>::ReleaseOverflow)?;
+					releases.remove(oldest);
+				}
+
+				releases.try_push(release).map_err(|_| Error::<T>::ReleaseOverflow)?;
+				Ok(())
+			})?;
+
+			Self::sync_deposit(&who, &PublishedVersions::<T>::hashed_key_for(&app_did, &version), version.encoded_size())?;
+			PublishedVersions::<T>::insert(&app_did, &version, ());
+
+			// emit event
+			Self::deposit_event(Event::ReleasePublished { app_did: app_did.to_vec(), version: version.to_vec(), channel });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::yank_release())]
+		/// mark a release as yanked so clients stop installing it
+		pub fn yank_release(origin: OriginFor<T>, app_did: Did<T>, version: BoundedVec<u8, T::MaxVersionLength>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			Self::owned_app(&who, &app_did)?;
+
+			Self::mutate_release(&app_did, &version, |release| release.yanked = true)?;
+
+			// emit event
+			Self::deposit_event(Event::ReleaseYanked { app_did: app_did.to_vec(), version: version.to_vec() });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::set_release_channel())]
+		/// move a release to the stable or beta channel
+		pub fn set_release_channel(
+			origin: OriginFor<T>,
+			app_did: Did<T>,
+			version: BoundedVec<u8, T::MaxVersionLength>,
+			channel: ReleaseChannel
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			Self::owned_app(&who, &app_did)?;
+
+			Self::mutate_release(&app_did, &version, |release| release.channel = channel)?;
+
+			// emit event
+			Self::deposit_event(Event::ReleaseChannelChanged { app_did: app_did.to_vec(), version: version.to_vec(), channel });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::verify_app())]
+		/// mark an app publisher as verified, `evidence` is the hash of the review record
+		pub fn verify_app(origin: OriginFor<T>, did: Did<T>, evidence: H256) -> DispatchResult {
+			T::VerifierOrigin::ensure_origin(origin)?;
+
+			AppRegistry::<T>::try_mutate(&did, |app| -> DispatchResult {
+				let app = app.as_mut().ok_or(Error::<T>::AppNotFound)?;
+				app.verification = Verification::Verified { evidence, at: T::TimeProvider::now().as_secs() };
+				Ok(())
+			})?;
+
+			// emit event
+			Self::deposit_event(Event::AppVerified { did: did.to_vec(), evidence });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::revoke_app_verification())]
+		/// withdraw the verification of an app publisher
+		pub fn revoke_app_verification(origin: OriginFor<T>, did: Did<T>, evidence: H256) -> DispatchResult {
+			T::VerifierOrigin::ensure_origin(origin)?;
+
+			AppRegistry::<T>::try_mutate(&did, |app| -> DispatchResult {
+				let app = app.as_mut().ok_or(Error::<T>::AppNotFound)?;
+				app.verification = Verification::Revoked { evidence, at: T::TimeProvider::now().as_secs() };
+				Ok(())
+			})?;
+
+			// emit event
+			Self::deposit_event(Event::AppVerificationRevoked { did: did.to_vec(), evidence });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::set_vault_pointer(cid.len() as u32))]
+		/// anchor the location of a user's encrypted data, callable by the app owner or a
+		/// session key the user issued to the app, while the user's consent is current
+		pub fn set_vault_pointer(
+			origin: OriginFor<T>,
+			app_did: Did<T>,
+			did: Did<T>,
+			cid: ContentHash<T>
+		) -> DispatchResult {
+			let app = match ensure_signed(origin.clone()) {
+				Ok(who) => Self::owned_app(&who, &app_did)?,
+				Err(_) => {
+					Self::ensure_session_of(origin, &did, &app_did)?;
+					AppRegistry::<T>::get(&app_did).ok_or(Error::<T>::AppNotFound)?
+				},
+			};
+			ensure!(app.status == AppStatus::Active, Error::<T>::AppSuspended);
+
+			let consented = ConsentGrants::<T>::get(&did, &app_did).map_or(false, |g| Self::is_live(&g));
+			ensure!(consented, Error::<T>::ConsentRequired);
+
+			let version = DataVaults::<T>::get(&app_did, &did).map_or(0, |v| v.version + 1);
+
+			let pointer: VaultPointer<T> = VaultPointer {
+				cid: Self::stored_hash(cid.clone())?,
+				version,
+				updated: T::TimeProvider::now().as_secs()
+			};
+
+			DataVaults::<T>::insert(&app_did, &did, pointer);
+
+			// emit event
+			Self::deposit_event(Event::VaultPointerUpdated { app_did: app_did.to_vec(), did: did.to_vec(), cid: cid.to_vec(), version });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::erase_vault_pointer())]
+		/// erase the pointer to data an app holds for the user, callable by the user at any time
+		pub fn erase_vault_pointer(origin: OriginFor<T>, did: Did<T>, app_did: Did<T>) -> DispatchResult {
+			Self::ensure_app_controller(origin, &did, &app_did)?;
+			ensure!(DataVaults::<T>::contains_key(&app_did, &did), Error::<T>::VaultPointerNotFound);
+
+			DataVaults::<T>::remove(&app_did, &did);
 
 			// emit event
-			Self::deposit_event(Event::SignUpTokenGenerated { token } );
+			Self::deposit_event(Event::VaultPointerErased { app_did: app_did.to_vec(), did: did.to_vec() });
 
 			Ok(())
 		}
+
+		#[pallet::weight(T::WeightInfo::log_access())]
+		/// record that an app accessed a scope of the user's data, `purpose` is an app defined code.
+		/// The user has to currently share the scope with the app.
+		pub fn log_access(
+			origin: OriginFor<T>,
+			app_did: Did<T>,
+			did: Did<T>,
+			scope: BoundedVec<u8, T::MaxScopeLength>,
+			purpose: u32
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			Self::owned_app(&who, &app_did)?;
+
+			// apps can only log access to what the user currently shares with them
+			let granted = ConsentGrants::<T>::get(&did, &app_did)
+				.filter(|g| Self::is_live(g))
+				.map_or(false, |g| g.scopes.contains(&scope));
+			ensure!(granted, Error::<T>::ConsentRequired);
+
+			let seq = AccessLog::<T>::try_mutate(&did, |log| -> Result<u64, DispatchError> {
+				let seq = log.last().map_or(0, |r| r.seq + 1);
+
+				// drop the oldest record once the buffer is full
+				if !log.is_empty() && log.len() >= T::MaxAccessRecords::get() as usize {
+					log.remove(0);
+				}
+
+				let record: AccessRecord<T> = AccessRecord {
+					seq,
+					app_did: app_did.clone(),
+					scope,
+					purpose,
+					timestamp: T::TimeProvider::now().as_secs()
+				};
+
+				log.try_push(record).map_err(|_| Error::<T>::AccessLogOverflow)?;
+				Ok(seq)
+			})?;
+
+			// emit event
+			Self::deposit_event(Event::DataAccessed { app_did: app_did.to_vec(), did: did.to_vec(), seq });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::authorize_session_key())]
+		/// let an app key make a limited set of samaritan calls as the user DID
+		pub fn authorize_session_key(
+			origin: OriginFor<T>,
+			did: Did<T>,
+			app_did: Did<T>,
+			key: T::AccountId,
+			calls: BoundedVec<BoundedVec<u8, T::MaxCallNameLength>, T::MaxSessionCalls>,
+			expires: T::BlockNumber,
+			max_calls: u32
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			// session keys can only be handed out by the owning account itself
+			Self::ensure_did_owner(&who, &did)?;
+			Self::ensure_pairwise_scope(&did, &app_did)?;
+			ensure!(AppRegistry::<T>::contains_key(&app_did), Error::<T>::AppNotFound);
+			ensure!(expires > frame_system::Pallet::<T>::block_number(), Error::<T>::InvalidExpiry);
+			ensure!(!SessionKeys::<T>::contains_key(&key), Error::<T>::SessionKeyInUse);
+
+			let session: SessionKey<T> = SessionKey {
+				did: did.clone(),
+				app_did: app_did.clone(),
+				calls,
+				expires,
+				remaining: max_calls
+			};
+
+			SessionKeys::<T>::insert(&key, session);
+			SessionKeyEpochs::<T>::insert(&key, SessionEpochs::<T>::get(&did));
+
+			// emit event
+			Self::deposit_event(Event::SessionKeyAuthorized { did: did.to_vec(), app_did: app_did.to_vec(), key });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::revoke_session_key())]
+		/// remove a session key before it expires
+		pub fn revoke_session_key(origin: OriginFor<T>, key: T::AccountId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let session = SessionKeys::<T>::get(&key).ok_or(Error::<T>::SessionKeyNotFound)?;
+			Self::ensure_did_owner(&who, &session.did)?;
+
+			SessionKeys::<T>::remove(&key);
+			SessionKeyEpochs::<T>::remove(&key);
+
+			// emit event
+			Self::deposit_event(Event::SessionKeyRevoked { did: session.did.to_vec(), key });
+
+			Ok(())
+		}
+
+		#[pallet::weight({
+			let info = call.get_dispatch_info();
+			(info.weight.saturating_add(T::WeightInfo::session_call()), info.class)
+		})]
+		/// dispatch an allowed samaritan call as the user DID the session key acts for
+		pub fn session_call(origin: OriginFor<T>, call: Box<<T as Config>::RuntimeCall>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let mut session = SessionKeys::<T>::get(&who).ok_or(Error::<T>::SessionKeyNotFound)?;
+			// keys issued before the DID was recovered are revoked
+			ensure!(
+				SessionKeyEpochs::<T>::get(&who) == SessionEpochs::<T>::get(&session.did),
+				Error::<T>::SessionKeyNotFound
+			);
+			ensure!(
+				session.remaining > 0 && frame_system::Pallet::<T>::block_number() <= session.expires,
+				Error::<T>::SessionKeyExpired
+			);
+
+			// only samaritan calls named by the user are allowed
+			let allowed = match call.is_sub_type() {
+				Some(inner) => {
+					let name = inner.get_call_name().as_bytes();
+					session.calls.iter().any(|c| c[..] == name[..])
+				},
+				None => false,
+			};
+			ensure!(allowed, Error::<T>::CallNotAllowed);
+
+			session.remaining -= 1;
+			if session.remaining == 0 {
+				SessionKeys::<T>::remove(&who);
+				SessionKeyEpochs::<T>::remove(&who);
+			} else {
+				SessionKeys::<T>::insert(&who, session.clone());
+			}
+
+			let origin: <T as Config>::RuntimeOrigin = RawOrigin::Session {
+				did: session.did.clone(),
+				app_did: session.app_did.clone()
+			}.into();
+			(*call).dispatch(origin.into()).map_err(|e| e.error)?;
+
+			// emit event
+			Self::deposit_event(Event::SessionCallDispatched { did: session.did.to_vec(), key: who });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::create_plan())]
+		/// define a recurring price for an app
+		pub fn create_plan(
+			origin: OriginFor<T>,
+			app_did: Did<T>,
+			price: BalanceOf<T>,
+			period: T::BlockNumber,
+			grace: T::BlockNumber
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			Self::owned_app(&who, &app_did)?;
+			ensure!(!period.is_zero(), Error::<T>::InvalidPeriod);
+
+			let plan_id = NextPlanId::<T>::get(&app_did);
+			let plan: SubscriptionPlan<T> = SubscriptionPlan { price, period, grace, active: true };
+
+			SubscriptionPlans::<T>::insert(&app_did, plan_id, plan);
+			NextPlanId::<T>::insert(&app_did, plan_id.saturating_add(1));
+			ActivePlans::<T>::mutate(&app_did, |n| *n = n.saturating_add(1));
+
+			// emit event
+			Self::deposit_event(Event::PlanCreated { app_did: app_did.to_vec(), plan_id });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::retire_plan())]
+		/// stop a plan from taking subscribers, current ones end at their next charge
+		pub fn retire_plan(origin: OriginFor<T>, app_did: Did<T>, plan_id: u32) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			Self::owned_app(&who, &app_did)?;
+
+			SubscriptionPlans::<T>::try_mutate(&app_did, plan_id, |plan| -> DispatchResult {
+				let plan = plan.as_mut().filter(|p| p.active).ok_or(Error::<T>::PlanNotFound)?;
+				plan.active = false;
+				Ok(())
+			})?;
+			ActivePlans::<T>::mutate(&app_did, |n| *n = n.saturating_sub(1));
+
+			// emit event
+			Self::deposit_event(Event::PlanRetired { app_did: app_did.to_vec(), plan_id });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::subscribe())]
+		/// subscribe a DID to an app plan, paying the first period now
+		pub fn subscribe(
+			origin: OriginFor<T>,
+			did: Did<T>,
+			app_did: Did<T>,
+			plan_id: u32,
+			cap: BalanceOf<T>
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			// the paying account must own the DID
+			Self::ensure_did_owner(&who, &did)?;
+			Self::ensure_pairwise_scope(&did, &app_did)?;
+			ensure!(!Subscriptions::<T>::contains_key(&did, &app_did), Error::<T>::AlreadySubscribed);
+
+			let plan = SubscriptionPlans::<T>::get(&app_did, plan_id)
+				.filter(|p| p.active)
+				.ok_or(Error::<T>::PlanNotFound)?;
+			let app = AppRegistry::<T>::get(&app_did).ok_or(Error::<T>::AppNotFound)?;
+			ensure!(app.status == AppStatus::Active, Error::<T>::AppSuspended);
+			ensure!(plan.price <= cap, Error::<T>::SpendingCapExceeded);
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let next_charge = now.saturating_add(plan.period);
+			Self::schedule_charge(next_charge, &did, &app_did)?;
+
+			T::Currency::transfer(&who, &app.owner, plan.price, ExistenceRequirement::KeepAlive)?;
+
+			let sub: Subscription<T> = Subscription {
+				plan_id,
+				payer: who,
+				cap,
+				spent: plan.price,
+				next_charge,
+				past_due: false,
+				cancelled: false
+			};
+
+			Subscriptions::<T>::insert(&did, &app_did, sub);
+
+			// emit event
+			Self::deposit_event(Event::Subscribed { did: did.to_vec(), app_did: app_did.to_vec(), plan_id });
+			Self::deposit_event(Event::SubscriptionCharged { did: did.to_vec(), app_did: app_did.to_vec(), amount: plan.price });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::cancel_subscription())]
+		/// cancel a subscription, it stays usable until the paid period is over
+		pub fn cancel_subscription(origin: OriginFor<T>, did: Did<T>, app_did: Did<T>) -> DispatchResult {
+			Self::ensure_app_controller(origin, &did, &app_did)?;
+
+			Subscriptions::<T>::try_mutate(&did, &app_did, |sub| -> DispatchResult {
+				let sub = sub.as_mut().ok_or(Error::<T>::SubscriptionNotFound)?;
+				sub.cancelled = true;
+				Ok(())
+			})?;
+
+			// emit event
+			Self::deposit_event(Event::SubscriptionCancelled { did: did.to_vec(), app_did: app_did.to_vec() });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::set_sponsorship())]
+		/// choose the calls an app pays the fees of, and how often each account may use them
+		pub fn set_sponsorship(
+			origin: OriginFor<T>,
+			app_did: Did<T>,
+			calls: BoundedVec<BoundedVec<u8, T::MaxCallNameLength>, T::MaxSponsoredCalls>,
+			max_per_account: u32
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			Self::owned_app(&who, &app_did)?;
+
+			Sponsorships::<T>::insert(&app_did, Sponsorship { calls, max_per_account });
+
+			// emit event
+			Self::deposit_event(Event::SponsorshipUpdated { app_did: app_did.to_vec() });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::fund_sponsorship())]
+		/// add funds to the account an app pays sponsored fees from
+		pub fn fund_sponsorship(origin: OriginFor<T>, app_did: Did<T>, amount: BalanceOf<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(Sponsorships::<T>::contains_key(&app_did), Error::<T>::SponsorshipNotFound);
+
+			T::Currency::transfer(&who, &Self::sponsor_account(&app_did), amount, ExistenceRequirement::KeepAlive)?;
+
+			// emit event
+			Self::deposit_event(Event::SponsorshipFunded { app_did: app_did.to_vec(), amount });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::withdraw_sponsorship())]
+		/// move funds from an app's sponsorship account back to its owner
+		pub fn withdraw_sponsorship(origin: OriginFor<T>, app_did: Did<T>, amount: BalanceOf<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			Self::owned_app(&who, &app_did)?;
+
+			T::Currency::transfer(&Self::sponsor_account(&app_did), &who, amount, ExistenceRequirement::AllowDeath)?;
+
+			// emit event
+			Self::deposit_event(Event::SponsorshipWithdrawn { app_did: app_did.to_vec(), amount });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::add_did_key())]
+		/// register a key that may sign operations for a DID
+		pub fn add_did_key(origin: OriginFor<T>, did: Did<T>, key: T::AccountId) -> DispatchResult {
+			Self::ensure_did_controller(origin, &did)?;
+
+			DidKeys::<T>::try_mutate(&did, |keys| -> DispatchResult {
+				ensure!(!keys.contains(&key), Error::<T>::DuplicateDidKey);
+				keys.try_push(key.clone()).map_err(|_| Error::<T>::DidKeysOverflow)?;
+				Ok(())
+			})?;
+
+			// emit event
+			Self::deposit_event(Event::DidKeyAdded { did: did.to_vec(), key });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::remove_did_key())]
+		/// remove a verification key from a DID
+		pub fn remove_did_key(origin: OriginFor<T>, did: Did<T>, key: T::AccountId) -> DispatchResult {
+			Self::ensure_did_controller(origin, &did)?;
+
+			DidKeys::<T>::try_mutate(&did, |keys| -> DispatchResult {
+				let index = keys.iter().position(|k| *k == key).ok_or(Error::<T>::DidKeyNotFound)?;
+				keys.remove(index);
+				Ok(())
+			})?;
+
+			// emit event
+			Self::deposit_event(Event::DidKeyRemoved { did: did.to_vec(), key });
+
+			Ok(())
+		}
+
+		#[pallet::weight({
+			let info = call.get_dispatch_info();
+			(info.weight.saturating_add(T::WeightInfo::submit_did_call(T::MaxDidKeys::get())), info.class)
+		})]
+		/// relay a call signed off-chain by a DID key, dispatching it with the DID as origin
+		pub fn submit_did_call(
+			origin: OriginFor<T>,
+			did: Did<T>,
+			call: Box<<T as Config>::RuntimeCall>,
+			nonce: u64,
+			expires: T::BlockNumber,
+			signature: T::DidSignature
+		) -> DispatchResult {
+			let relayer = ensure_signed(origin)?;
+
+			ensure!(frame_system::Pallet::<T>::block_number() <= expires, Error::<T>::DidCallExpired);
+			ensure!(nonce == DidNonces::<T>::get(&did), Error::<T>::InvalidDidNonce);
+
+			let payload = Self::did_call_payload(&did, nonce, expires, &call);
+			ensure!(Self::signed_by_did(&did, &payload, &signature), Error::<T>::BadDidSignature);
+
+			// the nonce is used up even when the inner call fails, so it can't be replayed later
+			DidNonces::<T>::insert(&did, nonce.saturating_add(1));
+
+			let origin: <T as Config>::RuntimeOrigin = RawOrigin::Did(did.clone()).into();
+			let result = (*call).dispatch(origin.into()).map(|_| ()).map_err(|e| e.error);
+
+			// emit event
+			Self::deposit_event(Event::DidCallDispatched { did: did.to_vec(), nonce, relayer, result });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::set_parameters())]
+		/// update the soft limits, held deposits follow new rates when their entry is next written
+		pub fn set_parameters(origin: OriginFor<T>, parameters: SamaritanParameters<T>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			// the hard bounds stay the upper caps, and every DID keeps room for its first document
+			ensure!(parameters.quorum_cap <= T::MaxQuorumMembersCount::get(), Error::<T>::ParameterOutOfBounds);
+			ensure!(
+				(1..=T::MaxCacheLength::get()).contains(&parameters.history_window),
+				Error::<T>::ParameterOutOfBounds
+			);
+
+			Parameters::<T>::put(&parameters);
+
+			// emit event
+			Self::deposit_event(Event::ParametersUpdated { parameters });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::approve_pairwise_recovery())]
+		/// approve an open pairwise DID recovery as a member of the master DID's trust quorum.
+		/// The DID changes hands once a majority of the quorum approved.
+		pub fn approve_pairwise_recovery(origin: OriginFor<T>, did: Did<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let mut recovery = PairwiseRecoveries::<T>::get(&did).ok_or(Error::<T>::RecoveryNotFound)?;
+			let member = Self::quorum_member(&who, &recovery.master_did).ok_or(Error::<T>::NotRecoveryAuthority)?;
+			ensure!(!recovery.approvals.contains(&member), Error::<T>::AlreadyApproved);
+
+			// members that left the quorum are dropped, so there is always room
+			let quorum = Self::quorum_of(&recovery.master_did);
+			recovery.approvals.retain(|index| quorum.contains(index));
+			recovery.approvals.try_push(member).map_err(|_| Error::<T>::QuorumOverflow)?;
+			PairwiseRecoveries::<T>::insert(&did, recovery);
+
+			Self::approve_or_finish(&did)
+		}
 	}
 }
 
+/// blocks a subscription charge is pushed back at most when agendas are full
+pub const MAX_CHARGE_DEFERRALS: u32 = 8;
+
 /// helper functions
 impl<T: Config> Pallet<T> {
+	/// whether a multi-block migration is still running
+	pub fn migrating() -> bool {
+		DocTranslateProgress::<T>::exists() ||
+			RekeyProgress::<T>::exists() ||
+			InterningQuorums::<T>::get() ||
+			PurgingSignUps::<T>::get() ||
+			BackfillProgress::<T>::exists() ||
+			DepositBackfillProgress::<T>::exists()
+	}
+
+	/// the index of a DID, interning it on first use. Entries are never removed, `payer` holds
+	/// a deposit for the two of them from then on.
+	pub(crate) fn intern_with_deposit(
+		payer: &T::AccountId,
+		did: &BoundedVec<u8, T::MaxDIDLength>
+	) -> Result<DidIndex, DispatchError> {
+		if let Some(index) = DidIndices::<T>::get(did) {
+			return Ok(index);
+		}
+
+		let index = Self::intern(did);
+		// one entry in each direction
+		Self::sync_deposit(payer, &DidIndices::<T>::hashed_key_for(did), (did, index).encoded_size().saturating_mul(2))?;
+
+		Ok(index)
+	}
+
+	/// the index of a DID, interning it on first use without a deposit, for genesis and migrations
+	pub(crate) fn intern(did: &BoundedVec<u8, T::MaxDIDLength>) -> DidIndex {
+		if let Some(index) = DidIndices::<T>::get(did) {
+			return index;
+		}
+
+		let index = NextDidIndex::<T>::mutate(|next| {
+			let index = *next;
+			*next = next.saturating_add(1);
+			index
+		});
+		DidIndices::<T>::insert(did, index);
+		IndexedDids::<T>::insert(index, did);
+
+		index
+	}
+
+	/// the DID a reference points to, indices must have been interned
+	fn resolve(did: DidRef<T>) -> Result<Did<T>, Error<T>> {
+		match did {
+			DidRef::Did(did) => Ok(did),
+			DidRef::Index(index) => IndexedDids::<T>::get(index).ok_or(Error::<T>::UnknownDidIndex),
+		}
+	}
+
+	/// account paying the storage deposits of a DID's entries
+	fn did_payer(did: &BoundedVec<u8, T::MaxDIDLength>) -> Result<T::AccountId, Error<T>> {
+		DidOwners::<T>::get(did).ok_or(Error::<T>::DIDNotFound)
+	}
+
+	/// hold a deposit for a storage entry that matches its encoded size,
+	/// moving it to the payer if someone else held it before
+	fn sync_deposit(payer: &T::AccountId, storage_key: &[u8], len: usize) -> DispatchResult {
+		let key = H256(sp_io::hashing::blake2_256(storage_key));
+		let deposit = Self::deposit_for(len);
+
+		match StorageDeposits::<T>::get(&key) {
+			Some((depositor, held)) if depositor == *payer => {
+				if deposit > held {
+					T::Currency::reserve(payer, deposit - held)?;
+				} else {
+					T::Currency::unreserve(payer, held - deposit);
+				}
+			},
+			prev => {
+				T::Currency::reserve(payer, deposit)?;
+				if let Some((depositor, held)) = prev {
+					T::Currency::unreserve(&depositor, held);
+				}
+			}
+		}
+
+		StorageDeposits::<T>::insert(&key, (payer.clone(), deposit));
+		Ok(())
+	}
+
+	/// the interned index of the caller's samaritan DID if it is in the trust quorum of `master_did`
+	fn quorum_member(who: &T::AccountId, master_did: &BoundedVec<u8, T::MaxDIDLength>) -> Option<DidIndex> {
+		let member = DidIndices::<T>::get(&SamaritanRegistry::<T>::get(who)?.did)?;
+		Self::quorum_of(master_did).contains(&member).then_some(member)
+	}
+
+	/// the trust quorum members of a DID, empty when it has none
+	fn quorum_of(did: &BoundedVec<u8, T::MaxDIDLength>) -> Vec<DidIndex> {
+		DidIndices::<T>::get(did)
+			.and_then(Quorums::<T>::get)
+			.map(|quorum| quorum.into_inner())
+			.unwrap_or_default()
+	}
+
+	/// hand the pairwise DID over once a majority of the current quorum approved its recovery
+	fn approve_or_finish(did: &BoundedVec<u8, T::MaxDIDLength>) -> DispatchResult {
+		let recovery = PairwiseRecoveries::<T>::get(did).ok_or(Error::<T>::RecoveryNotFound)?;
+		let quorum = Self::quorum_of(&recovery.master_did);
+		let approvals = recovery.approvals.iter().filter(|index| quorum.contains(index)).count();
+
+		if approvals > quorum.len() / 2 {
+			Self::finish_pairwise_recovery(did, recovery.new_owner);
+		} else {
+			// emit event
+			Self::deposit_event(Event::PairwiseRecoveryApproved { did: did.to_vec(), approvals: approvals as u32 });
+		}
+
+		Ok(())
+	}
+
+	/// give a pairwise DID to its new owner and close any recovery request
+	fn finish_pairwise_recovery(did: &BoundedVec<u8, T::MaxDIDLength>, new_owner: T::AccountId) {
+		if PairwiseRecoveries::<T>::contains_key(did) {
+			Self::release_deposit(&PairwiseRecoveries::<T>::hashed_key_for(did));
+			PairwiseRecoveries::<T>::remove(did);
+		}
+		DidOwners::<T>::insert(did, new_owner);
+		// signatures made for the previous owner can't be relayed anymore, and neither its
+		// keys nor the session keys it issued act for the DID
+		DidNonces::<T>::mutate(did, |nonce| *nonce = nonce.saturating_add(1));
+		Self::release_deposit(&DidKeys::<T>::hashed_key_for(did));
+		DidKeys::<T>::remove(did);
+		SessionEpochs::<T>::mutate(did, |epoch| *epoch = epoch.wrapping_add(1));
+
+		// emit event
+		Self::deposit_event(Event::PairwiseDidRecovered { did: did.to_vec() });
+	}
+
+	/// whether one of the DID's keys made the signature
+	fn signed_by_did(did: &BoundedVec<u8, T::MaxDIDLength>, payload: &[u8], signature: &T::DidSignature) -> bool {
+		DidKeys::<T>::get(did).iter().any(|key| signature.verify(payload, key))
+	}
+
+	/// refund the deposit held for a removed storage entry
+	fn release_deposit(storage_key: &[u8]) {
+		let key = H256(sp_io::hashing::blake2_256(storage_key));
+
+		if let Some((depositor, held)) = StorageDeposits::<T>::take(&key) {
+			T::Currency::unreserve(&depositor, held);
+		}
+	}
+
+	/// put a subscription on the charge agenda of a block
+	fn schedule_charge(
+		at: T::BlockNumber,
+		did: &BoundedVec<u8, T::MaxDIDLength>,
+		app_did: &BoundedVec<u8, T::MaxDIDLength>
+	) -> Result<(), Error<T>> {
+		ChargeAgenda::<T>::try_mutate(at, |due| {
+			due.try_push((did.clone(), app_did.clone())).map_err(|_| Error::<T>::ChargeAgendaFull)
+		})
+	}
+
+	/// collect a due subscription charge, retrying once after the grace period.
+	/// Returns the number of full agendas the next charge was pushed past.
+	fn process_charge(
+		now: T::BlockNumber,
+		did: BoundedVec<u8, T::MaxDIDLength>,
+		app_did: BoundedVec<u8, T::MaxDIDLength>
+	) -> u32 {
+		let mut sub = match Subscriptions::<T>::get(&did, &app_did) {
+			// entries left behind by an ended subscription are skipped
+			Some(sub) if sub.next_charge == now => sub,
+			_ => return 0,
+		};
+
+		let plan = SubscriptionPlans::<T>::get(&app_did, sub.plan_id).filter(|p| p.active);
+		let owner = AppRegistry::<T>::get(&app_did).map(|app| app.owner);
+
+		let (plan, owner) = match (plan, owner) {
+			(Some(plan), Some(owner)) if !sub.cancelled && sub.spent.saturating_add(plan.price) <= sub.cap =>
+				(plan, owner),
+			_ => {
+				Self::end_subscription(&did, &app_did);
+				return 0;
+			},
+		};
+
+		let paid = T::Currency::transfer(&sub.payer, &owner, plan.price, ExistenceRequirement::KeepAlive).is_ok();
+		if paid {
+			sub.spent = sub.spent.saturating_add(plan.price);
+			sub.past_due = false;
+			sub.next_charge = now.saturating_add(plan.period);
+
+			Self::deposit_event(Event::SubscriptionCharged {
+				did: did.to_vec(),
+				app_did: app_did.to_vec(),
+				amount: plan.price
+			});
+		} else {
+			Self::deposit_event(Event::SubscriptionChargeFailed { did: did.to_vec(), app_did: app_did.to_vec() });
+
+			// a charge is retried once, at the end of the grace period
+			if sub.past_due || plan.grace.is_zero() {
+				Self::end_subscription(&did, &app_did);
+				return 0;
+			}
+			sub.past_due = true;
+			sub.next_charge = now.saturating_add(plan.grace);
+		}
+
+		// a full agenda pushes the charge to the following blocks, a subscription that
+		// finds no room within `MAX_CHARGE_DEFERRALS` blocks ends
+		let mut at = sub.next_charge;
+		let mut deferrals = 0;
+		while Self::schedule_charge(at, &did, &app_did).is_err() {
+			if deferrals == MAX_CHARGE_DEFERRALS {
+				Self::end_subscription(&did, &app_did);
+				return deferrals;
+			}
+			deferrals += 1;
+			at = at.saturating_add(One::one());
+		}
+		sub.next_charge = at;
+		Subscriptions::<T>::insert(&did, &app_did, sub);
+
+		deferrals
+	}
+
+	fn end_subscription(did: &BoundedVec<u8, T::MaxDIDLength>, app_did: &BoundedVec<u8, T::MaxDIDLength>) {
+		Subscriptions::<T>::remove(did, app_did);
+		Self::deposit_event(Event::SubscriptionEnded { did: did.to_vec(), app_did: app_did.to_vec() });
+	}
+
+	/// make sure the origin controls the DID, either as the owning account or as a DID origin
+	fn ensure_did_controller(
+		origin: OriginFor<T>,
+		did: &BoundedVec<u8, T::MaxDIDLength>
+	) -> DispatchResult {
+		Self::ensure_controller(origin, did, None)
+	}
+
+	/// like `ensure_did_controller`, but session keys only act for the app they were issued to
+	fn ensure_app_controller(
+		origin: OriginFor<T>,
+		did: &BoundedVec<u8, T::MaxDIDLength>,
+		app_did: &BoundedVec<u8, T::MaxDIDLength>
+	) -> DispatchResult {
+		Self::ensure_controller(origin, did, Some(app_did))
+	}
+
+	/// the DID controller check, scoped to `app_did` for session keys when given
+	fn ensure_controller(
+		origin: OriginFor<T>,
+		did: &BoundedVec<u8, T::MaxDIDLength>,
+		app_did: Option<&BoundedVec<u8, T::MaxDIDLength>>
+	) -> DispatchResult {
+		let origin: <T as Config>::RuntimeOrigin = origin.into();
+		let origin: Result<RawOrigin<T>, <T as Config>::RuntimeOrigin> = origin.into();
+
+		match origin {
+			Ok(RawOrigin::Did(caller)) => {
+				ensure!(caller == *did, Error::<T>::NotDIDOwner);
+				Ok(())
+			},
+			Ok(RawOrigin::Session { did: caller, app_did: issued_to }) => {
+				ensure!(caller == *did, Error::<T>::NotDIDOwner);
+				ensure!(app_did.map_or(true, |app_did| issued_to == *app_did), Error::<T>::SessionAppMismatch);
+				Ok(())
+			},
+			Err(origin) => {
+				let origin: OriginFor<T> = origin.into();
+				let who = ensure_signed(origin)?;
+				Self::ensure_did_owner(&who, did)?;
+				Ok(())
+			},
+		}
+	}
+
+	/// make sure the origin is a session key the DID issued to the app
+	fn ensure_session_of(
+		origin: OriginFor<T>,
+		did: &BoundedVec<u8, T::MaxDIDLength>,
+		app_did: &BoundedVec<u8, T::MaxDIDLength>
+	) -> DispatchResult {
+		let origin: <T as Config>::RuntimeOrigin = origin.into();
+		let origin: Result<RawOrigin<T>, <T as Config>::RuntimeOrigin> = origin.into();
+
+		match origin {
+			Ok(RawOrigin::Session { did: caller, app_did: issued_to }) => {
+				ensure!(caller == *did, Error::<T>::NotDIDOwner);
+				ensure!(issued_to == *app_did, Error::<T>::SessionAppMismatch);
+				Ok(())
+			},
+			_ => Err(DispatchError::BadOrigin),
+		}
+	}
+
+	/// a signup token that has not expired
+	fn token_live(entry: &SignUpToken<T>) -> bool {
+		Parameters::<T>::get().signup_token_ttl
+			.map_or(true, |ttl| frame_system::Pallet::<T>::block_number() <= entry.issued.saturating_add(ttl))
+	}
+
+	/// a grant that has neither been revoked nor expired
+	fn is_live(grant: &ConsentGrant<T>) -> bool {
+		!grant.revoked &&
+			grant.expires_at.map_or(true, |e| e > T::TimeProvider::now().as_secs())
+	}
+
+	/// widen a content hash from a call to the bound it is stored under
+	fn stored_hash(hash: ContentHash<T>) -> Result<BoundedVec<u8, T::MaxHashLength>, Error<T>> {
+		hash.into_inner().try_into().map_err(|_| Error::<T>::ParameterOutOfBounds)
+	}
+
+	/// the next consent history head, chaining a replaced grant onto the previous head
+	pub fn consent_receipt(head: Option<&H256>, grant: &ConsentGrant<T>) -> H256 {
+		H256::from(sp_io::hashing::blake2_256(&(head, grant).encode()))
+	}
+
+	/// apply `f` to the release of an app with the given version
+	fn mutate_release(
+		app_did: &BoundedVec<u8, T::MaxDIDLength>,
+		version: &BoundedVec<u8, T::MaxVersionLength>,
+		f: impl FnOnce(&mut Release<T>)
+	) -> Result<(), Error<T>> {
+		AppReleases::<T>::try_mutate(app_did, |releases| {
+			let release = releases
+				.iter_mut()
+				.find(|r| r.version == *version)
+				.ok_or(Error::<T>::ReleaseNotFound)?;
+
+			f(release);
+			Ok(())
+		})
+	}
+
+	/// pairwise DIDs can only be used with the app they were created for
+	fn ensure_pairwise_scope(
+		did: &BoundedVec<u8, T::MaxDIDLength>,
+		app_did: &BoundedVec<u8, T::MaxDIDLength>
+	) -> Result<(), Error<T>> {
+		match PairwiseDids::<T>::get(did) {
+			Some(record) if record.app_did != *app_did => Err(Error::<T>::PairwiseAppMismatch),
+			_ => Ok(()),
+		}
+	}
+
+	/// make sure `who` controls the DID
+	fn ensure_did_owner(
+		who: &T::AccountId,
+		did: &BoundedVec<u8, T::MaxDIDLength>
+	) -> Result<(), Error<T>> {
+		match DidOwners::<T>::get(did) {
+			Some(owner) if owner == *who => Ok(()),
+			_ => Err(Error::<T>::NotDIDOwner),
+		}
+	}
+
+	/// fetch an app, making sure `who` owns it
+	fn owned_app(
+		who: &T::AccountId,
+		did: &BoundedVec<u8, T::MaxDIDLength>
+	) -> Result<AppInfo<T>, Error<T>> {
+		let app = AppRegistry::<T>::get(did).ok_or(Error::<T>::AppNotFound)?;
+		if app.owner != *who {
+			return Err(Error::<T>::NotAppOwner);
+		}
+
+		Ok(app)
+	}
+
+	/// bytes a DID key signs to authorize a relayed call
+	pub fn did_call_payload(
+		did: &BoundedVec<u8, T::MaxDIDLength>,
+		nonce: u64,
+		expires: T::BlockNumber,
+		call: &<T as Config>::RuntimeCall
+	) -> Vec<u8> {
+		// the genesis hash keeps signatures from being replayed on other chains
+		let genesis = frame_system::Pallet::<T>::block_hash(T::BlockNumber::zero());
+		(b"samaritan/did-call", genesis, did, nonce, expires, call).encode()
+	}
+
+	/// deposit held for a storage entry of the given encoded size
+	pub fn deposit_for(len: usize) -> BalanceOf<T> {
+		let bytes: BalanceOf<T> = (len as u32).into();
+		let parameters = Parameters::<T>::get();
+		parameters.deposit_base.saturating_add(parameters.deposit_per_byte.saturating_mul(bytes))
+	}
+
+	/// first block of the current free call quota period
+	pub fn quota_period_start() -> T::BlockNumber {
+		let now = frame_system::Pallet::<T>::block_number();
+		let period = T::FreeCallPeriod::get();
+
+		if period.is_zero() {
+			return now;
+		}
+		now - now % period
+	}
+
+	/// free samaritan calls an account can still make in the current period
+	pub fn free_calls_left(who: &T::AccountId) -> u32 {
+		// only registered samaritans get a quota
+		if !SamaritanRegistry::<T>::contains_key(who) {
+			return 0;
+		}
+
+		let start = Self::quota_period_start();
+		let used = FreeQuotas::<T>::get(who)
+			.filter(|q| q.period_start == start)
+			.map(|q| q.used)
+			.unwrap_or(0);
+
+		T::FreeCallsPerPeriod::get().saturating_sub(used)
+	}
+
+	pub(crate) fn use_free_call(who: &T::AccountId) {
+		let period_start = Self::quota_period_start();
+
+		FreeQuotas::<T>::mutate(who, |quota| {
+			let used = quota.as_ref()
+				.filter(|q| q.period_start == period_start)
+				.map(|q| q.used)
+				.unwrap_or(0);
+			*quota = Some(FreeQuota { period_start, used: used.saturating_add(1) });
+		});
+	}
+
+	/// account holding the budget an app pays sponsored fees from
+	pub fn sponsor_account(app_did: &BoundedVec<u8, T::MaxDIDLength>) -> T::AccountId {
+		// DIDs share long prefixes, so they are hashed before being folded into the account id
+		T::PalletId::get().into_sub_account_truncating(sp_io::hashing::blake2_256(app_did))
+	}
+
+	/// look up a registered app
+	pub fn app_info(did_str: Vec<u8>) -> Option<AppInfo<T>> {
+		let did = BoundedVec::<u8, T::MaxDIDLength>::try_from(did_str).ok()?;
+
+		AppRegistry::<T>::get(&did)
+	}
+
+	/// salted commitment of a signup token: blake2(token ++ app DID ++ user DID)
+	pub fn token_commitment(token: &H256, app_did: &[u8], did: &[u8]) -> H256 {
+		let mut preimage = token.as_bytes().to_vec();
+		preimage.extend_from_slice(app_did);
+		preimage.extend_from_slice(did);
+
+		H256::from(sp_io::hashing::blake2_256(&preimage))
+	}
+
+	/// look up the unexpired signup entry for an app and user
+	fn signup_entry(app_did_str: &Vec<u8>, did_str: &Vec<u8>) -> Option<SignUpToken<T>> {
+		let adid = BoundedVec::<u8, T::MaxDIDLength>::try_from(app_did_str.clone()).ok()?;
+		let udid = BoundedVec::<u8, T::MaxDIDLength>::try_from(did_str.clone()).ok()?;
+
+		SignUpDataRegistry::<T>::get(&adid, &udid).filter(Self::token_live)
+	}
+
+	/// check a revealed signup token without consuming it
+	pub fn verify_signup_token(app_did_str: Vec<u8>, did_str: Vec<u8>, token: H256) -> bool {
+		match Self::signup_entry(&app_did_str, &did_str) {
+			Some(entry) => Self::token_commitment(&token, &app_did_str, &did_str) == entry.commitment,
+			None => false,
+		}
+	}
+
+	/// the consent a user gave to an app
+	pub fn consent(did_str: Vec<u8>, app_did_str: Vec<u8>) -> Option<ConsentGrant<T>> {
+		let did = BoundedVec::<u8, T::MaxDIDLength>::try_from(did_str).ok()?;
+		let adid = BoundedVec::<u8, T::MaxDIDLength>::try_from(app_did_str).ok()?;
+
+		ConsentGrants::<T>::get(&did, &adid)
+	}
+
+	/// whether the user currently lets the app read a scope
+	pub fn has_consent(did_str: Vec<u8>, app_did_str: Vec<u8>, scope: Vec<u8>) -> bool {
+		match Self::consent(did_str, app_did_str) {
+			Some(grant) => Self::is_live(&grant) && grant.scopes.iter().any(|s| s[..] == scope[..]),
+			None => false,
+		}
+	}
+
+	/// bytes a master DID key signs to pair a pseudonymous DID with an app
+	pub fn pairwise_payload(did: &[u8], app_did: &[u8]) -> Vec<u8> {
+		let genesis = frame_system::Pallet::<T>::block_hash(T::BlockNumber::zero());
+		(b"samaritan/pairwise", genesis, did, app_did).encode()
+	}
+
+	/// commitment linking a pairwise DID to its master DID: blake2(pairwise ++ master ++ master signature)
+	pub fn pairwise_link(did: &[u8], master_did: &[u8], master_signature: &T::DidSignature) -> H256 {
+		H256::from(sp_io::hashing::blake2_256(&(did, master_did, master_signature).encode()))
+	}
+
+	/// check a claimed link between a pairwise DID and a master DID
+	pub fn verify_pairwise_link(did_str: Vec<u8>, master_did_str: Vec<u8>, master_signature: T::DidSignature) -> bool {
+		let (did, master_did) = match (
+			BoundedVec::<u8, T::MaxDIDLength>::try_from(did_str),
+			BoundedVec::<u8, T::MaxDIDLength>::try_from(master_did_str)
+		) {
+			(Ok(did), Ok(master_did)) => (did, master_did),
+			_ => return false,
+		};
+
+		match PairwiseDids::<T>::get(&did) {
+			Some(record) =>
+				Self::pairwise_link(&did, &master_did, &master_signature) == record.link_commitment &&
+					Self::signed_by_did(&master_did, &Self::pairwise_payload(&did, &record.app_did), &master_signature),
+			None => false,
+		}
+	}
+
+	/// the newest release of an app on a channel that has not been yanked
+	pub fn latest_release(app_did_str: Vec<u8>, channel: ReleaseChannel) -> Option<Release<T>> {
+		let adid = BoundedVec::<u8, T::MaxDIDLength>::try_from(app_did_str).ok()?;
+
+		AppReleases::<T>::get(&adid)
+			.into_iter()
+			.rev()
+			.find(|r| r.channel == channel && !r.yanked)
+	}
+
+	/// page through a user's access history, starting at sequence number `from`
+	pub fn access_log(did_str: Vec<u8>, from: u64, limit: u32) -> Vec<AccessRecord<T>> {
+		let did = match BoundedVec::<u8, T::MaxDIDLength>::try_from(did_str) {
+			Ok(did) => did,
+			Err(_) => return Vec::new(),
+		};
+
+		AccessLog::<T>::get(&did)
+			.into_iter()
+			.filter(|r| r.seq >= from)
+			.take(limit as usize)
+			.collect()
+	}
+
+	/// the nonce the next relayed call for the DID must carry
+	pub fn did_nonce_of(did_str: Vec<u8>) -> u64 {
+		BoundedVec::<u8, T::MaxDIDLength>::try_from(did_str)
+			.map(|did| DidNonces::<T>::get(&did))
+			.unwrap_or(0)
+	}
+
+	/// check the storage invariants, used by `try_state` and the fuzz harness
+	#[cfg(any(feature = "try-runtime", feature = "fuzzing", test))]
+	pub fn do_try_state() -> Result<(), &'static str> {
+		// the maps are only partially readable until the migrations are done
+		if Self::migrating() {
+			return Ok(());
+		}
+
+		let parameters = Parameters::<T>::get();
+		ensure!(parameters.quorum_cap <= T::MaxQuorumMembersCount::get(), "quorum cap above its bound");
+		ensure!(
+			(1..=T::MaxCacheLength::get()).contains(&parameters.history_window),
+			"history window outside its bounds"
+		);
+
+		// owners are backfilled by the v5 migration, checked above
+		for (who, sam) in SamaritanRegistry::<T>::iter() {
+			ensure!(DocMetaRegistry::<T>::contains_key(&sam.did), "samaritan DID without document history");
+			ensure!(DidOwners::<T>::get(&sam.did) == Some(who), "samaritan does not own its DID");
+		}
+
+		for (did, app) in AppRegistry::<T>::iter() {
+			ensure!(DidOwners::<T>::get(&did) == Some(app.owner), "app owner does not own the app DID");
+		}
+
+		for (_, docs) in DocMetaRegistry::<T>::iter() {
+			ensure!(!docs.is_empty(), "empty DID document history");
+			ensure!(docs.iter().filter(|doc| doc.active).count() <= 1, "more than one active DID document");
+			ensure!(
+				docs.windows(2).all(|w| w[0].version < w[1].version && w[0].created <= w[1].created),
+				"DID document versions or creation times out of order"
+			);
+		}
+
+		for (_, quorum) in Quorums::<T>::iter() {
+			ensure!(
+				quorum.iter().enumerate().all(|(i, member)| !quorum[..i].contains(member)),
+				"duplicate trust quorum member"
+			);
+			ensure!(quorum.iter().all(|member| IndexedDids::<T>::contains_key(member)), "quorum member not interned");
+		}
+
+		for (index, did) in IndexedDids::<T>::iter() {
+			ensure!(DidIndices::<T>::get(&did) == Some(index), "DID index tables disagree");
+		}
+
+		for (did, _) in PairwiseRecoveries::<T>::iter() {
+			ensure!(PairwiseDids::<T>::contains_key(&did), "recovery open for an unknown pairwise DID");
+		}
+
+		for (app_did, did, _) in SignUpDataRegistry::<T>::iter() {
+			ensure!(DidOwners::<T>::contains_key(&app_did), "signup token for an unowned app DID");
+			ensure!(DidOwners::<T>::contains_key(&did), "signup token for an unowned user DID");
+		}
+
+		Ok(())
+	}
+
+	/// the interned index of a DID
+	pub fn did_index_of(did_str: Vec<u8>) -> Option<DidIndex> {
+		BoundedVec::<u8, T::MaxDIDLength>::try_from(did_str)
+			.ok()
+			.and_then(|did| DidIndices::<T>::get(&did))
+	}
+
+	/// the trust quorum of a DID as full DIDs
+	pub fn trust_quorum(did_str: Vec<u8>) -> Vec<Vec<u8>> {
+		BoundedVec::<u8, T::MaxDIDLength>::try_from(did_str)
+			.ok()
+			.and_then(|did| DidIndices::<T>::get(&did))
+			.and_then(Quorums::<T>::get)
+			.map(|quorum| quorum.into_iter().filter_map(IndexedDids::<T>::get).map(|did| did.to_vec()).collect())
+			.unwrap_or_default()
+	}
+
 	/// convert account id to string
 	pub fn vec_to_str(
 		vector: &Vec<u8>
@@ -0,0 +1,776 @@
+//! Storage migrations of the samaritan pallet, one module per storage version.
+
+use super::*;
+use codec::Decode;
+use frame_support::{
+	storage::{unhashed, StoragePrefixedMap},
+	traits::{Contains, GetStorageVersion, IsSubType, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+	Blake2_128Concat, StorageHasher, Twox64Concat,
+};
+use sp_runtime::Perbill;
+use sp_std::marker::PhantomData;
+
+/// share of the block weight multi-block migrations may use
+pub const BLOCK_SHARE: Perbill = Perbill::from_percent(25);
+
+/// weight multi-block migrations may use in a block
+pub fn block_budget<T: Config>() -> Weight {
+	BLOCK_SHARE * T::BlockWeights::get().max_block
+}
+
+/// carry on with the multi-block migrations within `limit`, oldest first
+pub fn step<T: Config>(limit: Weight) -> Weight {
+	if DocTranslateProgress::<T>::exists() {
+		v1::step::<T>(limit)
+	} else if RekeyProgress::<T>::exists() {
+		v2::step::<T>(limit)
+	} else if InterningQuorums::<T>::get() {
+		v3::step::<T>(limit)
+	} else if PurgingSignUps::<T>::get() {
+		v4::step::<T>(limit)
+	} else if BackfillProgress::<T>::exists() {
+		v5::step::<T>(limit)
+	} else {
+		v6::step::<T>(limit)
+	}
+}
+
+/// call filter pausing samaritan calls until the multi-block migrations are done
+pub struct PauseWhileMigrating<T>(PhantomData<T>);
+
+impl<T: Config> Contains<<T as Config>::RuntimeCall> for PauseWhileMigrating<T> {
+	fn contains(call: &<T as Config>::RuntimeCall) -> bool {
+		<<T as Config>::RuntimeCall as IsSubType<Call<T>>>::is_sub_type(call).is_none() || !Pallet::<T>::migrating()
+	}
+}
+
+pub mod v1 {
+	use super::*;
+
+	/// `DocMetadata` as stored before v1
+	#[derive(Encode, Decode)]
+	pub struct OldDocMetadata<T: Config> {
+		pub version: u64,
+		pub hl: BoundedVec<u8, T::MaxHashLength>,
+		pub created: u64,
+		pub active: bool,
+	}
+
+	/// the document registry as it was keyed up to v1
+	#[frame_support::storage_alias]
+	pub type DocMetaRegistry<T: Config> = StorageMap<
+		Pallet<T>,
+		Twox64Concat,
+		BoundedVec<u8, <T as Config>::MaxDIDLength>,
+		BoundedVec<DocMetadata<T>, <T as Config>::MaxCacheLength>,
+	>;
+
+	/// document histories translated per block at most
+	pub const MAX_TRANSLATIONS_PER_BLOCK: u32 = 256;
+
+	/// the v1 layout of a document, its block is unknown and `updated` starts out as `created`
+	fn translate<T: Config>(old: OldDocMetadata<T>) -> DocMetadata<T> {
+		DocMetadata {
+			version: old.version,
+			hl: old.hl,
+			created: old.created,
+			active: old.active,
+			updated: old.created,
+			block: Zero::zero(),
+		}
+	}
+
+	/// translate document histories until `limit` or `MAX_TRANSLATIONS_PER_BLOCK` is reached, returning the weight used
+	pub fn step<T: Config>(limit: Weight) -> Weight {
+		let db = T::DbWeight::get();
+
+		let mut cursor = match DocTranslateProgress::<T>::get() {
+			Some(cursor) => cursor,
+			None => return db.reads(1),
+		};
+
+		// cursor read and write, then per entry the next key and its value and writing the value
+		let mut used = db.reads_writes(1, 1);
+		let per_entry = db.reads_writes(2, 1);
+		let prefix = DocMetaRegistry::<T>::final_prefix();
+		let mut translated = 0u32;
+
+		while translated < MAX_TRANSLATIONS_PER_BLOCK && used.saturating_add(per_entry) <= limit {
+			used = used.saturating_add(per_entry);
+			let from = cursor.last.as_ref().map(|key| key.to_vec()).unwrap_or_else(|| prefix.to_vec());
+
+			let key = match sp_io::storage::next_key(&from).filter(|key| key.starts_with(&prefix)) {
+				Some(key) => key,
+				None => {
+					DocTranslateProgress::<T>::kill();
+					log::info!(target: "runtime::samaritan", "DID documents translated to v1");
+					return used;
+				},
+			};
+
+			// histories are rewritten in place, the cursor keeps them from being translated twice
+			match unhashed::get::<Vec<OldDocMetadata<T>>>(&key) {
+				// same length as before, no history is dropped
+				Some(docs) => unhashed::put(&key, &docs.into_iter().map(translate::<T>).collect::<Vec<_>>()),
+				None => log::error!(target: "runtime::samaritan", "DID document history does not decode, left as it is"),
+			}
+			translated += 1;
+
+			cursor.last = match key.try_into() {
+				Ok(last) => Some(last),
+				// no DID key is this long, leave the rest of the map alone
+				Err(_) => {
+					log::error!(target: "runtime::samaritan", "storage key over the cursor bound in the document registry");
+					DocTranslateProgress::<T>::kill();
+					return used;
+				},
+			};
+		}
+
+		DocTranslateProgress::<T>::put(cursor);
+		used
+	}
+
+	/// add the `updated` time and publishing `block` to every DID document. This only starts
+	/// the translation, `on_initialize` carries it out over as many blocks as it takes.
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 0 {
+				log::info!(target: "runtime::samaritan", "skipping v1 migration, already applied");
+				return T::DbWeight::get().reads(1);
+			}
+
+			DocTranslateProgress::<T>::put(v2::Cursor { map: 0, last: None });
+			StorageVersion::new(1).put::<Pallet<T>>();
+			log::info!(target: "runtime::samaritan", "started translating DID documents to v1");
+
+			T::DbWeight::get().reads_writes(1, 2)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), &'static str> {
+			ensure!(DocTranslateProgress::<T>::exists(), "document translation did not start");
+			// later migrations may have run in the same upgrade
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 1, "storage version not bumped to v1");
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v2 {
+	use super::*;
+	use codec::MaxEncodedLen;
+	use frame_support::{CloneNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound};
+	use scale_info::TypeInfo;
+
+	/// longest raw storage key a cursor has to hold, a double map keyed by two of the longest
+	/// DIDs or accounts. Each key is behind a 16 byte hash, a DID behind a length of at most 5 bytes.
+	pub struct MaxKeyLength<T>(PhantomData<T>);
+
+	impl<T: Config> Get<u32> for MaxKeyLength<T> {
+		fn get() -> u32 {
+			let key = T::MaxDIDLength::get().saturating_add(5).max(T::AccountId::max_encoded_len() as u32);
+			// pallet and map prefix
+			32u32.saturating_add(key.saturating_add(16).saturating_mul(2))
+		}
+	}
+
+	/// entries re-keyed per block at most, keeps the proof size of a block in check
+	pub const MAX_REKEYS_PER_BLOCK: u32 = 256;
+
+	/// the trust quorums as they were stored by DID up to v2
+	#[frame_support::storage_alias]
+	pub type TrustQuorum<T: Config> = StorageMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		BoundedVec<u8, <T as Config>::MaxDIDLength>,
+		BoundedVec<BoundedVec<u8, <T as Config>::MaxDIDLength>, <T as Config>::MaxQuorumMembersCount>,
+	>;
+
+	/// where the re-keying continues in the next block
+	#[derive(CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, Encode, Decode, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct Cursor<T: Config> {
+		/// index of the map being re-keyed
+		pub map: u8,
+		/// last raw key visited in that map
+		pub last: Option<BoundedVec<u8, MaxKeyLength<T>>>,
+	}
+
+	/// prefixes of the maps keyed by user chosen DIDs, and whether they are double maps
+	fn maps<T: Config>() -> [([u8; 32], bool); 4] {
+		[
+			(DocMetaRegistry::<T>::final_prefix(), false),
+			(ProfileRegistry::<T>::final_prefix(), false),
+			(TrustQuorum::<T>::final_prefix(), false),
+			(SignUpDataRegistry::<T>::final_prefix(), true),
+		]
+	}
+
+	/// split `twox64(key) ++ key` off the front of `raw`, returning the key and the rest
+	fn take_twox_key(raw: &[u8]) -> Option<(&[u8], &[u8])> {
+		let encoded = raw.get(8..)?;
+
+		// DIDs are length prefixed, decoding one tells where the key ends
+		let mut input = encoded;
+		Vec::<u8>::decode(&mut input).ok()?;
+		let (key, rest) = encoded.split_at(encoded.len() - input.len());
+
+		(Twox64Concat::hash(key)[..8] == raw[..8]).then(|| (key, rest))
+	}
+
+	/// the `Blake2_128Concat` key suffix for a `Twox64Concat` one, `None` if already re-keyed
+	fn rekeyed(suffix: &[u8], double: bool) -> Option<Vec<u8>> {
+		let (k1, rest) = take_twox_key(suffix)?;
+		let mut out = Blake2_128Concat::hash(k1);
+
+		let rest = if double {
+			let (k2, rest) = take_twox_key(rest)?;
+			out.extend(Blake2_128Concat::hash(k2));
+			rest
+		} else {
+			rest
+		};
+
+		rest.is_empty().then(|| out)
+	}
+
+	/// re-key entries until `limit` or `MAX_REKEYS_PER_BLOCK` is reached, returning the weight used
+	pub fn step<T: Config>(limit: Weight) -> Weight {
+		let db = T::DbWeight::get();
+
+		let mut cursor = match RekeyProgress::<T>::get() {
+			Some(cursor) => cursor,
+			None => return db.reads(1),
+		};
+
+		// cursor read and write, then per entry the next key, value and deposit reads
+		// and the writes moving value and deposit
+		let mut used = db.reads_writes(1, 1);
+		let per_entry = db.reads_writes(3, 4);
+		let maps = maps::<T>();
+		let mut moved = 0u32;
+
+		while moved < MAX_REKEYS_PER_BLOCK && used.saturating_add(per_entry) <= limit {
+			let (prefix, double) = match maps.get(cursor.map as usize) {
+				Some(map) => *map,
+				None => {
+					RekeyProgress::<T>::kill();
+					log::info!(target: "runtime::samaritan", "re-keying to Blake2_128Concat complete");
+					return used;
+				},
+			};
+
+			used = used.saturating_add(per_entry);
+			let from = cursor.last.as_ref().map(|key| key.to_vec()).unwrap_or_else(|| prefix.to_vec());
+
+			let key = match sp_io::storage::next_key(&from).filter(|key| key.starts_with(&prefix)) {
+				Some(key) => key,
+				None => {
+					cursor = Cursor { map: cursor.map + 1, last: None };
+					continue;
+				},
+			};
+
+			// keys written under the new hasher are passed over
+			if let Some(suffix) = rekeyed(&key[prefix.len()..], double) {
+				let new_key = [&prefix[..], &suffix[..]].concat();
+
+				if let Some(value) = unhashed::get_raw(&key) {
+					unhashed::put_raw(&new_key, &value);
+				}
+				unhashed::kill(&key);
+
+				// deposits are held under the hash of the storage key
+				let old_deposit = H256(sp_io::hashing::blake2_256(&key));
+				if let Some(deposit) = StorageDeposits::<T>::take(&old_deposit) {
+					StorageDeposits::<T>::insert(H256(sp_io::hashing::blake2_256(&new_key)), deposit);
+				}
+
+				moved += 1;
+			}
+
+			cursor.last = match key.try_into() {
+				Ok(last) => Some(last),
+				// no DID key is this long, leave the rest of the map alone
+				Err(_) => {
+					log::error!(target: "runtime::samaritan", "storage key over the cursor bound in map {}", cursor.map);
+					cursor = Cursor { map: cursor.map + 1, last: None };
+					continue;
+				},
+			};
+		}
+
+		RekeyProgress::<T>::put(cursor);
+		used
+	}
+
+	/// move the DID keyed maps from `Twox64Concat` to `Blake2_128Concat`. This only starts the
+	/// re-keying, `on_initialize` carries it out over as many blocks as it takes.
+	pub struct MigrateToV2<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 1 {
+				log::info!(target: "runtime::samaritan", "skipping v2 migration, storage is not at v1");
+				return T::DbWeight::get().reads(1);
+			}
+
+			RekeyProgress::<T>::put(Cursor { map: 0, last: None });
+			StorageVersion::new(2).put::<Pallet<T>>();
+			log::info!(target: "runtime::samaritan", "started re-keying to Blake2_128Concat");
+
+			T::DbWeight::get().reads_writes(1, 2)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), &'static str> {
+			ensure!(RekeyProgress::<T>::exists(), "re-keying did not start");
+			// later migrations may have run in the same upgrade
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 2, "storage version not bumped to v2");
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v3 {
+	use super::*;
+
+	/// move the trust quorums from full DIDs to interned indices. Like v2 this only starts the
+	/// migration, `on_initialize` drains the old quorums once the re-keying is done.
+	pub struct MigrateToV3<T>(PhantomData<T>);
+
+	/// raw key of the last old quorum that could not be moved, they stay in the old map
+	#[frame_support::storage_alias]
+	pub type LastKeptQuorum<T: Config> = StorageValue<Pallet<T>, BoundedVec<u8, v2::MaxKeyLength<T>>>;
+
+	/// move quorums until `limit` is reached, returning the weight used
+	pub fn step<T: Config>(limit: Weight) -> Weight {
+		let db = T::DbWeight::get();
+
+		if !InterningQuorums::<T>::get() {
+			return db.reads(1);
+		}
+
+		// the old quorum and its deposit, then interning the owner and every member
+		// and writing the new quorum and deposit
+		let members = T::MaxQuorumMembersCount::get() as u64 + 1;
+		let per_quorum = db.reads_writes(2 + members, 4 + 3 * members);
+		let mut used = db.reads(2);
+
+		// moved quorums are removed from the old map and kept ones are passed over,
+		// so the next block picks up where this one stopped
+		let mut old = match LastKeptQuorum::<T>::get() {
+			Some(last) => v2::TrustQuorum::<T>::iter_from(last.to_vec()),
+			None => v2::TrustQuorum::<T>::iter(),
+		};
+		while used.saturating_add(per_quorum) <= limit {
+			used = used.saturating_add(per_quorum);
+
+			let (did, dids) = match old.next() {
+				Some(quorum) => quorum,
+				None => {
+					InterningQuorums::<T>::kill();
+					LastKeptQuorum::<T>::kill();
+					log::info!(target: "runtime::samaritan", "trust quorums moved to interned DIDs");
+					return used.saturating_add(db.writes(2));
+				},
+			};
+			let old_key = v2::TrustQuorum::<T>::hashed_key_for(&did);
+
+			let index = Pallet::<T>::intern(&did);
+			let quorum: Vec<DidIndex> = dids.iter().map(|member| Pallet::<T>::intern(member)).collect();
+			// same bound as before, so this should not fail. If it does the quorum is not dropped
+			let quorum: BoundedVec<DidIndex, T::MaxQuorumMembersCount> = match quorum.try_into() {
+				Ok(quorum) => quorum,
+				Err(_) => {
+					log::error!(target: "runtime::samaritan", "quorum {} over its bound, kept in the old map", index);
+					match old_key.try_into() {
+						Ok(last) => LastKeptQuorum::<T>::put(last),
+						Err(_) => log::error!(target: "runtime::samaritan", "storage key of quorum {} over the cursor bound", index),
+					}
+					continue;
+				},
+			};
+
+			// the deposit follows the quorum to its new, smaller entry
+			let payer = StorageDeposits::<T>::get(H256(sp_io::hashing::blake2_256(&old_key))).map(|(who, _)| who);
+			Pallet::<T>::release_deposit(&old_key);
+			if let Some(payer) = payer {
+				if Pallet::<T>::sync_deposit(&payer, &Quorums::<T>::hashed_key_for(index), quorum.encoded_size()).is_err() {
+					log::error!(target: "runtime::samaritan", "could not hold the deposit of quorum {}", index);
+				}
+			}
+
+			v2::TrustQuorum::<T>::remove(&did);
+			Quorums::<T>::insert(index, quorum);
+		}
+
+		used
+	}
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 2 {
+				log::info!(target: "runtime::samaritan", "skipping v3 migration, storage is not at v2");
+				return T::DbWeight::get().reads(1);
+			}
+
+			InterningQuorums::<T>::put(true);
+			StorageVersion::new(3).put::<Pallet<T>>();
+			log::info!(target: "runtime::samaritan", "started moving trust quorums to interned DIDs");
+
+			T::DbWeight::get().reads_writes(1, 2)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), &'static str> {
+			ensure!(InterningQuorums::<T>::get(), "quorum interning did not start");
+			// later migrations may have run in the same upgrade
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 3, "storage version not bumped to v3");
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v4 {
+	use super::*;
+
+	/// signup entries removed per block at most
+	pub const MAX_PURGES_PER_BLOCK: u32 = 256;
+
+	/// remove signup entries until `limit` or `MAX_PURGES_PER_BLOCK` is reached, returning the weight used
+	pub fn step<T: Config>(limit: Weight) -> Weight {
+		let db = T::DbWeight::get();
+
+		if !PurgingSignUps::<T>::get() {
+			return db.reads(1);
+		}
+
+		// the next key and its deposit, then removing both and refunding the depositor
+		let per_entry = db.reads_writes(3, 3);
+		let prefix = SignUpDataRegistry::<T>::final_prefix();
+		let mut used = db.reads(1);
+		let mut purged = 0u32;
+
+		while purged < MAX_PURGES_PER_BLOCK && used.saturating_add(per_entry) <= limit {
+			used = used.saturating_add(per_entry);
+
+			// removed entries are gone, so the next block picks up where this one stopped
+			let key = match sp_io::storage::next_key(&prefix).filter(|key| key.starts_with(&prefix)) {
+				Some(key) => key,
+				None => {
+					PurgingSignUps::<T>::kill();
+					log::info!(target: "runtime::samaritan", "signup entries of earlier layouts removed");
+					return used.saturating_add(db.writes(1));
+				},
+			};
+
+			// raw keys, values in the plaintext layout don't decode
+			Pallet::<T>::release_deposit(&key);
+			unhashed::kill(&key);
+			purged += 1;
+		}
+
+		used
+	}
+
+	/// remove every signup entry, including plaintext tokens stored before commitments were
+	/// introduced. Pending signups have to be started again. Like v3 this only starts the
+	/// migration, `on_initialize` removes the entries once the earlier ones are done.
+	pub struct MigrateToV4<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV4<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 3 {
+				log::info!(target: "runtime::samaritan", "skipping v4 migration, storage is not at v3");
+				return T::DbWeight::get().reads(1);
+			}
+
+			PurgingSignUps::<T>::put(true);
+			StorageVersion::new(4).put::<Pallet<T>>();
+			log::info!(target: "runtime::samaritan", "started removing signup entries of earlier layouts");
+
+			T::DbWeight::get().reads_writes(1, 2)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), &'static str> {
+			ensure!(PurgingSignUps::<T>::get(), "signup purge did not start");
+			// later migrations may have run in the same upgrade
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 4, "storage version not bumped to v4");
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v5 {
+	use super::*;
+	use v2::Cursor;
+
+	/// owners recorded per block at most
+	pub const MAX_BACKFILLS_PER_BLOCK: u32 = 256;
+
+	/// record the owner of a DID registered before `DidOwners` existed
+	fn backfill<T: Config>(did: &BoundedVec<u8, T::MaxDIDLength>, owner: &T::AccountId) {
+		match DidOwners::<T>::get(did) {
+			None => DidOwners::<T>::insert(did, owner),
+			Some(current) if current != *owner => {
+				log::error!(target: "runtime::samaritan", "DID already owned by another account, left as it is");
+			},
+			Some(_) => {},
+		}
+	}
+
+	/// the next registry entry after the cursor as its raw key, DID and owner
+	fn next_entry<T: Config>(cursor: &Cursor<T>) -> Option<Option<(Vec<u8>, BoundedVec<u8, T::MaxDIDLength>, T::AccountId)>> {
+		let from = |prefix: [u8; 32]| cursor.last.as_ref().map(|key| key.to_vec()).unwrap_or_else(|| prefix.to_vec());
+
+		match cursor.map {
+			0 => Some(
+				SamaritanRegistry::<T>::iter_from(from(SamaritanRegistry::<T>::final_prefix()))
+					.next()
+					.map(|(who, sam)| (SamaritanRegistry::<T>::hashed_key_for(&who), sam.did, who))
+			),
+			1 => Some(
+				AppRegistry::<T>::iter_from(from(AppRegistry::<T>::final_prefix()))
+					.next()
+					.map(|(did, app)| (AppRegistry::<T>::hashed_key_for(&did), did, app.owner))
+			),
+			_ => None,
+		}
+	}
+
+	/// record owners until `limit` or `MAX_BACKFILLS_PER_BLOCK` is reached, returning the weight used
+	pub fn step<T: Config>(limit: Weight) -> Weight {
+		let db = T::DbWeight::get();
+
+		let mut cursor = match BackfillProgress::<T>::get() {
+			Some(cursor) => cursor,
+			None => return db.reads(1),
+		};
+
+		// cursor read and write, then per entry the entry and its current owner and writing the owner
+		let mut used = db.reads_writes(1, 1);
+		let per_entry = db.reads_writes(2, 1);
+		let mut recorded = 0u32;
+
+		while recorded < MAX_BACKFILLS_PER_BLOCK && used.saturating_add(per_entry) <= limit {
+			used = used.saturating_add(per_entry);
+
+			let (key, did, owner) = match next_entry::<T>(&cursor) {
+				Some(Some(entry)) => entry,
+				Some(None) => {
+					cursor = Cursor { map: cursor.map + 1, last: None };
+					continue;
+				},
+				None => {
+					BackfillProgress::<T>::kill();
+					log::info!(target: "runtime::samaritan", "owners of earlier DIDs recorded");
+					return used;
+				},
+			};
+
+			backfill::<T>(&did, &owner);
+			recorded += 1;
+
+			cursor.last = match key.try_into() {
+				Ok(last) => Some(last),
+				// no registry key is this long, leave the rest of the map alone
+				Err(_) => {
+					log::error!(target: "runtime::samaritan", "storage key over the cursor bound in registry {}", cursor.map);
+					cursor = Cursor { map: cursor.map + 1, last: None };
+					continue;
+				},
+			};
+		}
+
+		BackfillProgress::<T>::put(cursor);
+		used
+	}
+
+	/// record the owners of samaritan and app DIDs registered before `DidOwners` was
+	/// introduced, without them their owners can't act for the DIDs. Like v4 this only
+	/// starts the migration, `on_initialize` records the owners once the earlier ones are done.
+	pub struct MigrateToV5<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV5<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 4 {
+				log::info!(target: "runtime::samaritan", "skipping v5 migration, storage is not at v4");
+				return T::DbWeight::get().reads(1);
+			}
+
+			BackfillProgress::<T>::put(Cursor { map: 0, last: None });
+			StorageVersion::new(5).put::<Pallet<T>>();
+			log::info!(target: "runtime::samaritan", "started recording the owners of earlier DIDs");
+
+			T::DbWeight::get().reads_writes(1, 2)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), &'static str> {
+			ensure!(BackfillProgress::<T>::exists(), "owner backfill did not start");
+			// later migrations may have run in the same upgrade
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 5, "storage version not bumped to v5");
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v6 {
+	use super::*;
+	use v2::Cursor;
+
+	/// entries charged per block at most
+	pub const MAX_CHARGES_PER_BLOCK: u32 = 256;
+
+	/// an entry as its raw key, the account paying for it and the size its deposit is held for
+	type Entry<T> = (Vec<u8>, Option<<T as frame_system::Config>::AccountId>, usize);
+
+	/// hold the deposit of an entry stored before deposits existed. Entries without an owner,
+	/// or whose owner can't pay, are logged and left free.
+	fn charge<T: Config>((key, payer, len): Entry<T>) {
+		if StorageDeposits::<T>::contains_key(H256(sp_io::hashing::blake2_256(&key))) {
+			return;
+		}
+
+		match payer {
+			Some(payer) => if Pallet::<T>::sync_deposit(&payer, &key, len).is_err() {
+				log::warn!(target: "runtime::samaritan", "owner can't pay the deposit of an earlier entry, left free");
+			},
+			None => log::warn!(target: "runtime::samaritan", "earlier entry has no owner to pay its deposit, left free"),
+		}
+	}
+
+	/// the next entry after the cursor in the maps that hold deposits since v6
+	fn next_entry<T: Config>(cursor: &Cursor<T>) -> Option<Option<Entry<T>>> {
+		let from = |prefix: [u8; 32]| cursor.last.as_ref().map(|key| key.to_vec()).unwrap_or_else(|| prefix.to_vec());
+
+		match cursor.map {
+			0 => Some(
+				SamaritanRegistry::<T>::iter_from(from(SamaritanRegistry::<T>::final_prefix()))
+					.next()
+					.map(|(who, sam)| (SamaritanRegistry::<T>::hashed_key_for(&who), Some(who), sam.encoded_size()))
+			),
+			1 => Some(
+				DocMetaRegistry::<T>::iter_from(from(DocMetaRegistry::<T>::final_prefix()))
+					.next()
+					.map(|(did, docs)| (DocMetaRegistry::<T>::hashed_key_for(&did), DidOwners::<T>::get(&did), docs.encoded_size()))
+			),
+			2 => Some(
+				ProfileRegistry::<T>::iter_from(from(ProfileRegistry::<T>::final_prefix()))
+					.next()
+					.map(|(did, profile)| {
+						(ProfileRegistry::<T>::hashed_key_for(&did), DidOwners::<T>::get(&did), profile.encoded_size())
+					})
+			),
+			// the quorum owner pays for the quorum and for interning its members
+			3 => Some(
+				Quorums::<T>::iter_from(from(Quorums::<T>::final_prefix()))
+					.next()
+					.map(|(index, quorum)| {
+						let payer = IndexedDids::<T>::get(index).and_then(|did| DidOwners::<T>::get(&did));
+						(Quorums::<T>::hashed_key_for(index), payer, quorum.encoded_size())
+					})
+			),
+			4 => Some(
+				DidIndices::<T>::iter_from(from(DidIndices::<T>::final_prefix()))
+					.next()
+					.map(|(did, index)| {
+						let len = (&did, index).encoded_size().saturating_mul(2);
+						(DidIndices::<T>::hashed_key_for(&did), DidOwners::<T>::get(&did), len)
+					})
+			),
+			_ => None,
+		}
+	}
+
+	/// charge deposits until `limit` or `MAX_CHARGES_PER_BLOCK` is reached, returning the weight used
+	pub fn step<T: Config>(limit: Weight) -> Weight {
+		let db = T::DbWeight::get();
+
+		let mut cursor = match DepositBackfillProgress::<T>::get() {
+			Some(cursor) => cursor,
+			None => return db.reads(1),
+		};
+
+		// cursor read and write, then per entry the entry, its owner, its deposit and the owner's
+		// account, writing the deposit and the account
+		let mut used = db.reads_writes(1, 1);
+		let per_entry = db.reads_writes(5, 2);
+		let mut charged = 0u32;
+
+		while charged < MAX_CHARGES_PER_BLOCK && used.saturating_add(per_entry) <= limit {
+			used = used.saturating_add(per_entry);
+
+			let entry = match next_entry::<T>(&cursor) {
+				Some(Some(entry)) => entry,
+				Some(None) => {
+					cursor = Cursor { map: cursor.map + 1, last: None };
+					continue;
+				},
+				None => {
+					DepositBackfillProgress::<T>::kill();
+					log::info!(target: "runtime::samaritan", "deposits of earlier entries held");
+					return used;
+				},
+			};
+
+			let key = entry.0.clone();
+			charge::<T>(entry);
+			charged += 1;
+
+			cursor.last = match key.try_into() {
+				Ok(last) => Some(last),
+				// no map key is this long, leave the rest of the map alone
+				Err(_) => {
+					log::error!(target: "runtime::samaritan", "storage key over the cursor bound in map {}", cursor.map);
+					cursor = Cursor { map: cursor.map + 1, last: None };
+					continue;
+				},
+			};
+		}
+
+		DepositBackfillProgress::<T>::put(cursor);
+		used
+	}
+
+	/// hold deposits for samaritans, DID documents, profiles, trust quorums and interned DIDs
+	/// stored before deposits were introduced, so they pay for their state like newer ones.
+	/// Like v5 this only starts the migration, `on_initialize` charges the entries once the
+	/// earlier ones are done.
+	pub struct MigrateToV6<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV6<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 5 {
+				log::info!(target: "runtime::samaritan", "skipping v6 migration, storage is not at v5");
+				return T::DbWeight::get().reads(1);
+			}
+
+			DepositBackfillProgress::<T>::put(Cursor { map: 0, last: None });
+			StorageVersion::new(6).put::<Pallet<T>>();
+			log::info!(target: "runtime::samaritan", "started holding deposits for earlier entries");
+
+			T::DbWeight::get().reads_writes(1, 2)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), &'static str> {
+			ensure!(DepositBackfillProgress::<T>::exists(), "deposit backfill did not start");
+			ensure!(Pallet::<T>::on_chain_storage_version() == 6, "storage version not bumped to v6");
+
+			Ok(())
+		}
+	}
+}
@@ -1,78 +1,189 @@
 use crate as pallet_samaritan;
-use frame_support::traits::{ConstU16, ConstU64};
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64, ConstU8, UnixTime},
+	weights::Weight,
+	PalletId,
+};
 use frame_system as system;
+use frame_system::EnsureRoot;
 use sp_core::H256;
+use sp_keystore::{testing::KeyStore, KeystoreExt};
 use sp_runtime::{
 	testing::Header,
 	traits::{BlakeTwo256, IdentityLookup},
+	AccountId32, MultiSignature, MultiSigner,
 };
+use std::sync::Arc;
 
-use pallet_timestamp;
-use frame_support::parameter_types;
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
 
+pub type AccountId = AccountId32;
+pub type Balance = u64;
+
+pub const ALICE: AccountId = AccountId32::new([1u8; 32]);
+pub const BOB: AccountId = AccountId32::new([2u8; 32]);
+pub const CHARLIE: AccountId = AccountId32::new([3u8; 32]);
+pub const DAVE: AccountId = AccountId32::new([4u8; 32]);
+pub const EVE: AccountId = AccountId32::new([5u8; 32]);
+
+/// balance every test account starts with
+pub const ENDOWMENT: Balance = 1_000_000;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		TransactionPayment: pallet_transaction_payment::{Pallet, Storage, Event<T>},
+		Samaritan: pallet_samaritan::{Pallet, Call, Storage, Config<T>, Event<T>, Origin<T>},
+	}
+);
+
 impl system::Config for Test {
 	type BaseCallFilter = frame_support::traits::Everything;
-	type BlockWeights = ();
+	type BlockWeights = BlockWeights;
 	type BlockLength = ();
 	type DbWeight = ();
-	type Origin = Origin;
-	type Call = Call;
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
 	type Index = u64;
 	type BlockNumber = u64;
 	type Hash = H256;
 	type Hashing = BlakeTwo256;
-	type AccountId = u64;
+	type AccountId = AccountId;
 	type Lookup = IdentityLookup<Self::AccountId>;
 	type Header = Header;
-	type Event = Event;
+	type RuntimeEvent = RuntimeEvent;
 	type BlockHashCount = ConstU64<250>;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<Balance>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
 	type SS58Prefix = ConstU16<42>;
 	type OnSetCode = ();
-	type MaxConsumers = frame_support::traits::ConstU32<16>;
+	type MaxConsumers = ConstU32<16>;
 }
 
-impl pallet_timestamp::Config for Test {
-	/// A timestamp: milliseconds since the unix epoch.
-	type Moment = u64;
-	type OnTimestampSet = ();
-	type MinimumPeriod = ConstU64<{ 6000 / 2 }>;
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = Balance;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
 	type WeightInfo = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+}
+
+impl pallet_transaction_payment::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, ()>;
+	type WeightToFee = frame_support::weights::IdentityFee<Balance>;
+	type LengthToFee = frame_support::weights::IdentityFee<Balance>;
+	type FeeMultiplierUpdate = ();
+	type OperationalFeeMultiplier = ConstU8<5>;
+}
+
+parameter_types! {
+	// no base extrinsic weight, so fees stay in the range of the test balances
+	pub BlockWeights: frame_system::limits::BlockWeights =
+		frame_system::limits::BlockWeights::simple_max(Weight::from_ref_time(1024));
+	/// seconds since the unix epoch, as reported by `MockTime`
+	pub static Now: u64 = 1_000;
+	pub const SamaritanPalletId: PalletId = PalletId(*b"sam/spon");
+	pub const MaxFreeCallWeight: Weight = Weight::from_ref_time(16);
+}
+
+/// clock the tests can move with `Now::set`
+pub struct MockTime;
+impl UnixTime for MockTime {
+	fn now() -> core::time::Duration {
+		core::time::Duration::from_secs(Now::get())
+	}
 }
 
 /// The Samaritan pallet manages samaritan DIDs and
 /// provides core functionality for the SamOS.
 impl pallet_samaritan::Config for Test {
-	type Event = Event;
-	type TimeProvider = Timestamp;
-	type MaxDIDLength = ConstU32<128>;
-	type MaxNameLength = ConstU32<128>;
-	type MaxCIDLength = ConstU2<128>;
-	type MaxCacheLength = ConstU32<128>;
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type TimeProvider = MockTime;
+	type VerifierOrigin = EnsureRoot<AccountId>;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	type WeightInfo = ();
+	type Currency = Balances;
+	type DepositBase = ConstU64<10>;
+	type DepositPerByte = ConstU64<1>;
+	type MaxDIDLength = ConstU32<64>;
+	type MaxNameLength = ConstU32<32>;
+	type MaxCacheLength = ConstU32<4>;
 	type MaxQuorumMembersCount = ConstU32<3>;
+	type DefaultQuorumCap = ConstU32<3>;
+	type MaxHashLength = ConstU32<128>;
+	type MaxCallHashLength = ConstU32<64>;
+	type MaxUriLength = ConstU32<64>;
+	type MaxRedirectUris = ConstU32<2>;
+	type MaxScopeLength = ConstU32<16>;
+	type MaxScopes = ConstU32<4>;
+	type MaxVersionLength = ConstU32<16>;
+	type MaxReleases = ConstU32<3>;
+	type MaxAccessRecords = ConstU32<3>;
+	type MaxCallNameLength = ConstU32<32>;
+	type MaxSessionCalls = ConstU32<4>;
+	type MaxChargesPerBlock = ConstU32<2>;
+	type PalletId = SamaritanPalletId;
+	type MaxSponsoredCalls = ConstU32<4>;
+	type FreeCallsPerPeriod = ConstU32<2>;
+	type FreeCallPeriod = ConstU64<10>;
+	type MaxFreeCallWeight = MaxFreeCallWeight;
+	type MaxFreeCallLength = ConstU32<64>;
+	type DidSignature = MultiSignature;
+	type DidSigner = MultiSigner;
+	type MaxDidKeys = ConstU32<2>;
 }
 
-// Configure a mock runtime to test the pallet.
-frame_support::construct_runtime!(
-	pub enum Test where
-		Block = Block,
-		NodeBlock = Block,
-		UncheckedExtrinsic = UncheckedExtrinsic,
-	{
-		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
-		Samaritan: pallet_samaritan::{Pallet, Call, Storage, Event<T>},
-	}
-);
-
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	new_test_ext_with(Default::default())
+}
+
+/// test externalities seeded with samaritan genesis state
+pub fn new_test_ext_with(samaritan: pallet_samaritan::GenesisConfig<Test>) -> sp_io::TestExternalities {
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+
+	pallet_balances::GenesisConfig::<Test> {
+		balances: [ALICE, BOB, CHARLIE, DAVE, EVE].into_iter().map(|who| (who, ENDOWMENT)).collect(),
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	samaritan.assimilate_storage(&mut storage).unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	// DID keys are generated and used for signing in the benchmarks
+	ext.register_extension(KeystoreExt(Arc::new(KeyStore::new())));
+	// events are not recorded in the genesis block
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// move to block `n`, running the samaritan hooks of every block on the way
+pub fn run_to_block(n: u64) {
+	use frame_support::traits::Hooks;
+
+	while System::block_number() < n {
+		let next = System::block_number() + 1;
+		System::set_block_number(next);
+		Samaritan::on_initialize(next);
+	}
 }
@@ -0,0 +1,232 @@
+//! Transaction fee payment that lets an app sponsor the calls of its users.
+//!
+//! Works like `pallet_transaction_payment::ChargeTransactionPayment`, except that the signer may
+//! name an app DID. When that app sponsors the call being made and the signer has not used up
+//! their share, the fee is taken from the app's sponsorship account instead of the signer.
+//!
+//! Registered samaritans also get a quota of fee-less samaritan calls that refills every
+//! `FreeCallPeriod` blocks. The quota is checked when the pool validates a transaction and
+//! used up when it is dispatched, after which the signer pays as usual. Calls heavier than
+//! `MaxFreeCallWeight` or longer than `MaxFreeCallLength` are always paid for, and the pool holds
+//! one free transaction per account at a time.
+
+use crate::{Config, Event, Pallet, Sponsorships, SponsorshipUsage};
+use codec::{Decode, Encode};
+use frame_support::{
+	dispatch::{DispatchInfo, PostDispatchInfo},
+	traits::{Get, GetCallName, IsSubType, IsType},
+	BoundedVec,
+};
+use pallet_transaction_payment::{ChargeTransactionPayment, OnChargeTransaction};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SaturatedConversion, Saturating, SignedExtension},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	DispatchResult,
+};
+use sp_std::vec;
+
+pub type FeeBalanceOf<T> = <<T as pallet_transaction_payment::Config>::OnChargeTransaction as OnChargeTransaction<T>>::Balance;
+type LiquidityInfoOf<T> = <<T as pallet_transaction_payment::Config>::OnChargeTransaction as OnChargeTransaction<T>>::LiquidityInfo;
+type DidOf<T> = BoundedVec<u8, <T as Config>::MaxDIDLength>;
+
+/// prefix of the transaction tag a free call provides
+const FREE_CALL_TAG: &[u8] = b"samaritan/free";
+
+/// charge the transaction fee to the signer, or to the app sponsoring the call,
+/// unless the call fits in the signer's free quota
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct ChargeSponsoredTransactionPayment<T: Config + pallet_transaction_payment::Config> {
+	#[codec(compact)]
+	tip: FeeBalanceOf<T>,
+	/// app DID asked to pay the fee
+	sponsor: Option<DidOf<T>>,
+}
+
+impl<T: Config + pallet_transaction_payment::Config> ChargeSponsoredTransactionPayment<T>
+where
+	<T as frame_system::Config>::RuntimeCall: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+	FeeBalanceOf<T>: Send + Sync,
+{
+	pub fn new(tip: FeeBalanceOf<T>, sponsor: Option<DidOf<T>>) -> Self {
+		Self { tip, sponsor }
+	}
+
+	/// the app sponsoring this call for the signer, if any
+	fn sponsorship(
+		&self,
+		who: &T::AccountId,
+		call: &<T as frame_system::Config>::RuntimeCall,
+	) -> Result<Option<DidOf<T>>, TransactionValidityError> {
+		let app_did = match &self.sponsor {
+			Some(app_did) => app_did,
+			None => return Ok(None),
+		};
+
+		let sponsorship = Sponsorships::<T>::get(app_did).ok_or(InvalidTransaction::Payment)?;
+
+		let sponsored = match <T as Config>::RuntimeCall::from_ref(call).is_sub_type() {
+			Some(inner) => {
+				let name = inner.get_call_name().as_bytes();
+				sponsorship.calls.iter().any(|c| c[..] == name[..])
+			},
+			None => false,
+		};
+		if !sponsored {
+			return Err(InvalidTransaction::Call.into())
+		}
+
+		if SponsorshipUsage::<T>::get(app_did, who) >= sponsorship.max_per_account {
+			return Err(InvalidTransaction::ExhaustsResources.into())
+		}
+
+		Ok(Some(app_did.clone()))
+	}
+
+	/// whether the call can be made from the signer's free quota
+	fn is_free(
+		&self,
+		who: &T::AccountId,
+		call: &<T as frame_system::Config>::RuntimeCall,
+		info: &DispatchInfoOf<<T as frame_system::Config>::RuntimeCall>,
+		len: usize,
+	) -> bool {
+		self.sponsor.is_none() &&
+			info.weight <= T::MaxFreeCallWeight::get() &&
+			len <= T::MaxFreeCallLength::get() as usize &&
+			<T as Config>::RuntimeCall::from_ref(call).is_sub_type().is_some() &&
+			Pallet::<T>::free_calls_left(who) > 0
+	}
+
+	fn withdraw_fee(
+		&self,
+		who: &T::AccountId,
+		call: &<T as frame_system::Config>::RuntimeCall,
+		info: &DispatchInfoOf<<T as frame_system::Config>::RuntimeCall>,
+		len: usize,
+	) -> Result<(FeeBalanceOf<T>, T::AccountId, LiquidityInfoOf<T>, Option<DidOf<T>>), TransactionValidityError> {
+		let fee = pallet_transaction_payment::Pallet::<T>::compute_fee(len as u32, info, self.tip);
+
+		let sponsor = self.sponsorship(who, call)?;
+		let payer = match &sponsor {
+			Some(app_did) => Pallet::<T>::sponsor_account(app_did),
+			None => who.clone(),
+		};
+
+		<<T as pallet_transaction_payment::Config>::OnChargeTransaction as OnChargeTransaction<T>>::withdraw_fee(
+			&payer, call, info, fee, self.tip,
+		)
+		.map(|imbalance| (fee, payer, imbalance, sponsor))
+	}
+}
+
+impl<T: Config + pallet_transaction_payment::Config> sp_std::fmt::Debug for ChargeSponsoredTransactionPayment<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "ChargeSponsoredTransactionPayment<{:?}, {:?}>", self.tip, self.sponsor)
+	}
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Config + pallet_transaction_payment::Config> SignedExtension for ChargeSponsoredTransactionPayment<T>
+where
+	<T as frame_system::Config>::RuntimeCall: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+	FeeBalanceOf<T>: Send + Sync,
+{
+	const IDENTIFIER: &'static str = "ChargeSponsoredTransactionPayment";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::RuntimeCall;
+	type AdditionalSigned = ();
+	type Pre = (
+		// tip
+		FeeBalanceOf<T>,
+		// signer
+		Self::AccountId,
+		// account the fee was taken from, none for free calls
+		Option<Self::AccountId>,
+		LiquidityInfoOf<T>,
+		// sponsoring app
+		Option<DidOf<T>>,
+	);
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		if self.is_free(who, call, info, len) {
+			// free transactions are dropped from the pool once the quota period is over
+			let period_start = Pallet::<T>::quota_period_start();
+			let period_end = period_start.saturating_add(T::FreeCallPeriod::get());
+			let now = frame_system::Pallet::<T>::block_number();
+
+			return Ok(ValidTransaction {
+				// one free transaction per account waits in the pool, so a quota can't be spent many times over
+				provides: vec![(FREE_CALL_TAG, who, period_start).encode()],
+				longevity: period_end.saturating_sub(now).saturated_into::<u64>().max(1),
+				..Default::default()
+			});
+		}
+
+		let (final_fee, _, _, _) = self.withdraw_fee(who, call, info, len)?;
+		Ok(ValidTransaction {
+			priority: ChargeTransactionPayment::<T>::get_priority(info, len, self.tip, final_fee),
+			..Default::default()
+		})
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		if self.is_free(who, call, info, len) {
+			Pallet::<T>::use_free_call(who);
+			return Ok((self.tip, who.clone(), None, Default::default(), None));
+		}
+
+		let (_, payer, imbalance, sponsor) = self.withdraw_fee(who, call, info, len)?;
+
+		if let Some(app_did) = &sponsor {
+			SponsorshipUsage::<T>::mutate(app_did, who, |used| *used = used.saturating_add(1));
+		}
+
+		Ok((self.tip, who.clone(), Some(payer), imbalance, sponsor))
+	}
+
+	fn post_dispatch(
+		maybe_pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		_result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		if let Some((tip, who, Some(payer), imbalance, sponsor)) = maybe_pre {
+			let actual_fee =
+				pallet_transaction_payment::Pallet::<T>::compute_actual_fee(len as u32, info, post_info, tip);
+
+			<<T as pallet_transaction_payment::Config>::OnChargeTransaction as OnChargeTransaction<T>>::correct_and_deposit_fee(
+				&payer, info, post_info, actual_fee, tip, imbalance,
+			)?;
+
+			if let Some(app_did) = sponsor {
+				Pallet::<T>::deposit_event(Event::TransactionSponsored { app_did: app_did.to_vec(), who });
+			}
+		}
+		Ok(())
+	}
+}
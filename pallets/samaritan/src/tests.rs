@@ -1,47 +1,2784 @@
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+	migrations::{
+		v1::{self, MigrateToV1, OldDocMetadata},
+		v2::{self, MigrateToV2},
+		v3::MigrateToV3,
+		v4::MigrateToV4,
+		v5::MigrateToV5,
+		v6::MigrateToV6,
+		PauseWhileMigrating,
+	},
+	mock::*, AppRegistry, AppStatus, Call as SamaritanCall, ChargeAgenda, ChargeSponsoredTransactionPayment, Did, DidOwners, DidRef, DocMetaRegistry,
+	Error, Event, MAX_CHARGE_DEFERRALS, PairwiseRecoveries, ProfileRegistry, Quorums, RawOrigin, ReleaseChannel, SamaritanRegistry, SignUpDataRegistry,
+	StorageDeposits, Verification,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	assert_noop, assert_ok, bounded_vec,
+	dispatch::{DispatchInfo, PostDispatchInfo},
+	storage::{unhashed, StoragePrefixedMap},
+	traits::{Contains, Currency, Get, GetStorageVersion, OnRuntimeUpgrade, ReservableCurrency, StorageVersion},
+	weights::Weight,
+	Blake2_128Concat, BoundedVec, StorageHasher, Twox64Concat,
+};
+use proptest::{collection::vec, prelude::*};
+use sp_core::{hashing::blake2_256, sr25519, Pair, H256};
+use sp_runtime::{
+	traits::{BadOrigin, Dispatchable, SignedExtension},
+	transaction_validity::{InvalidTransaction, TransactionValidityError},
+	MultiSignature,
+};
+
+const ALICE_DID: &[u8] = b"did:sam:alice";
+const DAVE_DID: &[u8] = b"did:sam:dave";
+const APP_DID: &[u8] = b"did:sam:app";
+const OTHER_APP_DID: &[u8] = b"did:sam:other-app";
+const PAIRWISE_DID: &[u8] = b"did:sam:pairwise";
+
+fn did(did_str: &[u8]) -> Did<Test> {
+	did_str.to_vec().try_into().unwrap()
+}
+
+fn bounded<S: Get<u32>>(bytes: &[u8]) -> BoundedVec<u8, S> {
+	bytes.to_vec().try_into().unwrap()
+}
+
+/// a samaritan call encoded with unbounded arguments, as any client could send it
+fn raw_call(call: SamaritanCall<Test>, args: impl Encode) -> Vec<u8> {
+	let mut raw = RuntimeCall::Samaritan(call).encode()[..2].to_vec();
+	args.encode_to(&mut raw);
+	raw
+}
+
+fn new_samaritan(who: AccountId, did_str: &[u8]) {
+	assert_ok!(Samaritan::create_samaritan(
+		RuntimeOrigin::signed(who),
+		bounded(b"samaritan"),
+		bounded(did_str),
+		bounded(b"QmDoc0")
+	));
+}
+
+/// an app asking for the `profile` and `email` scopes
+fn new_app(owner: AccountId, did_str: &[u8]) {
+	assert_ok!(Samaritan::create_app(
+		RuntimeOrigin::signed(owner),
+		bounded(did_str),
+		bounded(b"QmMeta"),
+		bounded(b"app"),
+		bounded(b"QmLogo"),
+		bounded_vec![bounded(b"https://app.example/cb")],
+		bounded_vec![bounded(b"profile"), bounded(b"email")]
+	));
+}
+
+fn grant_all(who: AccountId, did_str: &[u8], app_did_str: &[u8]) {
+	assert_ok!(Samaritan::grant_consent(
+		RuntimeOrigin::signed(who),
+		bounded(did_str),
+		bounded(app_did_str),
+		bounded_vec![bounded(b"profile"), bounded(b"email")],
+		None
+	));
+}
+
+/// Alice subscribed to a plan of Bob's app costing 100 every 10 blocks, retried after 5
+fn new_subscription(cap: Balance) {
+	new_samaritan(ALICE, ALICE_DID);
+	new_app(BOB, APP_DID);
+	assert_ok!(Samaritan::create_plan(RuntimeOrigin::signed(BOB), bounded(APP_DID), 100, 10, 5));
+	assert_ok!(Samaritan::subscribe(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(APP_DID), 0, cap));
+}
+
+/// signup token the tests generate off-chain
+const TOKEN: H256 = H256::repeat_byte(7);
+
+/// salted commitment to `TOKEN` for an app and user
+fn commitment(app_did_str: &[u8], did_str: &[u8]) -> H256 {
+	Samaritan::token_commitment(&TOKEN, app_did_str, did_str)
+}
+
+fn profile_call(did_str: &[u8], profile_hash: &[u8]) -> RuntimeCall {
+	RuntimeCall::Samaritan(SamaritanCall::update_profile { did: did(did_str), profile_hash: bounded(profile_hash) })
+}
+
+fn did_origin(did_str: &[u8]) -> RuntimeOrigin {
+	RawOrigin::Did(did(did_str)).into()
+}
+
+/// deposit held for interning a DID, covering the entries in both directions
+fn intern_deposit(did_str: &[u8]) -> Balance {
+	Samaritan::deposit_for((did(did_str), 0u64).encoded_size() * 2)
+}
+
+#[test]
+fn create_samaritan_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Samaritan::create_samaritan(
+			RuntimeOrigin::signed(ALICE),
+			bounded(b"alice"),
+			bounded(ALICE_DID),
+			bounded(b"QmDoc0")
+		));
+
+		let sam = Samaritan::sam_reg(&ALICE).unwrap();
+		assert_eq!(sam.did, did(ALICE_DID));
+		assert_eq!(sam.name.to_vec(), b"alice".to_vec());
+		assert_eq!(Samaritan::did_owner(did(ALICE_DID)), Some(ALICE));
+
+		let docs = Samaritan::doc_metareg(did(ALICE_DID)).unwrap();
+		assert_eq!(docs.len(), 1);
+		assert_eq!(docs[0].version, 0);
+		assert_eq!(docs[0].hl.to_vec(), b"QmDoc0".to_vec());
+		assert_eq!(docs[0].created, 1_000);
+		assert!(docs[0].active);
+
+		System::assert_last_event(
+			Event::SamaritanCreated { name: b"alice".to_vec(), did: ALICE_DID.to_vec() }.into(),
+		);
+	});
+}
+
+#[test]
+fn create_samaritan_rejects_oversized_input() {
+	let call = SamaritanCall::create_samaritan { name: bounded(b"alice"), did: did(ALICE_DID), meta_hash: bounded(b"QmDoc0") };
+
+	// input within the bounds decodes into the same call
+	let raw = raw_call(call.clone(), (b"alice".to_vec(), ALICE_DID.to_vec(), b"QmDoc0".to_vec()));
+	assert_eq!(RuntimeCall::decode(&mut &raw[..]).ok(), Some(RuntimeCall::Samaritan(call.clone())));
+
+	// MaxNameLength is 32, MaxDIDLength and MaxCallHashLength are 64
+	for args in [
+		(vec![b'a'; 33], ALICE_DID.to_vec(), b"QmDoc0".to_vec()),
+		(b"alice".to_vec(), vec![b'd'; 65], b"QmDoc0".to_vec()),
+		(b"alice".to_vec(), ALICE_DID.to_vec(), vec![b'h'; 65]),
+	] {
+		assert!(RuntimeCall::decode(&mut &raw_call(call.clone(), args)[..]).is_err());
+	}
+}
+
+fn seed() -> crate::GenesisConfig<Test> {
+	crate::GenesisConfig {
+		samaritans: vec![
+			(ALICE, b"alice".to_vec(), ALICE_DID.to_vec(), b"QmDoc0".to_vec()),
+			(DAVE, b"dave".to_vec(), DAVE_DID.to_vec(), b"QmDoc0".to_vec()),
+		],
+		apps: vec![(
+			BOB,
+			APP_DID.to_vec(),
+			b"QmMeta".to_vec(),
+			b"app".to_vec(),
+			b"QmLogo".to_vec(),
+			vec![b"https://app.example/cb".to_vec()],
+			vec![b"profile".to_vec()],
+		)],
+		documents: vec![(ALICE_DID.to_vec(), b"QmDoc1".to_vec())],
+		profiles: vec![(ALICE_DID.to_vec(), b"QmProfile".to_vec())],
+		quorums: vec![(ALICE_DID.to_vec(), vec![DAVE_DID.to_vec(), b"did:sam:m2".to_vec()])],
+	}
+}
+
+#[test]
+fn genesis_seeds_samaritans_and_apps() {
+	new_test_ext_with(seed()).execute_with(|| {
+		assert_eq!(Samaritan::sam_reg(&ALICE).unwrap().did, did(ALICE_DID));
+		assert_eq!(Samaritan::did_owner(did(DAVE_DID)), Some(DAVE));
+		assert_eq!(Samaritan::app_reg(did(APP_DID)).unwrap().owner, BOB);
+
+		let docs = Samaritan::doc_metareg(did(ALICE_DID)).unwrap();
+		assert_eq!(docs.iter().map(|d| (d.hl.to_vec(), d.active)).collect::<Vec<_>>(), vec![
+			(b"QmDoc0".to_vec(), false),
+			(b"QmDoc1".to_vec(), true),
+		]);
+		assert_eq!(ProfileRegistry::<Test>::get(did(ALICE_DID)).unwrap().to_vec(), b"QmProfile".to_vec());
+		assert_eq!(Samaritan::trust_quorum(ALICE_DID.to_vec()), vec![DAVE_DID.to_vec(), b"did:sam:m2".to_vec()]);
+
+		// seeded entries hold deposits like the calls that made them
+		let held = StorageDeposits::<Test>::iter_values().filter(|(who, _)| *who == ALICE).map(|(_, d)| d).sum::<Balance>();
+		assert_eq!(Balances::reserved_balance(&ALICE), held);
+		assert_ok!(Samaritan::do_try_state());
+	});
+}
+
+#[test]
+#[should_panic(expected = "genesis input fits its bound")]
+fn genesis_rejects_oversized_input() {
+	let mut seed = seed();
+	seed.samaritans[0].2 = vec![b'd'; 65];
+	new_test_ext_with(seed);
+}
+
+#[test]
+fn create_samaritan_rejects_taken_did() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+
+		assert_noop!(
+			Samaritan::create_samaritan(RuntimeOrigin::signed(BOB), bounded(b"bob"), bounded(ALICE_DID), bounded(b"QmDoc0")),
+			Error::<Test>::DIDAlreadyRegistered
+		);
+	});
+}
+
+#[test]
+fn create_samaritan_keeps_previous_did() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		grant_all(ALICE, ALICE_DID, APP_DID);
+		new_samaritan(ALICE, DAVE_DID);
+
+		assert_eq!(Samaritan::did_owner(did(DAVE_DID)), Some(ALICE));
+		assert_eq!(Samaritan::sam_reg(&ALICE).unwrap().did, did(DAVE_DID));
+
+		// the previous DID and what it granted can't be taken over
+		assert_eq!(Samaritan::did_owner(did(ALICE_DID)), Some(ALICE));
+		assert_noop!(
+			Samaritan::create_samaritan(RuntimeOrigin::signed(BOB), bounded(b"bob"), bounded(ALICE_DID), bounded(b"QmDoc0")),
+			Error::<Test>::DIDAlreadyRegistered
+		);
+		assert_ok!(Samaritan::revoke_consent(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(APP_DID)));
+
+		// an app under the previous DID stays with its owner too
+		new_app(ALICE, ALICE_DID);
+		new_samaritan(ALICE, b"did:sam:third");
+		assert_eq!(Samaritan::did_owner(did(ALICE_DID)), Some(ALICE));
+		assert_ok!(Samaritan::do_try_state());
+	});
+}
+
+#[test]
+fn rename_samaritan_works() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+
+		assert_ok!(Samaritan::rename_samaritan(RuntimeOrigin::signed(ALICE), bounded(b"ally")));
+		assert_eq!(Samaritan::sam_reg(&ALICE).unwrap().name.to_vec(), b"ally".to_vec());
+		System::assert_last_event(Event::SamaritanNameChanged { name: b"ally".to_vec() }.into());
+
+		assert_noop!(
+			Samaritan::rename_samaritan(RuntimeOrigin::signed(BOB), bounded(b"bob")),
+			Error::<Test>::SamaritanNotFound
+		);
+	});
+}
+
+#[test]
+fn alter_state_toggles_latest_document() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		assert_ok!(Samaritan::update_document(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(b"QmDoc1")));
+
+		assert_ok!(Samaritan::alter_state(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), false));
+		let docs = Samaritan::doc_metareg(did(ALICE_DID)).unwrap();
+		assert!(!docs[0].active);
+		assert!(!docs[1].active);
+		System::assert_last_event(Event::SamaritanScopeChanged { did: ALICE_DID.to_vec(), state: false }.into());
+
+		assert_ok!(Samaritan::alter_state(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), true));
+		let docs = Samaritan::doc_metareg(did(ALICE_DID)).unwrap();
+		assert!(!docs[0].active);
+		assert!(docs[1].active);
+	});
+}
+
+#[test]
+fn alter_state_requires_controller_and_document() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+
+		assert_noop!(
+			Samaritan::alter_state(RuntimeOrigin::signed(BOB), bounded(ALICE_DID), false),
+			Error::<Test>::NotDIDOwner
+		);
+
+		// pairwise DIDs have an owner but no document
+		assert_ok!(Samaritan::register_pairwise_did(
+			RuntimeOrigin::signed(CHARLIE),
+			bounded(PAIRWISE_DID),
+			bounded(APP_DID),
+			H256::zero(),
+			bounded(b"QmEscrow")
+		));
+		assert_noop!(
+			Samaritan::alter_state(RuntimeOrigin::signed(CHARLIE), bounded(PAIRWISE_DID), false),
+			Error::<Test>::DIDMetaNotFound
+		);
+	});
+}
+
+#[test]
+fn update_document_keeps_a_single_active_document() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+
+		Now::set(2_000);
+		assert_ok!(Samaritan::update_document(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(b"QmDoc1")));
+		System::assert_last_event(Event::DIDDocumentUpdated { did: ALICE_DID.to_vec() }.into());
+
+		Now::set(3_000);
+		assert_ok!(Samaritan::update_document(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(b"QmDoc2")));
+
+		let docs = Samaritan::doc_metareg(did(ALICE_DID)).unwrap();
+		assert_eq!(docs.iter().map(|d| d.version).collect::<Vec<_>>(), vec![0, 1, 2]);
+		assert_eq!(docs.iter().map(|d| d.created).collect::<Vec<_>>(), vec![1_000, 2_000, 3_000]);
+		assert_eq!(docs.iter().map(|d| d.active).collect::<Vec<_>>(), vec![false, false, true]);
+		// superseded documents record when they were disabled
+		assert_eq!(docs.iter().map(|d| d.updated).collect::<Vec<_>>(), vec![2_000, 3_000, 3_000]);
+		assert_eq!(docs[2].hl.to_vec(), b"QmDoc2".to_vec());
+	});
+}
+
+#[test]
+fn update_document_reactivates_after_disable() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		assert_ok!(Samaritan::alter_state(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), false));
+
+		// a new document is always the active one
+		assert_ok!(Samaritan::update_document(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(b"QmDoc1")));
+		let docs = Samaritan::doc_metareg(did(ALICE_DID)).unwrap();
+		assert_eq!(docs.iter().filter(|d| d.active).count(), 1);
+		assert!(docs[1].active);
+	});
+}
+
+#[test]
+fn update_document_rolls_the_cache() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+
+		// MaxCacheLength is 4, the first document came with the samaritan
+		for n in 1..=5u8 {
+			assert_ok!(Samaritan::update_document(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(&[b'0' + n])));
+		}
+
+		// the oldest versions are dropped, the numbering carries on
+		let docs = Samaritan::doc_metareg(did(ALICE_DID)).unwrap();
+		assert_eq!(docs.iter().map(|d| d.version).collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+		assert_eq!(docs[3].hl.to_vec(), b"5".to_vec());
+		assert_eq!(docs.iter().filter(|d| d.active).count(), 1);
+	});
+}
+
+#[test]
+fn update_document_at_a_full_cache_keeps_its_size() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+
+		// MaxCacheLength is 4, the first document came with the samaritan. Same size documents
+		// keep the encoded history size the same
+		for _ in 0..3 {
+			assert_ok!(Samaritan::update_document(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(b"QmDoc0")));
+		}
+		let reserved = Balances::reserved_balance(&ALICE);
+
+		// instead of overflowing, the full history rolls over and holds no further deposit
+		assert_ok!(Samaritan::update_document(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(b"QmDoc0")));
+		assert_eq!(Samaritan::doc_metareg(did(ALICE_DID)).unwrap().len(), 4);
+		assert_eq!(Balances::reserved_balance(&ALICE), reserved);
+	});
+}
+
+#[test]
+fn update_document_checks_input() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+
+		assert_noop!(
+			Samaritan::update_document(RuntimeOrigin::signed(BOB), bounded(ALICE_DID), bounded(b"QmDoc1")),
+			Error::<Test>::NotDIDOwner
+		);
+	});
+}
+
+#[test]
+fn update_document_needs_a_document_history() {
+	new_test_ext().execute_with(|| {
+		// pairwise DIDs are owned but have no documents
+		pairwise_of_alice();
+		assert_noop!(
+			Samaritan::update_document(RuntimeOrigin::signed(CHARLIE), bounded(PAIRWISE_DID), bounded(b"QmDoc1")),
+			Error::<Test>::DIDNotFound
+		);
+	});
+}
+
+#[test]
+fn update_quorum_works() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+
+		assert_ok!(Samaritan::update_quorum(RuntimeOrigin::signed(ALICE), DidRef::Did(bounded(ALICE_DID)), DidRef::Did(bounded(DAVE_DID))));
+		assert_eq!(Samaritan::trust_quorum(ALICE_DID.to_vec()), vec![DAVE_DID.to_vec()]);
+		System::assert_last_event(
+			Event::TrustQuorumUpdated { did: ALICE_DID.to_vec(), trust_did: DAVE_DID.to_vec() }.into(),
+		);
+
+		assert_noop!(
+			Samaritan::update_quorum(RuntimeOrigin::signed(ALICE), DidRef::Did(bounded(ALICE_DID)), DidRef::Did(bounded(DAVE_DID))),
+			Error::<Test>::DuplicateQuorumMember
+		);
+		assert_noop!(
+			Samaritan::update_quorum(RuntimeOrigin::signed(BOB), DidRef::Did(bounded(ALICE_DID)), DidRef::Did(bounded(b"did:sam:bob"))),
+			Error::<Test>::NotDIDOwner
+		);
+	});
+}
+
+#[test]
+fn interning_holds_a_deposit_once() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+
+		// a new member is interned at the quorum owner's cost
+		let reserved = Balances::reserved_balance(&ALICE);
+		assert_ok!(Samaritan::update_quorum(RuntimeOrigin::signed(ALICE), DidRef::Did(bounded(ALICE_DID)), DidRef::Did(bounded(DAVE_DID))));
+		let quorum = Samaritan::quorum(Samaritan::did_index_of(ALICE_DID.to_vec()).unwrap()).unwrap();
+		assert_eq!(
+			Balances::reserved_balance(&ALICE),
+			reserved + intern_deposit(DAVE_DID) + Samaritan::deposit_for(quorum.encoded_size())
+		);
+
+		// known DIDs are not charged again
+		let reserved = Balances::reserved_balance(&DAVE);
+		new_samaritan(DAVE, DAVE_DID);
+		let sam = Samaritan::sam_reg(&DAVE).unwrap();
+		let docs = Samaritan::doc_metareg(did(DAVE_DID)).unwrap();
+		assert_eq!(
+			Balances::reserved_balance(&DAVE),
+			reserved + Samaritan::deposit_for(sam.encoded_size()) + Samaritan::deposit_for(docs.encoded_size())
+		);
+	});
+}
+
+#[test]
+fn quorum_calls_take_did_indices() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_samaritan(DAVE, DAVE_DID);
+		let alice = Samaritan::did_index_of(ALICE_DID.to_vec()).unwrap();
+		let dave = Samaritan::did_index_of(DAVE_DID.to_vec()).unwrap();
+
+		assert_ok!(Samaritan::update_quorum(RuntimeOrigin::signed(ALICE), DidRef::Index(alice), DidRef::Index(dave)));
+		assert_eq!(Samaritan::quorum(alice).unwrap().to_vec(), vec![dave]);
+		assert_noop!(
+			Samaritan::update_quorum(RuntimeOrigin::signed(ALICE), DidRef::Index(alice), DidRef::Index(99)),
+			Error::<Test>::UnknownDidIndex
+		);
+
+		// both forms name the same member
+		assert_ok!(Samaritan::filter_quorum(RuntimeOrigin::signed(ALICE), DidRef::Did(bounded(ALICE_DID)), DidRef::Index(dave)));
+		assert!(Samaritan::trust_quorum(ALICE_DID.to_vec()).is_empty());
+	});
+}
+
+#[test]
+fn update_quorum_overflows() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+
+		for member in [b"did:sam:m1", b"did:sam:m2", b"did:sam:m3"] {
+			assert_ok!(Samaritan::update_quorum(RuntimeOrigin::signed(ALICE), DidRef::Did(bounded(ALICE_DID)), DidRef::Did(bounded(member))));
+		}
+		assert_noop!(
+			Samaritan::update_quorum(RuntimeOrigin::signed(ALICE), DidRef::Did(bounded(ALICE_DID)), DidRef::Did(bounded(b"did:sam:m4"))),
+			Error::<Test>::QuorumOverflow
+		);
+		// a full quorum reports overflow before duplicates
+		assert_noop!(
+			Samaritan::update_quorum(RuntimeOrigin::signed(ALICE), DidRef::Did(bounded(ALICE_DID)), DidRef::Did(bounded(b"did:sam:m1"))),
+			Error::<Test>::QuorumOverflow
+		);
+	});
+}
+
+#[test]
+fn filter_quorum_works() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+
+		assert_noop!(
+			Samaritan::filter_quorum(RuntimeOrigin::signed(ALICE), DidRef::Did(bounded(ALICE_DID)), DidRef::Did(bounded(DAVE_DID))),
+			Error::<Test>::QuorumUninitialized
+		);
+
+		for member in [DAVE_DID, b"did:sam:m2"] {
+			assert_ok!(Samaritan::update_quorum(RuntimeOrigin::signed(ALICE), DidRef::Did(bounded(ALICE_DID)), DidRef::Did(bounded(member))));
+		}
+
+		assert_ok!(Samaritan::filter_quorum(RuntimeOrigin::signed(ALICE), DidRef::Did(bounded(ALICE_DID)), DidRef::Did(bounded(DAVE_DID))));
+		assert_eq!(Samaritan::trust_quorum(ALICE_DID.to_vec()), vec![b"did:sam:m2".to_vec()]);
+		System::assert_last_event(
+			Event::TrustQuorumUpdated { did: ALICE_DID.to_vec(), trust_did: DAVE_DID.to_vec() }.into(),
+		);
+
+		// removing a non-member leaves the quorum as it was
+		assert_ok!(Samaritan::filter_quorum(RuntimeOrigin::signed(ALICE), DidRef::Did(bounded(ALICE_DID)), DidRef::Did(bounded(DAVE_DID))));
+		assert_eq!(Samaritan::trust_quorum(ALICE_DID.to_vec()).len(), 1);
+
+		assert_noop!(
+			Samaritan::filter_quorum(RuntimeOrigin::signed(BOB), DidRef::Did(bounded(ALICE_DID)), DidRef::Did(bounded(b"did:sam:m2"))),
+			Error::<Test>::NotDIDOwner
+		);
+	});
+}
+
+#[test]
+fn update_profile_works() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+
+		assert_ok!(Samaritan::update_profile(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(b"QmProfile")));
+		assert_eq!(Samaritan::prof_reg(did(ALICE_DID)).unwrap().to_vec(), b"QmProfile".to_vec());
+		System::assert_last_event(Event::ProfileUpdated { did: ALICE_DID.to_vec() }.into());
+
+		assert_noop!(
+			Samaritan::update_profile(RuntimeOrigin::signed(BOB), bounded(ALICE_DID), bounded(b"QmProfile")),
+			Error::<Test>::NotDIDOwner
+		);
+	});
+}
+
+#[test]
+fn did_origin_controls_its_own_did() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_samaritan(DAVE, DAVE_DID);
+
+		assert_ok!(Samaritan::update_profile(did_origin(ALICE_DID), bounded(ALICE_DID), bounded(b"QmProfile")));
+		assert_noop!(
+			Samaritan::update_profile(did_origin(ALICE_DID), bounded(DAVE_DID), bounded(b"QmProfile")),
+			Error::<Test>::NotDIDOwner
+		);
+		assert_noop!(
+			Samaritan::update_profile(RuntimeOrigin::root(), bounded(ALICE_DID), bounded(b"QmProfile")),
+			BadOrigin
+		);
+	});
+}
+
+#[test]
+fn storage_deposits_follow_entry_size() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+
+		let sam = Samaritan::sam_reg(&ALICE).unwrap();
+		let docs = Samaritan::doc_metareg(did(ALICE_DID)).unwrap();
+		let held = Samaritan::deposit_for(sam.encoded_size()) +
+			Samaritan::deposit_for(docs.encoded_size()) +
+			intern_deposit(ALICE_DID);
+		assert_eq!(Balances::reserved_balance(&ALICE), held);
+
+		assert_ok!(Samaritan::update_profile(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(&[b'p'; 10])));
+		assert_eq!(Balances::reserved_balance(&ALICE), held + Samaritan::deposit_for(11));
+
+		// DepositPerByte is 1
+		assert_ok!(Samaritan::update_profile(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(&[b'p'; 40])));
+		assert_eq!(Balances::reserved_balance(&ALICE), held + Samaritan::deposit_for(11) + 30);
+
+		assert_ok!(Samaritan::update_profile(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(&[b'p'; 5])));
+		assert_eq!(Balances::reserved_balance(&ALICE), held + Samaritan::deposit_for(6));
+	});
+}
+
+#[test]
+fn storage_deposits_stay_with_the_previous_did() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		assert_ok!(Samaritan::update_profile(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(b"QmProfile")));
+		let registry = || Samaritan::deposit_for(Samaritan::sam_reg(&ALICE).unwrap().encoded_size());
+		let held = Balances::reserved_balance(&ALICE) - registry();
+
+		// moving to another DID adds its entries, the old ones stay with the account controlling them
+		new_samaritan(ALICE, DAVE_DID);
+		let docs = Samaritan::doc_metareg(did(DAVE_DID)).unwrap();
+		let held = held + registry() + Samaritan::deposit_for(docs.encoded_size()) + intern_deposit(DAVE_DID);
+		assert_eq!(Balances::reserved_balance(&ALICE), held);
+
+		// and follow their entries when those change, DepositPerByte is 1
+		assert_ok!(Samaritan::update_profile(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(b"Qm")));
+		assert_eq!(Balances::reserved_balance(&ALICE) + 7, held);
+	});
+}
+
+#[test]
+fn storage_deposit_needs_funds() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&ALICE, 5);
+
+		assert_noop!(
+			Samaritan::create_samaritan(RuntimeOrigin::signed(ALICE), bounded(b"alice"), bounded(ALICE_DID), bounded(b"QmDoc0")),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+	});
+}
+
+#[test]
+fn create_app_works() {
+	new_test_ext().execute_with(|| {
+		new_app(BOB, APP_DID);
+
+		let app = Samaritan::app_reg(did(APP_DID)).unwrap();
+		assert_eq!(app.owner, BOB);
+		assert_eq!(app.name.to_vec(), b"app".to_vec());
+		assert_eq!(app.logo.to_vec(), b"QmLogo".to_vec());
+		assert_eq!(app.scopes.len(), 2);
+		assert_eq!(app.status, AppStatus::Active);
+		assert_eq!(app.verification, Verification::Unverified);
+		assert_eq!(app.created, 1_000);
+
+		assert_eq!(Samaritan::did_owner(did(APP_DID)), Some(BOB));
+		assert_eq!(Samaritan::doc_metareg(did(APP_DID)).unwrap().len(), 1);
+		assert_eq!(Samaritan::app_info(APP_DID.to_vec()), Some(app));
+		System::assert_last_event(Event::AppCreated { did: APP_DID.to_vec() }.into());
+	});
+}
+
+#[test]
+fn create_app_rejects_taken_did() {
+	new_test_ext().execute_with(|| {
+		new_app(BOB, APP_DID);
+		new_samaritan(ALICE, ALICE_DID);
+
+		assert_noop!(
+			Samaritan::create_app(
+				RuntimeOrigin::signed(BOB),
+				bounded(APP_DID),
+				bounded(b"QmMeta"),
+				bounded(b"app"),
+				bounded(b"QmLogo"),
+				bounded_vec![],
+				bounded_vec![]
+			),
+			Error::<Test>::AppAlreadyExists
+		);
+		assert_noop!(
+			Samaritan::create_app(
+				RuntimeOrigin::signed(BOB),
+				bounded(ALICE_DID),
+				bounded(b"QmMeta"),
+				bounded(b"app"),
+				bounded(b"QmLogo"),
+				bounded_vec![],
+				bounded_vec![]
+			),
+			Error::<Test>::DIDAlreadyRegistered
+		);
+	});
+}
+
+#[test]
+fn create_app_checks_bounds() {
+	let call = SamaritanCall::create_app {
+		did: did(APP_DID),
+		meta_hash: bounded(b"QmMeta"),
+		name: bounded(b"app"),
+		logo: bounded(b"QmLogo"),
+		redirect_uris: bounded_vec![],
+		scopes: bounded_vec![],
+	};
+	let create = |name: Vec<u8>, uris: Vec<Vec<u8>>, scopes: Vec<Vec<u8>>| {
+		let raw = raw_call(call.clone(), (APP_DID.to_vec(), b"QmMeta".to_vec(), name, b"QmLogo".to_vec(), uris, scopes));
+		RuntimeCall::decode(&mut &raw[..])
+	};
+
+	assert!(create(b"app".to_vec(), vec![b"uri".to_vec(); 2], vec![b"scope".to_vec(); 4]).is_ok());
+	assert!(create(vec![b'n'; 33], vec![], vec![]).is_err());
+	assert!(create(b"app".to_vec(), vec![vec![b'u'; 65]], vec![]).is_err());
+	assert!(create(b"app".to_vec(), vec![b"uri".to_vec(); 3], vec![]).is_err());
+	assert!(create(b"app".to_vec(), vec![], vec![vec![b's'; 17]]).is_err());
+	assert!(create(b"app".to_vec(), vec![], vec![b"scope".to_vec(); 5]).is_err());
+}
+
+#[test]
+fn update_app_resets_verification_on_rebrand() {
+	new_test_ext().execute_with(|| {
+		new_app(BOB, APP_DID);
+		assert_ok!(Samaritan::verify_app(RuntimeOrigin::root(), bounded(APP_DID), H256::repeat_byte(1)));
+
+		let update = |logo: &[u8], uri: &[u8]| {
+			Samaritan::update_app(
+				RuntimeOrigin::signed(BOB),
+				bounded(APP_DID),
+				bounded(b"app"),
+				bounded(logo),
+				bounded_vec![bounded(uri)],
+				bounded_vec![bounded(b"profile")],
+			)
+		};
+
+		// new scopes alone keep the verification
+		assert_ok!(update(b"QmLogo", b"https://app.example/cb"));
+		let app = Samaritan::app_reg(did(APP_DID)).unwrap();
+		assert_eq!(app.scopes.len(), 1);
+		assert!(matches!(app.verification, Verification::Verified { .. }));
+		System::assert_last_event(Event::AppUpdated { did: APP_DID.to_vec() }.into());
+
+		assert_ok!(update(b"QmLogo2", b"https://app.example/cb"));
+		assert_eq!(Samaritan::app_reg(did(APP_DID)).unwrap().verification, Verification::Unverified);
+
+		// so does sending logins somewhere else
+		assert_ok!(Samaritan::verify_app(RuntimeOrigin::root(), bounded(APP_DID), H256::repeat_byte(2)));
+		assert_ok!(update(b"QmLogo2", b"https://evil.example/cb"));
+		assert_eq!(Samaritan::app_reg(did(APP_DID)).unwrap().verification, Verification::Unverified);
+	});
+}
+
+#[test]
+fn update_app_requires_owner() {
+	new_test_ext().execute_with(|| {
+		new_app(BOB, APP_DID);
+
+		assert_noop!(
+			Samaritan::update_app(RuntimeOrigin::signed(ALICE), bounded(APP_DID), bounded(b"app"), bounded(b"QmLogo"), bounded_vec![], bounded_vec![]),
+			Error::<Test>::NotAppOwner
+		);
+		assert_noop!(
+			Samaritan::update_app(RuntimeOrigin::signed(BOB), bounded(OTHER_APP_DID), bounded(b"app"), bounded(b"QmLogo"), bounded_vec![], bounded_vec![]),
+			Error::<Test>::AppNotFound
+		);
+	});
+}
+
+#[test]
+fn set_app_status_and_retire_app_work() {
+	new_test_ext().execute_with(|| {
+		new_app(BOB, APP_DID);
+
+		// the owner can't lift a suspension on its own
+		assert_noop!(
+			Samaritan::set_app_status(RuntimeOrigin::signed(BOB), bounded(APP_DID), AppStatus::Suspended),
+			BadOrigin
+		);
+		assert_noop!(
+			Samaritan::set_app_status(RuntimeOrigin::root(), bounded(OTHER_APP_DID), AppStatus::Suspended),
+			Error::<Test>::AppNotFound
+		);
+		assert_ok!(Samaritan::set_app_status(RuntimeOrigin::root(), bounded(APP_DID), AppStatus::Suspended));
+		assert_eq!(Samaritan::app_reg(did(APP_DID)).unwrap().status, AppStatus::Suspended);
+		System::assert_last_event(
+			Event::AppStatusChanged { did: APP_DID.to_vec(), status: AppStatus::Suspended }.into(),
+		);
+
+		assert_noop!(Samaritan::retire_app(RuntimeOrigin::signed(ALICE), bounded(APP_DID)), Error::<Test>::NotAppOwner);
+		assert_ok!(Samaritan::retire_app(RuntimeOrigin::signed(BOB), bounded(APP_DID)));
+		assert!(Samaritan::app_reg(did(APP_DID)).is_none());
+		// the document history stays
+		assert!(Samaritan::doc_metareg(did(APP_DID)).is_some());
+		System::assert_last_event(Event::AppRetired { did: APP_DID.to_vec() }.into());
+
+		assert_noop!(Samaritan::retire_app(RuntimeOrigin::signed(BOB), bounded(APP_DID)), Error::<Test>::AppNotFound);
+	});
+}
+
+#[test]
+fn retire_app_waits_for_plans() {
+	new_test_ext().execute_with(|| {
+		new_app(BOB, APP_DID);
+		assert_ok!(Samaritan::create_plan(RuntimeOrigin::signed(BOB), bounded(APP_DID), 100, 10, 5));
+
+		assert_noop!(
+			Samaritan::retire_app(RuntimeOrigin::signed(BOB), bounded(APP_DID)),
+			Error::<Test>::AppHasActivePlans
+		);
+
+		assert_ok!(Samaritan::retire_plan(RuntimeOrigin::signed(BOB), bounded(APP_DID), 0));
+		assert_noop!(
+			Samaritan::retire_plan(RuntimeOrigin::signed(BOB), bounded(APP_DID), 0),
+			Error::<Test>::PlanNotFound
+		);
+		assert_eq!(Samaritan::active_plans(did(APP_DID)), 0);
+		assert_ok!(Samaritan::retire_app(RuntimeOrigin::signed(BOB), bounded(APP_DID)));
+	});
+}
+
+#[test]
+fn signup_tokens_of_retired_apps_can_be_reaped() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		assert_ok!(Samaritan::generate_token(RuntimeOrigin::signed(ALICE), bounded(APP_DID), bounded(ALICE_DID), commitment(APP_DID, ALICE_DID)));
+		let reserved = Balances::reserved_balance(&ALICE);
+
+		let reap = || Samaritan::reap_signup_token(RuntimeOrigin::signed(CHARLIE), bounded(APP_DID), bounded(ALICE_DID));
+		assert_noop!(reap(), Error::<Test>::SignUpTokenInUse);
+
+		assert_ok!(Samaritan::retire_app(RuntimeOrigin::signed(BOB), bounded(APP_DID)));
+		assert_ok!(reap());
+		assert!(Samaritan::signup_data(did(APP_DID), did(ALICE_DID)).is_none());
+		assert!(Balances::reserved_balance(&ALICE) < reserved);
+		System::assert_last_event(
+			Event::SignUpTokenReaped { app_did: APP_DID.to_vec(), did: ALICE_DID.to_vec() }.into(),
+		);
+
+		assert_noop!(reap(), Error::<Test>::SignUpTokenNotFound);
+	});
+}
+
+#[test]
+fn signed_calls_reject_unsigned_origins() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+
+		assert_noop!(
+			Samaritan::create_samaritan(RuntimeOrigin::none(), bounded(b"alice"), bounded(DAVE_DID), bounded(b"QmMeta")),
+			BadOrigin
+		);
+		assert_noop!(
+			Samaritan::generate_token(RuntimeOrigin::none(), bounded(APP_DID), bounded(ALICE_DID), commitment(APP_DID, ALICE_DID)),
+			BadOrigin
+		);
+		assert_noop!(
+			Samaritan::grant_consent(RuntimeOrigin::none(), bounded(ALICE_DID), bounded(APP_DID), bounded_vec![bounded(b"profile")], None),
+			BadOrigin
+		);
+		assert_noop!(Samaritan::revoke_consent(RuntimeOrigin::none(), bounded(ALICE_DID), bounded(APP_DID)), BadOrigin);
+		assert_noop!(Samaritan::approve_pairwise_recovery(RuntimeOrigin::none(), bounded(PAIRWISE_DID)), BadOrigin);
+		assert_noop!(Samaritan::reap_signup_token(RuntimeOrigin::none(), bounded(APP_DID), bounded(ALICE_DID)), BadOrigin);
+	});
+}
+
+#[test]
+fn app_verification_requires_verifier_origin() {
+	new_test_ext().execute_with(|| {
+		new_app(BOB, APP_DID);
+		let evidence = H256::repeat_byte(7);
+
+		assert_noop!(Samaritan::verify_app(RuntimeOrigin::signed(BOB), bounded(APP_DID), evidence), BadOrigin);
+		assert_noop!(
+			Samaritan::verify_app(RuntimeOrigin::root(), bounded(OTHER_APP_DID), evidence),
+			Error::<Test>::AppNotFound
+		);
+
+		assert_ok!(Samaritan::verify_app(RuntimeOrigin::root(), bounded(APP_DID), evidence));
+		assert_eq!(
+			Samaritan::app_reg(did(APP_DID)).unwrap().verification,
+			Verification::Verified { evidence, at: 1_000 }
+		);
+		System::assert_last_event(Event::AppVerified { did: APP_DID.to_vec(), evidence }.into());
+
+		Now::set(2_000);
+		assert_noop!(Samaritan::revoke_app_verification(RuntimeOrigin::signed(BOB), bounded(APP_DID), evidence), BadOrigin);
+		assert_ok!(Samaritan::revoke_app_verification(RuntimeOrigin::root(), bounded(APP_DID), evidence));
+		assert_eq!(
+			Samaritan::app_reg(did(APP_DID)).unwrap().verification,
+			Verification::Revoked { evidence, at: 2_000 }
+		);
+		System::assert_last_event(Event::AppVerificationRevoked { did: APP_DID.to_vec(), evidence }.into());
+	});
+}
+
+#[test]
+fn signup_token_can_be_redeemed_once() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		let reserved = Balances::reserved_balance(&ALICE);
+
+		assert_ok!(Samaritan::generate_token(RuntimeOrigin::signed(ALICE), bounded(APP_DID), bounded(ALICE_DID), commitment(APP_DID, ALICE_DID)));
+		let token = TOKEN;
+		// only the app and user know the token
+		System::assert_last_event(Event::SignUpTokenGenerated { app_did: APP_DID.to_vec(), did: ALICE_DID.to_vec() }.into());
+		assert!(Samaritan::verify_signup_token(APP_DID.to_vec(), ALICE_DID.to_vec(), token));
+		assert!(Balances::reserved_balance(&ALICE) > reserved);
+
+		assert_noop!(
+			Samaritan::redeem_token(RuntimeOrigin::signed(BOB), bounded(APP_DID), bounded(ALICE_DID), H256::zero()),
+			Error::<Test>::InvalidSignUpToken
+		);
+
+		assert_ok!(Samaritan::redeem_token(RuntimeOrigin::signed(BOB), bounded(APP_DID), bounded(ALICE_DID), token));
+		assert!(Samaritan::signup_data(did(APP_DID), did(ALICE_DID)).is_none());
+		assert_eq!(Balances::reserved_balance(&ALICE), reserved);
+		System::assert_last_event(
+			Event::SignUpTokenRedeemed { app_did: APP_DID.to_vec(), did: ALICE_DID.to_vec() }.into(),
+		);
+
+		assert_noop!(
+			Samaritan::redeem_token(RuntimeOrigin::signed(BOB), bounded(APP_DID), bounded(ALICE_DID), token),
+			Error::<Test>::SignUpTokenNotFound
+		);
+	});
+}
+
+#[test]
+fn generate_token_requires_app_owner_or_did_controller() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		assert_ok!(Samaritan::generate_token(RuntimeOrigin::signed(ALICE), bounded(APP_DID), bounded(ALICE_DID), commitment(APP_DID, ALICE_DID)));
+		let entry = Samaritan::signup_data(did(APP_DID), did(ALICE_DID)).unwrap();
+
+		// another user can't replace the pending token or take over its deposit
+		assert_noop!(
+			Samaritan::generate_token(RuntimeOrigin::signed(CHARLIE), bounded(APP_DID), bounded(ALICE_DID), commitment(APP_DID, ALICE_DID)),
+			Error::<Test>::NotDIDOwner
+		);
+		assert_eq!(Samaritan::signup_data(did(APP_DID), did(ALICE_DID)), Some(entry));
+
+		// the app owner pays for the tokens it issues, to registered DIDs only
+		assert_noop!(
+			Samaritan::generate_token(RuntimeOrigin::signed(BOB), bounded(APP_DID), bounded(DAVE_DID), commitment(APP_DID, DAVE_DID)),
+			Error::<Test>::DIDNotFound
+		);
+		new_samaritan(DAVE, DAVE_DID);
+		let reserved = Balances::reserved_balance(&BOB);
+		assert_ok!(Samaritan::generate_token(RuntimeOrigin::signed(BOB), bounded(APP_DID), bounded(DAVE_DID), commitment(APP_DID, DAVE_DID)));
+		assert!(Balances::reserved_balance(&BOB) > reserved);
+
+		// so does a DID origin's owner
+		assert_ok!(Samaritan::generate_token(did_origin(ALICE_DID), bounded(APP_DID), bounded(ALICE_DID), commitment(APP_DID, ALICE_DID)));
+	});
+}
+
+#[test]
+fn generate_token_requires_active_app() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+
+		assert_noop!(
+			Samaritan::generate_token(RuntimeOrigin::signed(ALICE), bounded(OTHER_APP_DID), bounded(ALICE_DID), commitment(OTHER_APP_DID, ALICE_DID)),
+			Error::<Test>::AppNotFound
+		);
+
+		assert_ok!(Samaritan::set_app_status(RuntimeOrigin::root(), bounded(APP_DID), AppStatus::Suspended));
+		assert_noop!(
+			Samaritan::generate_token(RuntimeOrigin::signed(ALICE), bounded(APP_DID), bounded(ALICE_DID), commitment(APP_DID, ALICE_DID)),
+			Error::<Test>::AppSuspended
+		);
+	});
+}
+
+#[test]
+fn grant_consent_works() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+
+		assert_ok!(Samaritan::grant_consent(
+			RuntimeOrigin::signed(ALICE),
+			bounded(ALICE_DID),
+			bounded(APP_DID),
+			bounded_vec![bounded(b"profile")],
+			Some(2_000)
+		));
+		System::assert_last_event(Event::ConsentGranted { did: ALICE_DID.to_vec(), app_did: APP_DID.to_vec() }.into());
+
+		let grant = Samaritan::consent(ALICE_DID.to_vec(), APP_DID.to_vec()).unwrap();
+		assert_eq!(grant.granted_at, 1_000);
+		assert!(!grant.revoked);
+		assert!(Samaritan::has_consent(ALICE_DID.to_vec(), APP_DID.to_vec(), b"profile".to_vec()));
+		assert!(!Samaritan::has_consent(ALICE_DID.to_vec(), APP_DID.to_vec(), b"email".to_vec()));
+
+		Now::set(2_000);
+		assert!(!Samaritan::has_consent(ALICE_DID.to_vec(), APP_DID.to_vec(), b"profile".to_vec()));
+	});
+}
+
+#[test]
+fn grant_consent_chains_replaced_grants() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+
+		let grant = |scope: &[u8]| {
+			assert_ok!(Samaritan::grant_consent(
+				RuntimeOrigin::signed(ALICE),
+				bounded(ALICE_DID),
+				bounded(APP_DID),
+				bounded_vec![bounded(scope)],
+				None
+			));
+			Samaritan::consent(ALICE_DID.to_vec(), APP_DID.to_vec()).unwrap()
+		};
+
+		// nothing was replaced yet
+		let first = grant(b"profile");
+		assert_eq!(Samaritan::consent_history(bounded(ALICE_DID), bounded(APP_DID)), None);
+
+		let second = grant(b"email");
+		let head = Samaritan::consent_receipt(None, &first);
+		assert_eq!(Samaritan::consent_history(bounded(ALICE_DID), bounded(APP_DID)), Some(head));
+
+		grant(b"profile");
+		assert_eq!(
+			Samaritan::consent_history(bounded(ALICE_DID), bounded(APP_DID)),
+			Some(Samaritan::consent_receipt(Some(&head), &second))
+		);
+	});
+}
+
+#[test]
+fn grant_consent_checks_scopes_and_expiry() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+
+		let grant = |who: AccountId, app_did: &[u8], scope: &[u8], expires_at: Option<u64>| {
+			Samaritan::grant_consent(
+				RuntimeOrigin::signed(who),
+				bounded(ALICE_DID),
+				bounded(app_did),
+				bounded_vec![bounded(scope)],
+				expires_at,
+			)
+		};
+
+		assert_noop!(grant(BOB, APP_DID, b"profile", None), Error::<Test>::NotDIDOwner);
+		assert_noop!(grant(ALICE, OTHER_APP_DID, b"profile", None), Error::<Test>::AppNotFound);
+		assert_noop!(grant(ALICE, APP_DID, b"location", None), Error::<Test>::ScopeNotRequested);
+		assert_noop!(grant(ALICE, APP_DID, b"profile", Some(1_000)), Error::<Test>::InvalidExpiry);
+	});
+}
+
+#[test]
+fn revoke_consent_keeps_a_receipt() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+
+		assert_noop!(
+			Samaritan::revoke_consent(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(APP_DID)),
+			Error::<Test>::ConsentNotFound
+		);
+
+		grant_all(ALICE, ALICE_DID, APP_DID);
+		assert_noop!(
+			Samaritan::revoke_consent(RuntimeOrigin::signed(BOB), bounded(ALICE_DID), bounded(APP_DID)),
+			Error::<Test>::NotDIDOwner
+		);
+		assert_ok!(Samaritan::revoke_consent(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(APP_DID)));
+
+		assert!(Samaritan::consent(ALICE_DID.to_vec(), APP_DID.to_vec()).unwrap().revoked);
+		assert!(!Samaritan::has_consent(ALICE_DID.to_vec(), APP_DID.to_vec(), b"profile".to_vec()));
+		System::assert_last_event(Event::ConsentRevoked { did: ALICE_DID.to_vec(), app_did: APP_DID.to_vec() }.into());
+	});
+}
+
+#[test]
+fn pairwise_did_is_bound_to_its_app() {
+	new_test_ext().execute_with(|| {
+		let pair = did_with_key();
+		new_app(BOB, APP_DID);
+		new_app(BOB, OTHER_APP_DID);
+		let signature = pairing(&pair, PAIRWISE_DID, APP_DID);
+		let link = Samaritan::pairwise_link(PAIRWISE_DID, ALICE_DID, &signature);
+
+		assert_noop!(
+			Samaritan::register_pairwise_did(
+				RuntimeOrigin::signed(CHARLIE),
+				bounded(PAIRWISE_DID),
+				bounded(b"did:sam:nowhere"),
+				link,
+				bounded(b"QmEscrow")
+			),
+			Error::<Test>::AppNotFound
+		);
+		assert_ok!(Samaritan::register_pairwise_did(
+			RuntimeOrigin::signed(CHARLIE),
+			bounded(PAIRWISE_DID),
+			bounded(APP_DID),
+			link,
+			bounded(b"QmEscrow")
+		));
+		assert_eq!(Samaritan::did_owner(did(PAIRWISE_DID)), Some(CHARLIE));
+		assert_eq!(Samaritan::pairwise_dids(did(PAIRWISE_DID)).unwrap().app_did, did(APP_DID));
+		assert!(Samaritan::verify_pairwise_link(PAIRWISE_DID.to_vec(), ALICE_DID.to_vec(), signature));
+		// a pairing signed for another app does not open the commitment
+		let other = pairing(&pair, PAIRWISE_DID, OTHER_APP_DID);
+		assert!(!Samaritan::verify_pairwise_link(PAIRWISE_DID.to_vec(), ALICE_DID.to_vec(), other));
+		System::assert_last_event(
+			Event::PairwiseDidRegistered { did: PAIRWISE_DID.to_vec(), app_did: APP_DID.to_vec() }.into(),
+		);
+
+		assert_noop!(
+			Samaritan::register_pairwise_did(
+				RuntimeOrigin::signed(DAVE),
+				bounded(PAIRWISE_DID),
+				bounded(APP_DID),
+				link,
+				bounded(b"QmEscrow")
+			),
+			Error::<Test>::DIDAlreadyRegistered
+		);
+
+		// it can only be used with the app it was created for
+		assert_noop!(
+			Samaritan::generate_token(RuntimeOrigin::signed(CHARLIE), bounded(OTHER_APP_DID), bounded(PAIRWISE_DID), commitment(OTHER_APP_DID, PAIRWISE_DID)),
+			Error::<Test>::PairwiseAppMismatch
+		);
+		assert_noop!(
+			Samaritan::grant_consent(
+				RuntimeOrigin::signed(CHARLIE),
+				bounded(PAIRWISE_DID),
+				bounded(OTHER_APP_DID),
+				bounded_vec![bounded(b"profile")],
+				None
+			),
+			Error::<Test>::PairwiseAppMismatch
+		);
+		grant_all(CHARLIE, PAIRWISE_DID, APP_DID);
+
+		// nor turned into a samaritan or app DID, it may change hands on recovery
+		assert_noop!(
+			Samaritan::create_samaritan(RuntimeOrigin::signed(CHARLIE), bounded(b"samaritan"), bounded(PAIRWISE_DID), bounded(b"QmDoc0")),
+			Error::<Test>::DIDAlreadyRegistered
+		);
+		assert_noop!(
+			Samaritan::create_app(
+				RuntimeOrigin::signed(CHARLIE),
+				bounded(PAIRWISE_DID),
+				bounded(b"QmMeta"),
+				bounded(b"app"),
+				bounded(b"QmLogo"),
+				bounded_vec![bounded(b"https://app.example/cb")],
+				bounded_vec![bounded(b"profile")]
+			),
+			Error::<Test>::DIDAlreadyRegistered
+		);
+	});
+}
+
+/// the master DID key's signature pairing a pseudonymous DID with an app
+fn pairing(pair: &sr25519::Pair, did_str: &[u8], app_did_str: &[u8]) -> MultiSignature {
+	pair.sign(&Samaritan::pairwise_payload(did_str, app_did_str)).into()
+}
+
+/// Charlie holds a pairwise DID of Alice's for Bob's app, returning the master signature
+fn pairwise_of_alice() -> MultiSignature {
+	let pair = did_with_key();
+	new_app(BOB, APP_DID);
+	let signature = pairing(&pair, PAIRWISE_DID, APP_DID);
+
+	assert_ok!(Samaritan::register_pairwise_did(
+		RuntimeOrigin::signed(CHARLIE),
+		bounded(PAIRWISE_DID),
+		bounded(APP_DID),
+		Samaritan::pairwise_link(PAIRWISE_DID, ALICE_DID, &signature),
+		bounded(b"QmEscrow")
+	));
+
+	signature
+}
+
+fn recover(who: AccountId, did_str: &[u8], signature: MultiSignature, new_owner: AccountId) -> sp_runtime::DispatchResult {
+	Samaritan::recover_pairwise_did(RuntimeOrigin::signed(who), bounded(did_str), bounded(ALICE_DID), signature, new_owner)
+}
+
+#[test]
+fn pairwise_did_can_be_recovered() {
+	new_test_ext().execute_with(|| {
+		let signature = pairwise_of_alice();
+		new_samaritan(DAVE, DAVE_DID);
+
+		assert_noop!(recover(ALICE, b"did:sam:unknown", signature.clone(), EVE), Error::<Test>::PairwiseDidNotFound);
+		assert_noop!(recover(DAVE, PAIRWISE_DID, signature.clone(), EVE), Error::<Test>::NotRecoveryAuthority);
+
+		// a valid pairing the commitment was not made with
+		let pair = sr25519::Pair::from_seed(&[7u8; 32]);
+		let other = pairing(&pair, PAIRWISE_DID, OTHER_APP_DID);
+		assert_noop!(recover(ALICE, PAIRWISE_DID, other, EVE), Error::<Test>::InvalidPairwiseLink);
+
+		// a commitment made with a key that is not Alice's can't be opened either
+		let forged = pairing(&sr25519::Pair::from_seed(&[8u8; 32]), b"did:sam:forged", APP_DID);
+		assert_ok!(Samaritan::register_pairwise_did(
+			RuntimeOrigin::signed(CHARLIE),
+			bounded(b"did:sam:forged"),
+			bounded(APP_DID),
+			Samaritan::pairwise_link(b"did:sam:forged", ALICE_DID, &forged),
+			bounded(b"QmEscrow")
+		));
+		assert_noop!(recover(ALICE, b"did:sam:forged", forged, EVE), Error::<Test>::InvalidPairwiseLink);
+
+		// the master owner hands it over at once, signatures made for the old owner are void
+		assert_ok!(recover(ALICE, PAIRWISE_DID, signature, EVE));
+		assert_eq!(Samaritan::did_owner(did(PAIRWISE_DID)), Some(EVE));
+		assert_eq!(Samaritan::did_nonce_of(PAIRWISE_DID.to_vec()), 1);
+		System::assert_last_event(Event::PairwiseDidRecovered { did: PAIRWISE_DID.to_vec() }.into());
+	});
+}
+
+#[test]
+fn pairwise_recovery_needs_a_quorum_majority() {
+	new_test_ext().execute_with(|| {
+		let signature = pairwise_of_alice();
+		for (who, did_str) in [(DAVE, DAVE_DID), (EVE, b"did:sam:eve" as &[u8]), (BOB, b"did:sam:bob" as &[u8])] {
+			new_samaritan(who, did_str);
+			assert_ok!(Samaritan::update_quorum(RuntimeOrigin::signed(ALICE), DidRef::Did(bounded(ALICE_DID)), DidRef::Did(bounded(did_str))));
+		}
+		let approve = |who: AccountId| Samaritan::approve_pairwise_recovery(RuntimeOrigin::signed(who), bounded(PAIRWISE_DID));
+
+		assert_noop!(approve(DAVE), Error::<Test>::RecoveryNotFound);
+
+		// one of three members opens the request, it holds a deposit
+		let free = Balances::free_balance(&DAVE);
+		assert_ok!(recover(DAVE, PAIRWISE_DID, signature.clone(), EVE));
+		assert_eq!(Samaritan::did_owner(did(PAIRWISE_DID)), Some(CHARLIE));
+		assert!(Balances::free_balance(&DAVE) < free);
+		System::assert_last_event(Event::PairwiseRecoveryApproved { did: PAIRWISE_DID.to_vec(), approvals: 1 }.into());
+
+		assert_noop!(approve(DAVE), Error::<Test>::AlreadyApproved);
+		assert_noop!(approve(CHARLIE), Error::<Test>::NotRecoveryAuthority);
+
+		// another member redirecting the request starts the approvals over
+		assert_ok!(recover(BOB, PAIRWISE_DID, signature, BOB));
+		assert_eq!(Balances::free_balance(&DAVE), free);
+		assert_eq!(Samaritan::pairwise_recoveries(did(PAIRWISE_DID)).unwrap().approvals.len(), 1);
+
+		// the second of three approvals hands it over
+		assert_ok!(approve(EVE));
+		assert_eq!(Samaritan::did_owner(did(PAIRWISE_DID)), Some(BOB));
+		assert!(Samaritan::pairwise_recoveries(did(PAIRWISE_DID)).is_none());
+		let key = PairwiseRecoveries::<Test>::hashed_key_for(did(PAIRWISE_DID));
+		assert!(StorageDeposits::<Test>::get(H256(blake2_256(&key))).is_none());
+		System::assert_last_event(Event::PairwiseDidRecovered { did: PAIRWISE_DID.to_vec() }.into());
+		assert_noop!(approve(DAVE), Error::<Test>::RecoveryNotFound);
+	});
+}
+
+#[test]
+fn pairwise_recovery_revokes_the_old_keys() {
+	new_test_ext().execute_with(|| {
+		let signature = pairwise_of_alice();
+		let old_key = sr25519::Pair::from_seed(&[9u8; 32]);
+		assert_ok!(Samaritan::add_did_key(RuntimeOrigin::signed(CHARLIE), bounded(PAIRWISE_DID), old_key.public().into()));
+		assert_ok!(Samaritan::authorize_session_key(
+			RuntimeOrigin::signed(CHARLIE),
+			bounded(PAIRWISE_DID),
+			bounded(APP_DID),
+			DAVE,
+			bounded_vec![bounded(b"update_profile")],
+			10,
+			5
+		));
+
+		assert_ok!(recover(ALICE, PAIRWISE_DID, signature, EVE));
+		assert!(Samaritan::did_keys(did(PAIRWISE_DID)).is_empty());
+
+		// the old key signs for the nonce after the recovery
+		let call = profile_call(PAIRWISE_DID, b"QmProfile");
+		let nonce = Samaritan::did_nonce_of(PAIRWISE_DID.to_vec());
+		let payload = Samaritan::did_call_payload(&did(PAIRWISE_DID), nonce, 10, &call);
+		assert_noop!(
+			Samaritan::submit_did_call(
+				RuntimeOrigin::signed(CHARLIE),
+				bounded(PAIRWISE_DID),
+				Box::new(call.clone()),
+				nonce,
+				10,
+				old_key.sign(&payload).into()
+			),
+			Error::<Test>::BadDidSignature
+		);
+		assert_noop!(Samaritan::session_call(RuntimeOrigin::signed(DAVE), Box::new(call)), Error::<Test>::SessionKeyNotFound);
+	});
+}
+
+fn publish(version: &[u8], channel: ReleaseChannel) -> sp_runtime::DispatchResult {
+	Samaritan::publish_release(
+		RuntimeOrigin::signed(BOB),
+		bounded(APP_DID),
+		bounded(version),
+		bounded(b"QmManifest"),
+		bounded_vec![bounded(b"profile")],
+		H256::repeat_byte(1),
+		channel,
+	)
+}
+
+#[test]
+fn publish_release_keeps_a_rolling_window() {
+	new_test_ext().execute_with(|| {
+		new_app(BOB, APP_DID);
+
+		for version in [b"1.0", b"1.1", b"1.2", b"1.3"] {
+			assert_ok!(publish(version, ReleaseChannel::Stable));
+		}
+		System::assert_last_event(
+			Event::ReleasePublished { app_did: APP_DID.to_vec(), version: b"1.3".to_vec(), channel: ReleaseChannel::Stable }
+				.into(),
+		);
+
+		// MaxReleases is 3, the oldest release was dropped
+		let versions: Vec<Vec<u8>> =
+			Samaritan::app_releases(did(APP_DID)).iter().map(|r| r.version.to_vec()).collect();
+		assert_eq!(versions, vec![b"1.1".to_vec(), b"1.2".to_vec(), b"1.3".to_vec()]);
+
+		assert_noop!(publish(b"1.3", ReleaseChannel::Beta), Error::<Test>::DuplicateRelease);
+		assert_noop!(
+			Samaritan::publish_release(
+				RuntimeOrigin::signed(ALICE),
+				bounded(APP_DID),
+				bounded(b"2.0"),
+				bounded(b"QmManifest"),
+				bounded_vec![],
+				H256::zero(),
+				ReleaseChannel::Stable
+			),
+			Error::<Test>::NotAppOwner
+		);
+	});
+}
+
+#[test]
+fn rolling_window_keeps_the_latest_stable_release() {
+	new_test_ext().execute_with(|| {
+		new_app(BOB, APP_DID);
+		assert_ok!(publish(b"1.0", ReleaseChannel::Stable));
+		for version in [b"1.1", b"1.2", b"1.3"] {
+			assert_ok!(publish(version, ReleaseChannel::Beta));
+		}
+
+		// MaxReleases is 3, the oldest beta goes instead of the only stable release
+		let versions = || Samaritan::app_releases(did(APP_DID)).iter().map(|r| r.version.to_vec()).collect::<Vec<_>>();
+		assert_eq!(versions(), vec![b"1.0".to_vec(), b"1.2".to_vec(), b"1.3".to_vec()]);
+		assert_eq!(Samaritan::latest_release(APP_DID.to_vec(), ReleaseChannel::Stable).unwrap().version.to_vec(), b"1.0".to_vec());
+
+		// a dropped version can't be published again
+		assert_noop!(publish(b"1.1", ReleaseChannel::Stable), Error::<Test>::DuplicateRelease);
+
+		// a new stable release replaces it
+		assert_ok!(publish(b"2.0", ReleaseChannel::Stable));
+		assert_eq!(versions(), vec![b"1.2".to_vec(), b"1.3".to_vec(), b"2.0".to_vec()]);
+	});
+}
+
+#[test]
+fn latest_release_skips_yanked_and_other_channels() {
+	new_test_ext().execute_with(|| {
+		new_app(BOB, APP_DID);
+		assert_ok!(publish(b"1.0", ReleaseChannel::Stable));
+		assert_ok!(publish(b"1.1", ReleaseChannel::Beta));
+		assert_ok!(publish(b"1.2", ReleaseChannel::Stable));
+
+		let latest = |channel| Samaritan::latest_release(APP_DID.to_vec(), channel).map(|r| r.version.to_vec());
+		assert_eq!(latest(ReleaseChannel::Stable), Some(b"1.2".to_vec()));
+		assert_eq!(latest(ReleaseChannel::Beta), Some(b"1.1".to_vec()));
+
+		assert_ok!(Samaritan::yank_release(RuntimeOrigin::signed(BOB), bounded(APP_DID), bounded(b"1.2")));
+		System::assert_last_event(Event::ReleaseYanked { app_did: APP_DID.to_vec(), version: b"1.2".to_vec() }.into());
+		assert_eq!(latest(ReleaseChannel::Stable), Some(b"1.0".to_vec()));
+
+		assert_ok!(Samaritan::set_release_channel(
+			RuntimeOrigin::signed(BOB),
+			bounded(APP_DID),
+			bounded(b"1.1"),
+			ReleaseChannel::Stable
+		));
+		System::assert_last_event(
+			Event::ReleaseChannelChanged { app_did: APP_DID.to_vec(), version: b"1.1".to_vec(), channel: ReleaseChannel::Stable }
+				.into(),
+		);
+		assert_eq!(latest(ReleaseChannel::Stable), Some(b"1.1".to_vec()));
+		assert_eq!(latest(ReleaseChannel::Beta), None);
+
+		assert_noop!(
+			Samaritan::yank_release(RuntimeOrigin::signed(BOB), bounded(APP_DID), bounded(b"9.9")),
+			Error::<Test>::ReleaseNotFound
+		);
+		assert_noop!(
+			Samaritan::set_release_channel(RuntimeOrigin::signed(ALICE), bounded(APP_DID), bounded(b"1.0"), ReleaseChannel::Beta),
+			Error::<Test>::NotAppOwner
+		);
+	});
+}
+
+#[test]
+fn vault_pointer_requires_live_consent() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+
+		let anchor = |who: AccountId, cid: &[u8]| {
+			Samaritan::set_vault_pointer(RuntimeOrigin::signed(who), bounded(APP_DID), bounded(ALICE_DID), bounded(cid))
+		};
+
+		assert_noop!(anchor(BOB, b"QmVault0"), Error::<Test>::ConsentRequired);
+
+		grant_all(ALICE, ALICE_DID, APP_DID);
+		assert_noop!(anchor(ALICE, b"QmVault0"), Error::<Test>::NotAppOwner);
+
+		assert_ok!(anchor(BOB, b"QmVault0"));
+		assert_ok!(anchor(BOB, b"QmVault1"));
+		let pointer = Samaritan::data_vaults(did(APP_DID), did(ALICE_DID)).unwrap();
+		assert_eq!(pointer.version, 1);
+		assert_eq!(pointer.cid.to_vec(), b"QmVault1".to_vec());
+		System::assert_last_event(
+			Event::VaultPointerUpdated {
+				app_did: APP_DID.to_vec(),
+				did: ALICE_DID.to_vec(),
+				cid: b"QmVault1".to_vec(),
+				version: 1,
+			}
+			.into(),
+		);
+
+		assert_ok!(Samaritan::set_app_status(RuntimeOrigin::root(), bounded(APP_DID), AppStatus::Suspended));
+		assert_noop!(anchor(BOB, b"QmVault2"), Error::<Test>::AppSuspended);
+
+		assert_ok!(Samaritan::set_app_status(RuntimeOrigin::root(), bounded(APP_DID), AppStatus::Active));
+		assert_ok!(Samaritan::revoke_consent(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(APP_DID)));
+		assert_noop!(anchor(BOB, b"QmVault2"), Error::<Test>::ConsentRequired);
+	});
+}
+
+#[test]
+fn erase_vault_pointer_works() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		grant_all(ALICE, ALICE_DID, APP_DID);
+		assert_ok!(Samaritan::set_vault_pointer(
+			RuntimeOrigin::signed(BOB),
+			bounded(APP_DID),
+			bounded(ALICE_DID),
+			bounded(b"QmVault0")
+		));
+
+		assert_noop!(
+			Samaritan::erase_vault_pointer(RuntimeOrigin::signed(BOB), bounded(ALICE_DID), bounded(APP_DID)),
+			Error::<Test>::NotDIDOwner
+		);
+		assert_ok!(Samaritan::erase_vault_pointer(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(APP_DID)));
+		assert!(Samaritan::data_vaults(did(APP_DID), did(ALICE_DID)).is_none());
+		System::assert_last_event(Event::VaultPointerErased { app_did: APP_DID.to_vec(), did: ALICE_DID.to_vec() }.into());
+
+		assert_noop!(
+			Samaritan::erase_vault_pointer(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(APP_DID)),
+			Error::<Test>::VaultPointerNotFound
+		);
+	});
+}
+
+#[test]
+fn log_access_keeps_the_latest_records() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		grant_all(ALICE, ALICE_DID, APP_DID);
+
+		for purpose in 0..4 {
+			assert_ok!(Samaritan::log_access(
+				RuntimeOrigin::signed(BOB),
+				bounded(APP_DID),
+				bounded(ALICE_DID),
+				bounded(b"profile"),
+				purpose
+			));
+		}
+		System::assert_last_event(Event::DataAccessed { app_did: APP_DID.to_vec(), did: ALICE_DID.to_vec(), seq: 3 }.into());
+
+		// MaxAccessRecords is 3, sequence numbers keep counting
+		let seqs = |from, limit| {
+			Samaritan::access_log(ALICE_DID.to_vec(), from, limit).iter().map(|r| r.seq).collect::<Vec<_>>()
+		};
+		assert_eq!(seqs(0, 10), vec![1, 2, 3]);
+		assert_eq!(seqs(2, 10), vec![2, 3]);
+		assert_eq!(seqs(0, 1), vec![1]);
+
+		assert_noop!(
+			Samaritan::log_access(RuntimeOrigin::signed(ALICE), bounded(APP_DID), bounded(ALICE_DID), bounded(b"profile"), 0),
+			Error::<Test>::NotAppOwner
+		);
+	});
+}
+
+#[test]
+fn log_access_requires_consent_to_the_scope() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		let log = |origin: RuntimeOrigin, scope: &[u8]| {
+			Samaritan::log_access(origin, bounded(APP_DID), bounded(ALICE_DID), bounded(scope), 0)
+		};
+
+		assert_noop!(log(RuntimeOrigin::none(), b"profile"), BadOrigin);
+		assert_noop!(log(RuntimeOrigin::signed(BOB), b"profile"), Error::<Test>::ConsentRequired);
+
+		assert_ok!(Samaritan::grant_consent(
+			RuntimeOrigin::signed(ALICE),
+			bounded(ALICE_DID),
+			bounded(APP_DID),
+			bounded_vec![bounded(b"profile")],
+			None
+		));
+		assert_noop!(log(RuntimeOrigin::signed(BOB), b"email"), Error::<Test>::ConsentRequired);
+		assert_ok!(log(RuntimeOrigin::signed(BOB), b"profile"));
+
+		assert_ok!(Samaritan::revoke_consent(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(APP_DID)));
+		assert_noop!(log(RuntimeOrigin::signed(BOB), b"profile"), Error::<Test>::ConsentRequired);
+		assert_eq!(Samaritan::access_log(ALICE_DID.to_vec(), 0, 10).len(), 1);
+	});
+}
+
+fn authorize(
+	key: AccountId,
+	calls: BoundedVec<BoundedVec<u8, <Test as crate::Config>::MaxCallNameLength>, <Test as crate::Config>::MaxSessionCalls>,
+	expires: u64,
+	max_calls: u32,
+) -> sp_runtime::DispatchResult {
+	Samaritan::authorize_session_key(
+		RuntimeOrigin::signed(ALICE),
+		bounded(ALICE_DID),
+		bounded(APP_DID),
+		key,
+		calls,
+		expires,
+		max_calls,
+	)
+}
+
+#[test]
+fn session_key_makes_allowed_calls() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+
+		assert_ok!(authorize(EVE, bounded_vec![bounded(b"update_profile")], 10, 2));
+		System::assert_last_event(
+			Event::SessionKeyAuthorized { did: ALICE_DID.to_vec(), app_did: APP_DID.to_vec(), key: EVE }.into(),
+		);
+
+		assert_ok!(Samaritan::session_call(RuntimeOrigin::signed(EVE), Box::new(profile_call(ALICE_DID, b"QmProfile"))));
+		assert_eq!(Samaritan::prof_reg(did(ALICE_DID)).unwrap().to_vec(), b"QmProfile".to_vec());
+		assert_eq!(Samaritan::session_keys(&EVE).unwrap().remaining, 1);
+		System::assert_last_event(Event::SessionCallDispatched { did: ALICE_DID.to_vec(), key: EVE }.into());
+
+		let revoke = RuntimeCall::Samaritan(SamaritanCall::revoke_consent {
+			did: bounded(ALICE_DID),
+			app_did: bounded(APP_DID),
+		});
+		assert_noop!(Samaritan::session_call(RuntimeOrigin::signed(EVE), Box::new(revoke)), Error::<Test>::CallNotAllowed);
+
+		// the key acts for its own DID only, a failed call keeps its allowance
+		new_samaritan(DAVE, DAVE_DID);
+		let session_call = RuntimeCall::Samaritan(SamaritanCall::session_call {
+			call: Box::new(profile_call(DAVE_DID, b"QmProfile")),
+		});
+		assert_noop!(session_call.dispatch(RuntimeOrigin::signed(EVE)), Error::<Test>::NotDIDOwner);
+
+		// the last allowed call removes the key
+		assert_ok!(Samaritan::session_call(RuntimeOrigin::signed(EVE), Box::new(profile_call(ALICE_DID, b"QmProfile2"))));
+		assert!(Samaritan::session_keys(&EVE).is_none());
+		assert_noop!(
+			Samaritan::session_call(RuntimeOrigin::signed(EVE), Box::new(profile_call(ALICE_DID, b"QmProfile3"))),
+			Error::<Test>::SessionKeyNotFound
+		);
+	});
+}
+
+#[test]
+fn session_key_expires() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		assert_ok!(authorize(EVE, bounded_vec![bounded(b"update_profile")], 5, 10));
+
+		run_to_block(6);
+		assert_noop!(
+			Samaritan::session_call(RuntimeOrigin::signed(EVE), Box::new(profile_call(ALICE_DID, b"QmProfile"))),
+			Error::<Test>::SessionKeyExpired
+		);
+	});
+}
+
+#[test]
+fn session_key_acts_for_its_app_only() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		new_app(CHARLIE, OTHER_APP_DID);
+		grant_all(ALICE, ALICE_DID, APP_DID);
+		grant_all(ALICE, ALICE_DID, OTHER_APP_DID);
+		assert_ok!(authorize(EVE, bounded_vec![bounded(b"revoke_consent")], 10, 2));
+
+		let revoke = |app_did_str: &[u8]| {
+			Box::new(RuntimeCall::Samaritan(SamaritanCall::revoke_consent {
+				did: bounded(ALICE_DID),
+				app_did: bounded(app_did_str),
+			}))
+		};
+		assert_noop!(
+			Samaritan::session_call(RuntimeOrigin::signed(EVE), revoke(OTHER_APP_DID)),
+			Error::<Test>::SessionAppMismatch
+		);
+		assert!(!Samaritan::consent_grants(did(ALICE_DID), did(OTHER_APP_DID)).unwrap().revoked);
+
+		assert_ok!(Samaritan::session_call(RuntimeOrigin::signed(EVE), revoke(APP_DID)));
+		assert!(Samaritan::consent_grants(did(ALICE_DID), did(APP_DID)).unwrap().revoked);
+	});
+}
+
+#[test]
+fn session_key_sets_vault_pointer() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		new_app(CHARLIE, OTHER_APP_DID);
+		grant_all(ALICE, ALICE_DID, APP_DID);
+		grant_all(ALICE, ALICE_DID, OTHER_APP_DID);
+		assert_ok!(authorize(EVE, bounded_vec![bounded(b"set_vault_pointer")], 10, 3));
+
+		let set = |app_did_str: &[u8]| {
+			Box::new(RuntimeCall::Samaritan(SamaritanCall::set_vault_pointer {
+				app_did: bounded(app_did_str),
+				did: bounded(ALICE_DID),
+				cid: bounded(b"QmVault"),
+			}))
+		};
+		assert_ok!(Samaritan::session_call(RuntimeOrigin::signed(EVE), set(APP_DID)));
+		assert_eq!(Samaritan::data_vaults(did(APP_DID), did(ALICE_DID)).unwrap().cid.to_vec(), b"QmVault".to_vec());
+
+		assert_noop!(
+			Samaritan::session_call(RuntimeOrigin::signed(EVE), set(OTHER_APP_DID)),
+			Error::<Test>::SessionAppMismatch
+		);
+
+		// the user's own DID origin is not the app
+		assert_noop!(
+			Samaritan::set_vault_pointer(did_origin(ALICE_DID), bounded(APP_DID), bounded(ALICE_DID), bounded(b"QmVault")),
+			BadOrigin
+		);
+
+		// consent is still needed
+		assert_ok!(Samaritan::revoke_consent(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(APP_DID)));
+		assert_noop!(
+			Samaritan::session_call(RuntimeOrigin::signed(EVE), set(APP_DID)),
+			Error::<Test>::ConsentRequired
+		);
+	});
+}
+
+#[test]
+fn authorize_session_key_checks_input() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+
+		assert_noop!(authorize(EVE, bounded_vec![bounded(b"update_profile")], 1, 1), Error::<Test>::InvalidExpiry);
+		assert_noop!(
+			Samaritan::authorize_session_key(
+				RuntimeOrigin::signed(ALICE),
+				bounded(ALICE_DID),
+				bounded(OTHER_APP_DID),
+				EVE,
+				bounded_vec![],
+				10,
+				1
+			),
+			Error::<Test>::AppNotFound
+		);
+		assert_noop!(
+			Samaritan::authorize_session_key(
+				RuntimeOrigin::signed(BOB),
+				bounded(ALICE_DID),
+				bounded(APP_DID),
+				EVE,
+				bounded_vec![],
+				10,
+				1
+			),
+			Error::<Test>::NotDIDOwner
+		);
+
+		assert_ok!(authorize(EVE, bounded_vec![bounded(b"update_profile")], 10, 1));
+		assert_noop!(authorize(EVE, bounded_vec![bounded(b"update_profile")], 10, 1), Error::<Test>::SessionKeyInUse);
+	});
+}
+
+#[test]
+fn oversized_call_names_do_not_decode() {
+	// MaxCallNameLength is 32, MaxSessionCalls and MaxSponsoredCalls are 4
+	let session = SamaritanCall::authorize_session_key {
+		did: did(ALICE_DID),
+		app_did: did(APP_DID),
+		key: EVE,
+		calls: bounded_vec![],
+		expires: 10,
+		max_calls: 1,
+	};
+	let authorize = |calls: Vec<Vec<u8>>| {
+		let raw = raw_call(session.clone(), (ALICE_DID.to_vec(), APP_DID.to_vec(), EVE, calls, 10u64, 1u32));
+		RuntimeCall::decode(&mut &raw[..])
+	};
+	assert!(authorize(vec![b"update_profile".to_vec(); 4]).is_ok());
+	assert!(authorize(vec![vec![b'c'; 33]]).is_err());
+	assert!(authorize(vec![b"update_profile".to_vec(); 5]).is_err());
+
+	let sponsorship = SamaritanCall::set_sponsorship { app_did: did(APP_DID), calls: bounded_vec![], max_per_account: 1 };
+	let sponsor = |calls: Vec<Vec<u8>>| {
+		let raw = raw_call(sponsorship.clone(), (APP_DID.to_vec(), calls, 1u32));
+		RuntimeCall::decode(&mut &raw[..])
+	};
+	assert!(sponsor(vec![b"update_profile".to_vec(); 4]).is_ok());
+	assert!(sponsor(vec![vec![b'c'; 33]]).is_err());
+	assert!(sponsor(vec![b"update_profile".to_vec(); 5]).is_err());
+}
+
+#[test]
+fn revoke_session_key_works() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		assert_ok!(authorize(EVE, bounded_vec![bounded(b"update_profile")], 10, 1));
+
+		assert_noop!(Samaritan::revoke_session_key(RuntimeOrigin::signed(BOB), EVE), Error::<Test>::NotDIDOwner);
+		assert_ok!(Samaritan::revoke_session_key(RuntimeOrigin::signed(ALICE), EVE));
+		assert!(Samaritan::session_keys(&EVE).is_none());
+		System::assert_last_event(Event::SessionKeyRevoked { did: ALICE_DID.to_vec(), key: EVE }.into());
+
+		assert_noop!(Samaritan::revoke_session_key(RuntimeOrigin::signed(ALICE), EVE), Error::<Test>::SessionKeyNotFound);
+	});
+}
+
+#[test]
+fn create_and_retire_plan_work() {
+	new_test_ext().execute_with(|| {
+		new_app(BOB, APP_DID);
+
+		assert_noop!(
+			Samaritan::create_plan(RuntimeOrigin::signed(ALICE), bounded(APP_DID), 100, 10, 5),
+			Error::<Test>::NotAppOwner
+		);
+		assert_noop!(
+			Samaritan::create_plan(RuntimeOrigin::signed(BOB), bounded(APP_DID), 100, 0, 5),
+			Error::<Test>::InvalidPeriod
+		);
+
+		assert_ok!(Samaritan::create_plan(RuntimeOrigin::signed(BOB), bounded(APP_DID), 100, 10, 5));
+		assert_ok!(Samaritan::create_plan(RuntimeOrigin::signed(BOB), bounded(APP_DID), 900, 100, 5));
+		System::assert_last_event(Event::PlanCreated { app_did: APP_DID.to_vec(), plan_id: 1 }.into());
+		assert_eq!(Samaritan::next_plan_id(did(APP_DID)), 2);
+		assert_eq!(Samaritan::subscription_plan(did(APP_DID), 1).unwrap().price, 900);
+
+		assert_ok!(Samaritan::retire_plan(RuntimeOrigin::signed(BOB), bounded(APP_DID), 1));
+		assert!(!Samaritan::subscription_plan(did(APP_DID), 1).unwrap().active);
+		System::assert_last_event(Event::PlanRetired { app_did: APP_DID.to_vec(), plan_id: 1 }.into());
+
+		assert_noop!(
+			Samaritan::retire_plan(RuntimeOrigin::signed(BOB), bounded(APP_DID), 7),
+			Error::<Test>::PlanNotFound
+		);
+	});
+}
+
+#[test]
+fn subscribe_pays_the_first_period() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		assert_ok!(Samaritan::create_plan(RuntimeOrigin::signed(BOB), bounded(APP_DID), 100, 10, 5));
+		let (alice, bob) = (Balances::free_balance(&ALICE), Balances::free_balance(&BOB));
+
+		assert_ok!(Samaritan::subscribe(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(APP_DID), 0, 1_000));
+
+		assert_eq!(Balances::free_balance(&ALICE), alice - 100);
+		assert_eq!(Balances::free_balance(&BOB), bob + 100);
+
+		let sub = Samaritan::subscription(did(ALICE_DID), did(APP_DID)).unwrap();
+		assert_eq!(sub.payer, ALICE);
+		assert_eq!(sub.spent, 100);
+		assert_eq!(sub.next_charge, 11);
+		assert_eq!(Samaritan::charges_due(11).to_vec(), vec![(did(ALICE_DID), did(APP_DID))]);
+
+		System::assert_has_event(Event::Subscribed { did: ALICE_DID.to_vec(), app_did: APP_DID.to_vec(), plan_id: 0 }.into());
+		System::assert_last_event(
+			Event::SubscriptionCharged { did: ALICE_DID.to_vec(), app_did: APP_DID.to_vec(), amount: 100 }.into(),
+		);
+	});
+}
+
+#[test]
+fn subscribe_checks_input() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		assert_ok!(Samaritan::create_plan(RuntimeOrigin::signed(BOB), bounded(APP_DID), 100, 10, 5));
+
+		let subscribe = |who: AccountId, plan_id: u32, cap: Balance| {
+			Samaritan::subscribe(RuntimeOrigin::signed(who), bounded(ALICE_DID), bounded(APP_DID), plan_id, cap)
+		};
+
+		assert_noop!(subscribe(BOB, 0, 1_000), Error::<Test>::NotDIDOwner);
+		assert_noop!(subscribe(ALICE, 7, 1_000), Error::<Test>::PlanNotFound);
+		assert_noop!(subscribe(ALICE, 0, 50), Error::<Test>::SpendingCapExceeded);
+
+		assert_ok!(subscribe(ALICE, 0, 1_000));
+		assert_noop!(subscribe(ALICE, 0, 1_000), Error::<Test>::AlreadySubscribed);
+	});
+}
+
+#[test]
+fn subscribe_requires_active_plan_and_app() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		assert_ok!(Samaritan::create_plan(RuntimeOrigin::signed(BOB), bounded(APP_DID), 100, 10, 5));
+
+		let subscribe = || {
+			Samaritan::subscribe(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(APP_DID), 0, 1_000)
+		};
+
+		assert_ok!(Samaritan::set_app_status(RuntimeOrigin::root(), bounded(APP_DID), AppStatus::Suspended));
+		assert_noop!(subscribe(), Error::<Test>::AppSuspended);
+
+		assert_ok!(Samaritan::retire_plan(RuntimeOrigin::signed(BOB), bounded(APP_DID), 0));
+		assert_noop!(subscribe(), Error::<Test>::PlanNotFound);
+	});
+}
+
+#[test]
+fn charge_agenda_fills_up() {
+	new_test_ext().execute_with(|| {
+		new_app(BOB, APP_DID);
+		assert_ok!(Samaritan::create_plan(RuntimeOrigin::signed(BOB), bounded(APP_DID), 100, 10, 5));
+
+		// MaxChargesPerBlock is 2
+		for (who, did_str) in [(ALICE, ALICE_DID), (DAVE, DAVE_DID)] {
+			new_samaritan(who.clone(), did_str);
+			assert_ok!(Samaritan::subscribe(RuntimeOrigin::signed(who), bounded(did_str), bounded(APP_DID), 0, 1_000));
+		}
+
+		new_samaritan(CHARLIE, b"did:sam:charlie");
+		assert_noop!(
+			Samaritan::subscribe(RuntimeOrigin::signed(CHARLIE), bounded(b"did:sam:charlie"), bounded(APP_DID), 0, 1_000),
+			Error::<Test>::ChargeAgendaFull
+		);
+	});
+}
 
 #[test]
-fn it_works_for_default_value() {
+fn subscription_renews_every_period() {
 	new_test_ext().execute_with(|| {
-	// 	// Dispatch a signed extrinsic.
-	// 	assert_ok!(Samaritan::do_something(Origin::signed(1), 42));
-	// 	// Read pallet storage and assert an expected result.
-	// 	assert_eq!(TemplateModule::something(), Some(42));
+		new_subscription(1_000);
+		let bob = Balances::free_balance(&BOB);
+
+		run_to_block(11);
+		let sub = Samaritan::subscription(did(ALICE_DID), did(APP_DID)).unwrap();
+		assert_eq!(sub.spent, 200);
+		assert_eq!(sub.next_charge, 21);
+		assert_eq!(Balances::free_balance(&BOB), bob + 100);
+		assert!(Samaritan::charges_due(11).is_empty());
+		System::assert_last_event(
+			Event::SubscriptionCharged { did: ALICE_DID.to_vec(), app_did: APP_DID.to_vec(), amount: 100 }.into(),
+		);
+
+		run_to_block(21);
+		assert_eq!(Samaritan::subscription(did(ALICE_DID), did(APP_DID)).unwrap().spent, 300);
 	});
 }
 
-// Things you need to _write_ test with
-// assert_ok! e.g.: assert_ok!(Nicks::set_name(RuntimeOrigin::signed(2), b"Dave".to_vec()));
-// assert_eq! e.g.: assert_eq!(Balances::total_balance(&2), 8);
-// assert_noop! e.g.: assert_noop!(Nicks::set_name(RuntimeOrigin::signed(2), b"Dr. David Brubeck, III".to_vec()), Error::<Test>::TooLong,);
+#[test]
+fn charge_deferral_is_bounded() {
+	new_test_ext().execute_with(|| {
+		new_subscription(1_000);
 
+		// fill every agenda the renewal could be pushed to
+		let filler = (did(b"did:sam:x"), did(b"did:sam:y"));
+		for at in 21..=21 + MAX_CHARGE_DEFERRALS as u64 {
+			ChargeAgenda::<Test>::insert(at, BoundedVec::truncate_from(vec![filler.clone(), filler.clone()]));
+		}
 
+		run_to_block(11);
+		assert!(Samaritan::subscription(did(ALICE_DID), did(APP_DID)).is_none());
+		System::assert_last_event(Event::SubscriptionEnded { did: ALICE_DID.to_vec(), app_did: APP_DID.to_vec() }.into());
+	});
+}
 
 #[test]
-fn create_samaritan_works() {
+fn cancelled_subscription_ends_with_the_paid_period() {
+	new_test_ext().execute_with(|| {
+		new_subscription(1_000);
+
+		assert_noop!(
+			Samaritan::cancel_subscription(RuntimeOrigin::signed(BOB), bounded(ALICE_DID), bounded(APP_DID)),
+			Error::<Test>::NotDIDOwner
+		);
+		assert_noop!(
+			Samaritan::cancel_subscription(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(OTHER_APP_DID)),
+			Error::<Test>::SubscriptionNotFound
+		);
+
+		assert_ok!(Samaritan::cancel_subscription(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(APP_DID)));
+		System::assert_last_event(
+			Event::SubscriptionCancelled { did: ALICE_DID.to_vec(), app_did: APP_DID.to_vec() }.into(),
+		);
+		// still usable until the next charge
+		assert!(Samaritan::subscription(did(ALICE_DID), did(APP_DID)).unwrap().cancelled);
+
+		let alice = Balances::free_balance(&ALICE);
+		run_to_block(11);
+		assert!(Samaritan::subscription(did(ALICE_DID), did(APP_DID)).is_none());
+		assert_eq!(Balances::free_balance(&ALICE), alice);
+		System::assert_last_event(Event::SubscriptionEnded { did: ALICE_DID.to_vec(), app_did: APP_DID.to_vec() }.into());
+	});
+}
+
+#[test]
+fn subscription_ends_at_the_spending_cap() {
+	new_test_ext().execute_with(|| {
+		new_subscription(150);
+
+		run_to_block(11);
+		assert!(Samaritan::subscription(did(ALICE_DID), did(APP_DID)).is_none());
+		System::assert_last_event(Event::SubscriptionEnded { did: ALICE_DID.to_vec(), app_did: APP_DID.to_vec() }.into());
+	});
+}
+
+#[test]
+fn retired_plan_ends_subscriptions_at_next_charge() {
+	new_test_ext().execute_with(|| {
+		new_subscription(1_000);
+		assert_ok!(Samaritan::retire_plan(RuntimeOrigin::signed(BOB), bounded(APP_DID), 0));
+
+		run_to_block(11);
+		assert!(Samaritan::subscription(did(ALICE_DID), did(APP_DID)).is_none());
+		System::assert_last_event(Event::SubscriptionEnded { did: ALICE_DID.to_vec(), app_did: APP_DID.to_vec() }.into());
+	});
+}
+
+#[test]
+fn failed_charge_is_retried_after_grace() {
+	new_test_ext().execute_with(|| {
+		new_subscription(1_000);
+		Balances::make_free_balance_be(&ALICE, 50);
+
+		run_to_block(11);
+		let sub = Samaritan::subscription(did(ALICE_DID), did(APP_DID)).unwrap();
+		assert!(sub.past_due);
+		assert_eq!(sub.next_charge, 16);
+		assert_eq!(sub.spent, 100);
+		System::assert_last_event(
+			Event::SubscriptionChargeFailed { did: ALICE_DID.to_vec(), app_did: APP_DID.to_vec() }.into(),
+		);
+
+		Balances::make_free_balance_be(&ALICE, ENDOWMENT);
+		run_to_block(16);
+		let sub = Samaritan::subscription(did(ALICE_DID), did(APP_DID)).unwrap();
+		assert!(!sub.past_due);
+		assert_eq!(sub.next_charge, 26);
+		assert_eq!(sub.spent, 200);
+	});
+}
+
+#[test]
+fn subscription_ends_when_the_retry_fails() {
+	new_test_ext().execute_with(|| {
+		new_subscription(1_000);
+		Balances::make_free_balance_be(&ALICE, 50);
+
+		run_to_block(16);
+		assert!(Samaritan::subscription(did(ALICE_DID), did(APP_DID)).is_none());
+		System::assert_has_event(
+			Event::SubscriptionChargeFailed { did: ALICE_DID.to_vec(), app_did: APP_DID.to_vec() }.into(),
+		);
+		System::assert_last_event(Event::SubscriptionEnded { did: ALICE_DID.to_vec(), app_did: APP_DID.to_vec() }.into());
+	});
+}
+
+#[test]
+fn sponsorship_can_be_set_funded_and_withdrawn() {
+	new_test_ext().execute_with(|| {
+		new_app(BOB, APP_DID);
+		let account = Samaritan::sponsor_account(&did(APP_DID));
+
+		assert_noop!(
+			Samaritan::fund_sponsorship(RuntimeOrigin::signed(ALICE), bounded(APP_DID), 1_000),
+			Error::<Test>::SponsorshipNotFound
+		);
+		assert_noop!(
+			Samaritan::set_sponsorship(RuntimeOrigin::signed(ALICE), bounded(APP_DID), bounded_vec![], 1),
+			Error::<Test>::NotAppOwner
+		);
+
+		assert_ok!(Samaritan::set_sponsorship(
+			RuntimeOrigin::signed(BOB),
+			bounded(APP_DID),
+			bounded_vec![bounded(b"update_profile")],
+			2
+		));
+		assert_eq!(Samaritan::sponsorship(did(APP_DID)).unwrap().max_per_account, 2);
+		System::assert_last_event(Event::SponsorshipUpdated { app_did: APP_DID.to_vec() }.into());
+
+		// anyone can top up the budget
+		assert_ok!(Samaritan::fund_sponsorship(RuntimeOrigin::signed(ALICE), bounded(APP_DID), 1_000));
+		assert_eq!(Balances::free_balance(&account), 1_000);
+		System::assert_last_event(Event::SponsorshipFunded { app_did: APP_DID.to_vec(), amount: 1_000 }.into());
+
+		assert_noop!(
+			Samaritan::withdraw_sponsorship(RuntimeOrigin::signed(ALICE), bounded(APP_DID), 400),
+			Error::<Test>::NotAppOwner
+		);
+		let bob = Balances::free_balance(&BOB);
+		assert_ok!(Samaritan::withdraw_sponsorship(RuntimeOrigin::signed(BOB), bounded(APP_DID), 400));
+		assert_eq!(Balances::free_balance(&account), 600);
+		assert_eq!(Balances::free_balance(&BOB), bob + 400);
+		System::assert_last_event(Event::SponsorshipWithdrawn { app_did: APP_DID.to_vec(), amount: 400 }.into());
+
+		// retiring the app refunds the rest of the budget to the owner
+		assert_ok!(Samaritan::retire_app(RuntimeOrigin::signed(BOB), bounded(APP_DID)));
+		assert_eq!(Balances::free_balance(&account), 0);
+		assert_eq!(Balances::free_balance(&BOB), bob + 1_000);
+		assert!(Samaritan::sponsorship(did(APP_DID)).is_none());
+		System::assert_has_event(Event::SponsorshipWithdrawn { app_did: APP_DID.to_vec(), amount: 600 }.into());
+	});
+}
+
+fn dispatch_info() -> DispatchInfo {
+	DispatchInfo { weight: Weight::from_ref_time(5), ..Default::default() }
+}
+
+#[test]
+fn sponsored_fees_are_paid_by_the_app() {
+	new_test_ext().execute_with(|| {
+		new_app(BOB, APP_DID);
+		assert_ok!(Samaritan::set_sponsorship(
+			RuntimeOrigin::signed(BOB),
+			bounded(APP_DID),
+			bounded_vec![bounded(b"update_profile")],
+			1
+		));
+		assert_ok!(Samaritan::fund_sponsorship(RuntimeOrigin::signed(BOB), bounded(APP_DID), 1_000));
+
+		let account = Samaritan::sponsor_account(&did(APP_DID));
+		let call = profile_call(ALICE_DID, b"QmProfile");
+		let info = dispatch_info();
+		let fee = TransactionPayment::compute_fee(10, &info, 0);
+		let alice = Balances::free_balance(&ALICE);
+
+		let pre = ChargeSponsoredTransactionPayment::<Test>::new(0, Some(did(APP_DID)))
+			.pre_dispatch(&ALICE, &call, &info, 10)
+			.unwrap();
+		assert_eq!(Balances::free_balance(&account), 1_000 - fee);
+		assert_eq!(Balances::free_balance(&ALICE), alice);
+		assert_eq!(Samaritan::sponsorship_usage(did(APP_DID), &ALICE), 1);
+
+		assert_ok!(ChargeSponsoredTransactionPayment::<Test>::post_dispatch(
+			Some(pre),
+			&info,
+			&PostDispatchInfo::default(),
+			10,
+			&Ok(())
+		));
+		System::assert_last_event(Event::TransactionSponsored { app_did: APP_DID.to_vec(), who: ALICE }.into());
+
+		// max_per_account is 1
+		assert_eq!(
+			ChargeSponsoredTransactionPayment::<Test>::new(0, Some(did(APP_DID))).validate(&ALICE, &call, &info, 10),
+			Err(TransactionValidityError::Invalid(InvalidTransaction::ExhaustsResources))
+		);
+	});
+}
+
+#[test]
+fn sponsorship_only_covers_listed_calls() {
+	new_test_ext().execute_with(|| {
+		new_app(BOB, APP_DID);
+		assert_ok!(Samaritan::set_sponsorship(
+			RuntimeOrigin::signed(BOB),
+			bounded(APP_DID),
+			bounded_vec![bounded(b"update_profile")],
+			1
+		));
+		assert_ok!(Samaritan::fund_sponsorship(RuntimeOrigin::signed(BOB), bounded(APP_DID), 1_000));
+
+		let validate = |sponsor: &[u8], call: RuntimeCall| {
+			ChargeSponsoredTransactionPayment::<Test>::new(0, Some(did(sponsor))).validate(&ALICE, &call, &dispatch_info(), 10)
+		};
+
+		let rename = RuntimeCall::Samaritan(SamaritanCall::rename_samaritan { name: bounded(b"ally") });
+		assert_eq!(validate(APP_DID, rename), Err(InvalidTransaction::Call.into()));
+
+		let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+		assert_eq!(validate(APP_DID, remark), Err(InvalidTransaction::Call.into()));
+
+		assert_eq!(
+			validate(OTHER_APP_DID, profile_call(ALICE_DID, b"QmProfile")),
+			Err(InvalidTransaction::Payment.into())
+		);
+		assert_ok!(validate(APP_DID, profile_call(ALICE_DID, b"QmProfile")));
+	});
+}
+
+#[test]
+fn free_quota_covers_samaritan_calls() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		let call = profile_call(ALICE_DID, b"QmProfile");
+		let info = dispatch_info();
+		let ext = || ChargeSponsoredTransactionPayment::<Test>::new(0, None);
+
+		// FreeCallPeriod is 10, the first period ends at block 10
+		assert_eq!(Samaritan::free_calls_left(&ALICE), 2);
+		assert_eq!(ext().validate(&ALICE, &call, &info, 10).unwrap().longevity, 9);
+
+		let alice = Balances::free_balance(&ALICE);
+		for left in [1, 0] {
+			assert_ok!(ext().pre_dispatch(&ALICE, &call, &info, 10));
+			assert_eq!(Samaritan::free_calls_left(&ALICE), left);
+		}
+		assert_eq!(Balances::free_balance(&ALICE), alice);
+
+		// once used up the signer pays
+		let fee = TransactionPayment::compute_fee(10, &info, 0);
+		assert_ok!(ext().pre_dispatch(&ALICE, &call, &info, 10));
+		assert_eq!(Balances::free_balance(&ALICE), alice - fee);
+
+		// and the quota refills with the next period
+		run_to_block(10);
+		assert_eq!(Samaritan::free_calls_left(&ALICE), 2);
+	});
+}
+
+#[test]
+fn free_quota_leaves_out_heavy_and_long_calls() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		let call = profile_call(ALICE_DID, b"QmProfile");
+		let ext = || ChargeSponsoredTransactionPayment::<Test>::new(0, None);
+
+		// MaxFreeCallWeight is 16 and MaxFreeCallLength 64
+		let heavy = DispatchInfo { weight: Weight::from_ref_time(17), ..Default::default() };
+		let alice = Balances::free_balance(&ALICE);
+		assert_ok!(ext().pre_dispatch(&ALICE, &call, &heavy, 10));
+		assert_eq!(Balances::free_balance(&ALICE), alice - TransactionPayment::compute_fee(10, &heavy, 0));
+
+		let alice = Balances::free_balance(&ALICE);
+		assert_ok!(ext().pre_dispatch(&ALICE, &call, &dispatch_info(), 65));
+		assert_eq!(Balances::free_balance(&ALICE), alice - TransactionPayment::compute_fee(65, &dispatch_info(), 0));
+		assert_eq!(Samaritan::free_calls_left(&ALICE), 2);
+	});
+}
+
+#[test]
+fn free_calls_provide_one_tag_per_account() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_samaritan(DAVE, DAVE_DID);
+		let call = profile_call(ALICE_DID, b"QmProfile");
+		let provides = |who| {
+			ChargeSponsoredTransactionPayment::<Test>::new(0, None)
+				.validate(&who, &call, &dispatch_info(), 10)
+				.unwrap()
+				.provides
+		};
+
+		// a second free call of the same account replaces the first in the pool
+		assert_eq!(provides(ALICE).len(), 1);
+		assert_eq!(provides(ALICE), provides(ALICE));
+		assert_ne!(provides(ALICE), provides(DAVE));
+
+		// paid calls don't provide it
+		assert!(ChargeSponsoredTransactionPayment::<Test>::new(0, None)
+			.validate(&BOB, &call, &dispatch_info(), 10)
+			.unwrap()
+			.provides
+			.is_empty());
+	});
+}
+
+#[test]
+fn free_quota_is_for_samaritans_only() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		let info = dispatch_info();
+		let fee = TransactionPayment::compute_fee(10, &info, 0);
+		let ext = || ChargeSponsoredTransactionPayment::<Test>::new(0, None);
+
+		// accounts without a samaritan pay
+		let bob = Balances::free_balance(&BOB);
+		assert_eq!(Samaritan::free_calls_left(&BOB), 0);
+		assert_ok!(ext().pre_dispatch(&BOB, &profile_call(ALICE_DID, b"QmProfile"), &info, 10));
+		assert_eq!(Balances::free_balance(&BOB), bob - fee);
+
+		// and so do calls into other pallets
+		let alice = Balances::free_balance(&ALICE);
+		let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+		assert_ok!(ext().pre_dispatch(&ALICE, &remark, &info, 10));
+		assert_eq!(Balances::free_balance(&ALICE), alice - fee);
+		assert_eq!(Samaritan::free_calls_left(&ALICE), 2);
+	});
+}
+
+#[test]
+fn did_keys_can_be_added_and_removed() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+
+		assert_noop!(
+			Samaritan::add_did_key(RuntimeOrigin::signed(BOB), bounded(ALICE_DID), CHARLIE),
+			Error::<Test>::NotDIDOwner
+		);
+
+		assert_ok!(Samaritan::add_did_key(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), CHARLIE));
+		System::assert_last_event(Event::DidKeyAdded { did: ALICE_DID.to_vec(), key: CHARLIE }.into());
+		assert_noop!(
+			Samaritan::add_did_key(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), CHARLIE),
+			Error::<Test>::DuplicateDidKey
+		);
+
+		// MaxDidKeys is 2
+		assert_ok!(Samaritan::add_did_key(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), DAVE));
+		assert_noop!(
+			Samaritan::add_did_key(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), EVE),
+			Error::<Test>::DidKeysOverflow
+		);
+
+		assert_ok!(Samaritan::remove_did_key(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), CHARLIE));
+		assert_eq!(Samaritan::did_keys(did(ALICE_DID)).to_vec(), vec![DAVE]);
+		System::assert_last_event(Event::DidKeyRemoved { did: ALICE_DID.to_vec(), key: CHARLIE }.into());
+		assert_noop!(
+			Samaritan::remove_did_key(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), CHARLIE),
+			Error::<Test>::DidKeyNotFound
+		);
+	});
+}
+
+/// Alice's DID with a registered sr25519 key
+fn did_with_key() -> sr25519::Pair {
+	let pair = sr25519::Pair::from_seed(&[7u8; 32]);
+	new_samaritan(ALICE, ALICE_DID);
+	assert_ok!(Samaritan::add_did_key(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), pair.public().into()));
+	pair
+}
+
+fn sign(pair: &sr25519::Pair, call: &RuntimeCall, nonce: u64, expires: u64) -> MultiSignature {
+	pair.sign(&Samaritan::did_call_payload(&did(ALICE_DID), nonce, expires, call)).into()
+}
+
+#[test]
+fn submit_did_call_dispatches_as_the_did() {
+	new_test_ext().execute_with(|| {
+		let pair = did_with_key();
+		let call = profile_call(ALICE_DID, b"QmProfile");
+		let signature = sign(&pair, &call, 0, 10);
+
+		assert_ok!(Samaritan::submit_did_call(
+			RuntimeOrigin::signed(EVE),
+			bounded(ALICE_DID),
+			Box::new(call.clone()),
+			0,
+			10,
+			signature.clone()
+		));
+		assert_eq!(Samaritan::prof_reg(did(ALICE_DID)).unwrap().to_vec(), b"QmProfile".to_vec());
+		assert_eq!(Samaritan::did_nonce_of(ALICE_DID.to_vec()), 1);
+		System::assert_last_event(
+			Event::DidCallDispatched { did: ALICE_DID.to_vec(), nonce: 0, relayer: EVE, result: Ok(()) }.into(),
+		);
+
+		// the signature can't be replayed
+		assert_noop!(
+			Samaritan::submit_did_call(RuntimeOrigin::signed(EVE), bounded(ALICE_DID), Box::new(call), 0, 10, signature),
+			Error::<Test>::InvalidDidNonce
+		);
+	});
+}
+
+#[test]
+fn submit_did_call_reports_inner_failure() {
+	new_test_ext().execute_with(|| {
+		let pair = did_with_key();
+		new_samaritan(DAVE, DAVE_DID);
+
+		// the DID origin can't touch another DID
+		let call = profile_call(DAVE_DID, b"QmProfile");
+		let signature = sign(&pair, &call, 0, 10);
+
+		assert_ok!(Samaritan::submit_did_call(RuntimeOrigin::signed(EVE), bounded(ALICE_DID), Box::new(call), 0, 10, signature));
+		assert!(Samaritan::prof_reg(did(DAVE_DID)).is_none());
+		// the nonce is used up all the same
+		assert_eq!(Samaritan::did_nonce_of(ALICE_DID.to_vec()), 1);
+		System::assert_last_event(
+			Event::DidCallDispatched {
+				did: ALICE_DID.to_vec(),
+				nonce: 0,
+				relayer: EVE,
+				result: Err(Error::<Test>::NotDIDOwner.into()),
+			}
+			.into(),
+		);
+	});
+}
+
+#[test]
+fn submit_did_call_checks_signature_and_expiry() {
+	new_test_ext().execute_with(|| {
+		let pair = did_with_key();
+		let call = profile_call(ALICE_DID, b"QmProfile");
+		let submit = |nonce: u64, expires: u64, signature: MultiSignature| {
+			Samaritan::submit_did_call(
+				RuntimeOrigin::signed(EVE),
+				bounded(ALICE_DID),
+				Box::new(call.clone()),
+				nonce,
+				expires,
+				signature,
+			)
+		};
+
+		let stranger = sr25519::Pair::from_seed(&[8u8; 32]);
+		assert_noop!(submit(0, 10, sign(&stranger, &call, 0, 10)), Error::<Test>::BadDidSignature);
+		// the signature covers the expiry
+		assert_noop!(submit(0, 20, sign(&pair, &call, 0, 10)), Error::<Test>::BadDidSignature);
+		assert_noop!(submit(1, 10, sign(&pair, &call, 1, 10)), Error::<Test>::InvalidDidNonce);
+
+		run_to_block(11);
+		assert_noop!(submit(0, 10, sign(&pair, &call, 0, 10)), Error::<Test>::DidCallExpired);
+
+		// removed keys can no longer sign
+		assert_ok!(Samaritan::remove_did_key(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), pair.public().into()));
+		assert_noop!(submit(0, 20, sign(&pair, &call, 0, 20)), Error::<Test>::BadDidSignature);
+	});
+}
+
+#[test]
+fn try_state_catches_corrupted_storage() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		assert_ok!(Samaritan::update_document(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(b"QmDoc1")));
+		assert_ok!(Samaritan::update_quorum(RuntimeOrigin::signed(ALICE), DidRef::Did(bounded(ALICE_DID)), DidRef::Did(bounded(DAVE_DID))));
+		new_app(BOB, APP_DID);
+		assert_ok!(Samaritan::generate_token(RuntimeOrigin::signed(BOB), bounded(APP_DID), bounded(ALICE_DID), commitment(APP_DID, ALICE_DID)));
+		assert_ok!(Samaritan::do_try_state());
+
+		// two active documents
+		let mut docs = Samaritan::doc_metareg(did(ALICE_DID)).unwrap();
+		docs[0].active = true;
+		DocMetaRegistry::<Test>::insert(did(ALICE_DID), docs.clone());
+		assert_eq!(Samaritan::do_try_state(), Err("more than one active DID document"));
+
+		// versions going backwards
+		docs[0].active = false;
+		docs[0].version = 2;
+		DocMetaRegistry::<Test>::insert(did(ALICE_DID), docs.clone());
+		assert_eq!(Samaritan::do_try_state(), Err("DID document versions or creation times out of order"));
+
+		// a samaritan without history
+		DocMetaRegistry::<Test>::remove(did(ALICE_DID));
+		assert_eq!(Samaritan::do_try_state(), Err("samaritan DID without document history"));
+		docs[0].version = 0;
+		DocMetaRegistry::<Test>::insert(did(ALICE_DID), docs);
+
+		// duplicate and unknown quorum members
+		let alice = Samaritan::did_index_of(ALICE_DID.to_vec()).unwrap();
+		let dave = Samaritan::did_index_of(DAVE_DID.to_vec()).unwrap();
+		Quorums::<Test>::mutate(alice, |quorum| quorum.as_mut().unwrap().try_push(dave).unwrap());
+		assert_eq!(Samaritan::do_try_state(), Err("duplicate trust quorum member"));
+		Quorums::<Test>::insert(alice, BoundedVec::try_from(vec![99]).unwrap());
+		assert_eq!(Samaritan::do_try_state(), Err("quorum member not interned"));
+		Quorums::<Test>::remove(alice);
+
+		// a samaritan or app whose DID lost its owner
+		DidOwners::<Test>::insert(did(ALICE_DID), BOB);
+		assert_eq!(Samaritan::do_try_state(), Err("samaritan does not own its DID"));
+		DidOwners::<Test>::insert(did(ALICE_DID), ALICE);
+		DidOwners::<Test>::insert(did(APP_DID), ALICE);
+		assert_eq!(Samaritan::do_try_state(), Err("app owner does not own the app DID"));
+		DidOwners::<Test>::insert(did(APP_DID), BOB);
+
+		// signup tokens whose user or app DID lost its owner
+		DidOwners::<Test>::remove(did(ALICE_DID));
+		SamaritanRegistry::<Test>::remove(ALICE);
+		assert_eq!(Samaritan::do_try_state(), Err("signup token for an unowned user DID"));
+		DidOwners::<Test>::insert(did(ALICE_DID), ALICE);
+		AppRegistry::<Test>::remove(did(APP_DID));
+		DidOwners::<Test>::remove(did(APP_DID));
+		assert_eq!(Samaritan::do_try_state(), Err("signup token for an unowned app DID"));
+	});
+}
+
+fn set_parameters(f: impl FnOnce(&mut crate::SamaritanParameters<Test>)) {
+	let mut parameters = Samaritan::parameters();
+	f(&mut parameters);
+	assert_ok!(Samaritan::set_parameters(RuntimeOrigin::root(), parameters));
+}
+
+#[test]
+fn parameters_default_to_config() {
+	new_test_ext().execute_with(|| {
+		let parameters = Samaritan::parameters();
+		assert_eq!(parameters.quorum_cap, 3);
+		assert_eq!(parameters.history_window, 4);
+		assert_eq!((parameters.signup_token_ttl, parameters.consent_ttl), (None, None));
+		assert_eq!((parameters.deposit_base, parameters.deposit_per_byte), (10, 1));
+	});
+}
+
+#[test]
+fn set_parameters_requires_admin_within_bounds() {
+	new_test_ext().execute_with(|| {
+		let mut parameters = Samaritan::parameters();
+		parameters.consent_ttl = Some(500);
+
+		assert_noop!(Samaritan::set_parameters(RuntimeOrigin::signed(ALICE), parameters.clone()), BadOrigin);
+		assert_ok!(Samaritan::set_parameters(RuntimeOrigin::root(), parameters.clone()));
+		assert_eq!(Samaritan::parameters(), parameters);
+		System::assert_last_event(Event::ParametersUpdated { parameters: parameters.clone() }.into());
+
+		// MaxQuorumMembersCount is 3 and MaxCacheLength is 4
+		for (quorum_cap, history_window) in [(4, 4), (3, 5), (3, 0)] {
+			let mut over = parameters.clone();
+			over.quorum_cap = quorum_cap;
+			over.history_window = history_window;
+			assert_noop!(Samaritan::set_parameters(RuntimeOrigin::root(), over), Error::<Test>::ParameterOutOfBounds);
+		}
+	});
+}
+
+#[test]
+fn quorum_cap_limits_quorums() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		set_parameters(|p| p.quorum_cap = 1);
+
+		let add = |member: &[u8]| {
+			Samaritan::update_quorum(RuntimeOrigin::signed(ALICE), DidRef::Did(did(ALICE_DID)), DidRef::Did(did(member)))
+		};
+		assert_ok!(add(b"did:sam:m1"));
+		assert_noop!(add(b"did:sam:m2"), Error::<Test>::QuorumOverflow);
+
+		set_parameters(|p| p.quorum_cap = 2);
+		assert_ok!(add(b"did:sam:m2"));
+	});
+}
+
+#[test]
+fn history_window_limits_documents() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		set_parameters(|p| p.history_window = 2);
+
+		let update = |n: u8| Samaritan::update_document(RuntimeOrigin::signed(ALICE), did(ALICE_DID), bounded(&[b'0' + n]));
+		let versions = || Samaritan::doc_metareg(did(ALICE_DID)).unwrap().iter().map(|d| d.version).collect::<Vec<_>>();
+		assert_ok!(update(1));
+		assert_ok!(update(2));
+		assert_eq!(versions(), vec![1, 2]);
+
+		// versions past the window roll out oldest first, the latest stays active
+		for n in 3..=5 {
+			assert_ok!(update(n));
+		}
+		let docs = Samaritan::doc_metareg(did(ALICE_DID)).unwrap();
+		assert_eq!(versions(), vec![4, 5]);
+		assert_eq!(docs.iter().map(|d| d.hl.to_vec()).collect::<Vec<_>>(), vec![b"4".to_vec(), b"5".to_vec()]);
+		assert_eq!(docs.iter().map(|d| d.active).collect::<Vec<_>>(), vec![false, true]);
+
+		// a smaller window drops every version past it on the next update
+		set_parameters(|p| p.history_window = 1);
+		assert_ok!(update(6));
+		assert_eq!(versions(), vec![6]);
+	});
+}
+
+#[test]
+fn signup_token_expires_after_its_ttl() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		set_parameters(|p| p.signup_token_ttl = Some(5));
+
+		assert_ok!(Samaritan::generate_token(RuntimeOrigin::signed(ALICE), did(APP_DID), did(ALICE_DID), commitment(APP_DID, ALICE_DID)));
+		let token = TOKEN;
+
+		System::set_block_number(6);
+		assert!(Samaritan::verify_signup_token(APP_DID.to_vec(), ALICE_DID.to_vec(), token));
+
+		System::set_block_number(7);
+		assert!(!Samaritan::verify_signup_token(APP_DID.to_vec(), ALICE_DID.to_vec(), token));
+		assert_noop!(
+			Samaritan::redeem_token(RuntimeOrigin::signed(BOB), did(APP_DID), did(ALICE_DID), token),
+			Error::<Test>::SignUpTokenExpired
+		);
+
+		// lifting the expiry makes the token redeemable again
+		set_parameters(|p| p.signup_token_ttl = None);
+		assert_ok!(Samaritan::redeem_token(RuntimeOrigin::signed(BOB), did(APP_DID), did(ALICE_DID), token));
+	});
+}
+
+#[test]
+fn consent_ttl_applies_to_open_ended_grants() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		set_parameters(|p| p.consent_ttl = Some(500));
+
+		let grant = |expires_at: Option<u64>| {
+			assert_ok!(Samaritan::grant_consent(
+				RuntimeOrigin::signed(ALICE),
+				did(ALICE_DID),
+				did(APP_DID),
+				bounded_vec![bounded(b"profile")],
+				expires_at
+			));
+			Samaritan::consent(ALICE_DID.to_vec(), APP_DID.to_vec()).unwrap().expires_at
+		};
+
+		// Now starts at 1_000
+		assert_eq!(grant(None), Some(1_500));
+		assert_eq!(grant(Some(5_000)), Some(5_000));
+	});
+}
+
+#[test]
+fn deposits_follow_parameters() {
+	new_test_ext().execute_with(|| {
+		set_parameters(|p| {
+			p.deposit_base = 100;
+			p.deposit_per_byte = 2;
+		});
+		assert_eq!(Samaritan::deposit_for(10), 120);
+
+		new_samaritan(ALICE, ALICE_DID);
+		let sam = Samaritan::sam_reg(&ALICE).unwrap();
+		let docs = Samaritan::doc_metareg(did(ALICE_DID)).unwrap();
+		assert_eq!(
+			Balances::reserved_balance(&ALICE),
+			100 + 2 * sam.encoded_size() as u64 + 100 + 2 * docs.encoded_size() as u64 + intern_deposit(ALICE_DID)
+		);
+	});
+}
+
+#[test]
+fn migrate_to_v1_extends_document_metadata() {
+	new_test_ext().execute_with(|| {
+		let old: Vec<OldDocMetadata<Test>> = vec![
+			OldDocMetadata { version: 0, hl: b"QmDoc0".to_vec().try_into().unwrap(), created: 500, active: false },
+			OldDocMetadata { version: 1, hl: b"QmDoc1".to_vec().try_into().unwrap(), created: 900, active: true },
+		];
+		unhashed::put(&v1::DocMetaRegistry::<Test>::hashed_key_for(did(ALICE_DID)), &old);
+		assert_eq!(Samaritan::on_chain_storage_version(), 0);
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(Samaritan::on_chain_storage_version(), 1);
+		assert!(!PauseWhileMigrating::<Test>::contains(&profile_call(ALICE_DID, b"QmProfile")));
+
+		run_to_block(2);
+		assert!(Samaritan::doc_translate_progress().is_none());
+
+		let docs = v1::DocMetaRegistry::<Test>::get(did(ALICE_DID)).unwrap();
+		assert_eq!(docs.iter().map(|d| (d.version, d.created, d.active)).collect::<Vec<_>>(), vec![(0, 500, false), (1, 900, true)]);
+		assert!(docs.iter().all(|d| d.updated == d.created && d.block == 0));
+
+		// running it again leaves the migrated entries alone
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert!(Samaritan::doc_translate_progress().is_none());
+		assert_eq!(v1::DocMetaRegistry::<Test>::get(did(ALICE_DID)).unwrap(), docs);
+	});
+}
+
+/// move an entry and its deposit from `Blake2_128Concat` keys back to the `Twox64Concat` keys of v1
+fn to_twox(prefix: [u8; 32], keys: &[&[u8]]) {
+	let (mut blake, mut twox) = (prefix.to_vec(), prefix.to_vec());
+	for key in keys {
+		let encoded = did(key).encode();
+		blake.extend(Blake2_128Concat::hash(&encoded));
+		twox.extend(Twox64Concat::hash(&encoded));
+	}
+
+	let value = unhashed::get_raw(&blake).unwrap();
+	unhashed::kill(&blake);
+	unhashed::put_raw(&twox, &value);
+
+	let deposit = StorageDeposits::<Test>::take(H256(blake2_256(&blake))).unwrap();
+	StorageDeposits::<Test>::insert(H256(blake2_256(&twox)), deposit);
+}
+
+/// a trust quorum stored by full DIDs as before v3, with its deposit held from `who`
+fn old_quorum(who: AccountId, did_str: &[u8], members: &[&[u8]]) {
+	let quorum: BoundedVec<_, _> = members.iter().map(|member| did(member)).collect::<Vec<_>>().try_into().unwrap();
+	let deposit = Samaritan::deposit_for(quorum.encoded_size());
+	let key = v2::TrustQuorum::<Test>::hashed_key_for(did(did_str));
+
+	assert_ok!(Balances::reserve(&who, deposit));
+	StorageDeposits::<Test>::insert(H256(blake2_256(&key)), (who, deposit));
+	v2::TrustQuorum::<Test>::insert(did(did_str), quorum);
+}
+
+#[test]
+fn migrate_to_v2_rekeys_did_maps() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		old_quorum(ALICE, ALICE_DID, &[DAVE_DID]);
+		assert_ok!(Samaritan::update_profile(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(b"QmProfile")));
+		new_app(BOB, APP_DID);
+		assert_ok!(Samaritan::generate_token(RuntimeOrigin::signed(BOB), bounded(APP_DID), bounded(ALICE_DID), commitment(APP_DID, ALICE_DID)));
+
+		let docs = Samaritan::doc_metareg(did(ALICE_DID)).unwrap();
+		let token = Samaritan::signup_data(did(APP_DID), did(ALICE_DID)).unwrap();
+
+		to_twox(DocMetaRegistry::<Test>::final_prefix(), &[ALICE_DID]);
+		to_twox(DocMetaRegistry::<Test>::final_prefix(), &[APP_DID]);
+		to_twox(ProfileRegistry::<Test>::final_prefix(), &[ALICE_DID]);
+		to_twox(v2::TrustQuorum::<Test>::final_prefix(), &[ALICE_DID]);
+		to_twox(SignUpDataRegistry::<Test>::final_prefix(), &[APP_DID, ALICE_DID]);
+		StorageVersion::new(1).put::<Samaritan>();
+		assert_eq!(Samaritan::doc_metareg(did(ALICE_DID)), None);
+
+		MigrateToV2::<Test>::on_runtime_upgrade();
+		assert_eq!(Samaritan::on_chain_storage_version(), 2);
+
+		// samaritan calls wait for the re-keying, others go through
+		assert!(!PauseWhileMigrating::<Test>::contains(&profile_call(ALICE_DID, b"QmProfile2")));
+		assert!(PauseWhileMigrating::<Test>::contains(&RuntimeCall::System(frame_system::Call::remark { remark: vec![] })));
+
+		run_to_block(2);
+		assert_eq!(Samaritan::rekey_progress(), None);
+		assert!(PauseWhileMigrating::<Test>::contains(&profile_call(ALICE_DID, b"QmProfile2")));
 
-	let name = name_too_long;
-	let did_str = did_too_long;
-	let did_str = string;
-	let hash = hash;
+		assert_eq!(Samaritan::doc_metareg(did(ALICE_DID)), Some(docs));
+		assert!(Samaritan::doc_metareg(did(APP_DID)).is_some());
+		assert_eq!(Samaritan::prof_reg(did(ALICE_DID)).unwrap().to_vec(), b"QmProfile".to_vec());
+		assert_eq!(v2::TrustQuorum::<Test>::get(did(ALICE_DID)).unwrap().to_vec(), vec![did(DAVE_DID)]);
+		assert_eq!(Samaritan::signup_data(did(APP_DID), did(ALICE_DID)), Some(token));
 
-	assert_noop!(Samaritan::create_samaritan(Origin::signed(1), name_too_long, did_str, hash), 
-		Error::<Test>::NameOverflow);
+		// deposits moved along with their entries
+		let key = DocMetaRegistry::<Test>::hashed_key_for(did(ALICE_DID));
+		assert_eq!(Samaritan::storage_deposit(H256(blake2_256(&key))).map(|(who, _)| who), Some(ALICE));
+		assert_ok!(Samaritan::do_try_state());
+	});
+}
 
-	assert_noop!(Samaritan::create_samaritan(Origin::signed(1), name_too_long, did_str, hash), 
-	Error::<Test>::DIDLengthOverflow);
+#[test]
+fn migration_cursors_hold_the_longest_double_map_keys() {
+	let longest = vec![b'd'; <Test as crate::Config>::MaxDIDLength::get() as usize];
+	let key = SignUpDataRegistry::<Test>::hashed_key_for(did(&longest), did(&longest));
 
+	assert_ok!(BoundedVec::<u8, v2::MaxKeyLength<Test>>::try_from(key));
 }
 
+#[test]
+fn migrate_to_v3_interns_quorums() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		old_quorum(ALICE, ALICE_DID, &[DAVE_DID, b"did:sam:m2"]);
+		let reserved = Balances::reserved_balance(&ALICE);
+		StorageVersion::new(2).put::<Samaritan>();
+
+		MigrateToV3::<Test>::on_runtime_upgrade();
+		assert_eq!(Samaritan::on_chain_storage_version(), 3);
+		assert!(!PauseWhileMigrating::<Test>::contains(&profile_call(ALICE_DID, b"QmProfile")));
+
+		run_to_block(2);
+		assert!(!Samaritan::interning_quorums());
+		assert_eq!(v2::TrustQuorum::<Test>::iter().count(), 0);
+
+		// the samaritan DID was interned first, its members follow
+		assert_eq!(Samaritan::quorum(0).unwrap().to_vec(), vec![1, 2]);
+		assert_eq!(Samaritan::trust_quorum(ALICE_DID.to_vec()), vec![DAVE_DID.to_vec(), b"did:sam:m2".to_vec()]);
 
+		// the deposit shrank with the entry
+		assert!(Balances::reserved_balance(&ALICE) < reserved);
+		assert_ok!(Samaritan::do_try_state());
+	});
+}
 
 #[test]
-fn x() {}
+fn migrate_to_v4_purges_signup_entries() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		let reserved = Balances::reserved_balance(&ALICE);
+		assert_ok!(Samaritan::generate_token(RuntimeOrigin::signed(ALICE), bounded(APP_DID), bounded(ALICE_DID), commitment(APP_DID, ALICE_DID)));
+		// a plaintext token as stored before commitments
+		let legacy = SignUpDataRegistry::<Test>::hashed_key_for(did(APP_DID), did(DAVE_DID));
+		unhashed::put(&legacy, &H256::repeat_byte(9));
+		StorageVersion::new(3).put::<Samaritan>();
+
+		MigrateToV4::<Test>::on_runtime_upgrade();
+		assert_eq!(Samaritan::on_chain_storage_version(), 4);
+		assert!(!PauseWhileMigrating::<Test>::contains(&profile_call(ALICE_DID, b"QmProfile")));
+
+		run_to_block(2);
+		assert!(!Samaritan::purging_signups());
+		assert!(unhashed::get_raw(&legacy).is_none());
+		assert!(Samaritan::signup_data(did(APP_DID), did(ALICE_DID)).is_none());
+		assert_eq!(Balances::reserved_balance(&ALICE), reserved);
+
+		// running it again does nothing
+		MigrateToV4::<Test>::on_runtime_upgrade();
+		assert!(!Samaritan::purging_signups());
+		assert_ok!(Samaritan::do_try_state());
+	});
+}
 
 #[test]
-fn y() {}
+fn migrate_to_v5_backfills_did_owners() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		new_app(BOB, APP_DID);
+		// DIDs registered before their owners were recorded
+		DidOwners::<Test>::remove(did(ALICE_DID));
+		DidOwners::<Test>::remove(did(APP_DID));
+		StorageVersion::new(4).put::<Samaritan>();
+
+		// without an owner the DID is locked
+		assert_noop!(
+			Samaritan::update_profile(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(b"QmProfile")),
+			Error::<Test>::NotDIDOwner
+		);
+
+		MigrateToV5::<Test>::on_runtime_upgrade();
+		assert_eq!(Samaritan::on_chain_storage_version(), 5);
+		assert!(!PauseWhileMigrating::<Test>::contains(&profile_call(ALICE_DID, b"QmProfile")));
+
+		run_to_block(2);
+		assert!(Samaritan::backfill_progress().is_none());
+		assert_eq!(Samaritan::did_owner(did(ALICE_DID)), Some(ALICE));
+		assert_eq!(Samaritan::did_owner(did(APP_DID)), Some(BOB));
+		assert_ok!(Samaritan::update_profile(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(b"QmProfile")));
+
+		// running it again does nothing
+		MigrateToV5::<Test>::on_runtime_upgrade();
+		assert!(Samaritan::backfill_progress().is_none());
+		assert_ok!(Samaritan::do_try_state());
+	});
+}
 
 #[test]
-fn z() {}
+fn migrate_to_v6_holds_deposits_for_earlier_entries() {
+	new_test_ext().execute_with(|| {
+		new_samaritan(ALICE, ALICE_DID);
+		assert_ok!(Samaritan::update_profile(RuntimeOrigin::signed(ALICE), bounded(ALICE_DID), bounded(b"QmProfile")));
+		assert_ok!(Samaritan::update_quorum(RuntimeOrigin::signed(ALICE), DidRef::Did(bounded(ALICE_DID)), DidRef::Did(bounded(DAVE_DID))));
+		new_samaritan(CHARLIE, b"did:sam:charlie");
+		let held = Balances::reserved_balance(&ALICE);
 
+		// entries stored before deposits were held
+		for (_, (who, deposit)) in StorageDeposits::<Test>::drain() {
+			Balances::unreserve(&who, deposit);
+		}
+		Balances::make_free_balance_be(&CHARLIE, 1);
+		StorageVersion::new(5).put::<Samaritan>();
+
+		MigrateToV6::<Test>::on_runtime_upgrade();
+		assert_eq!(Samaritan::on_chain_storage_version(), 6);
+		assert!(!PauseWhileMigrating::<Test>::contains(&profile_call(ALICE_DID, b"QmProfile")));
+
+		run_to_block(2);
+		assert!(Samaritan::deposit_backfill_progress().is_none());
+		// Dave's DID was interned for the quorum but has no owner to pay for it
+		assert_eq!(Balances::reserved_balance(&ALICE), held - intern_deposit(DAVE_DID));
+		// Charlie can't pay, the entries stay free
+		assert_eq!(Balances::reserved_balance(&CHARLIE), 0);
+
+		// running it again does nothing
+		MigrateToV6::<Test>::on_runtime_upgrade();
+		assert!(Samaritan::deposit_backfill_progress().is_none());
+		assert_ok!(Samaritan::do_try_state());
+	});
+}
+
+proptest! {
+	#![proptest_config(ProptestConfig::with_cases(64))]
+
+	#[test]
+	fn random_calls_keep_invariants(steps in vec(any::<[u8; 4]>(), 0..128)) {
+		crate::fuzzing::run(&steps.concat());
+	}
+}
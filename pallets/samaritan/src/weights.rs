@@ -0,0 +1,883 @@
+//! Weights for pallet_samaritan
+//!
+//! NOT GENERATED: these weights are hand estimates from the storage accesses and worst-case
+//! setups of the benchmarks in `benchmarking.rs`, nothing here was measured. They must be
+//! regenerated on reference hardware before the runtime is deployed, with:
+//!
+//! ./target/release/parachain-template-node benchmark pallet
+//! --chain=dev
+//! --steps=50
+//! --repeat=20
+//! --pallet=pallet_samaritan
+//! --extrinsic=*
+//! --execution=wasm
+//! --wasm-execution=compiled
+//! --output=pallets/samaritan/src/weights.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_samaritan.
+pub trait WeightInfo {
+	fn create_samaritan(h: u32, ) -> Weight;
+	fn rename_samaritan() -> Weight;
+	fn alter_state(c: u32, ) -> Weight;
+	fn update_document(h: u32, c: u32, ) -> Weight;
+	fn update_quorum() -> Weight;
+	fn filter_quorum() -> Weight;
+	fn update_profile(h: u32, ) -> Weight;
+	fn create_app(h: u32, ) -> Weight;
+	fn update_app(h: u32, ) -> Weight;
+	fn set_app_status() -> Weight;
+	fn retire_app() -> Weight;
+	fn generate_token() -> Weight;
+	fn redeem_token() -> Weight;
+	fn reap_signup_token() -> Weight;
+	fn grant_consent() -> Weight;
+	fn revoke_consent() -> Weight;
+	fn register_pairwise_did(h: u32, ) -> Weight;
+	fn recover_pairwise_did(k: u32, ) -> Weight;
+	fn publish_release(h: u32, ) -> Weight;
+	fn yank_release() -> Weight;
+	fn set_release_channel() -> Weight;
+	fn verify_app() -> Weight;
+	fn revoke_app_verification() -> Weight;
+	fn set_vault_pointer(h: u32, ) -> Weight;
+	fn erase_vault_pointer() -> Weight;
+	fn log_access() -> Weight;
+	fn authorize_session_key() -> Weight;
+	fn revoke_session_key() -> Weight;
+	fn session_call() -> Weight;
+	fn create_plan() -> Weight;
+	fn retire_plan() -> Weight;
+	fn subscribe() -> Weight;
+	fn cancel_subscription() -> Weight;
+	fn set_sponsorship() -> Weight;
+	fn fund_sponsorship() -> Weight;
+	fn withdraw_sponsorship() -> Weight;
+	fn add_did_key() -> Weight;
+	fn remove_did_key() -> Weight;
+	fn submit_did_call(k: u32, ) -> Weight;
+	fn charge_subscription() -> Weight;
+	fn set_parameters() -> Weight;
+	fn approve_pairwise_recovery() -> Weight;
+}
+
+/// Weights for pallet_samaritan using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: Samaritan SamaritanRegistry (r:0 w:1)
+	// Storage: Samaritan DidOwners (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:3 w:3)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Samaritan DidIndices (r:1 w:1)
+	// Storage: Samaritan NextDidIndex (r:1 w:1)
+	// Storage: Samaritan IndexedDids (r:0 w:1)
+	// Storage: Samaritan DocMetaRegistry (r:0 w:1)
+	fn create_samaritan(h: u32, ) -> Weight {
+		Weight::from_ref_time(54_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			.saturating_add(T::DbWeight::get().reads(8 as u64))
+			.saturating_add(T::DbWeight::get().writes(10 as u64))
+	}
+	// Storage: Samaritan SamaritanRegistry (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn rename_samaritan() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(4 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan DocMetaRegistry (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn alter_state(c: u32, ) -> Weight {
+		Weight::from_ref_time(40_000_000 as u64)
+			// decoding and re-encoding every version in the history
+			.saturating_add(Weight::from_ref_time(1_500_000 as u64).saturating_mul(c as u64))
+			.saturating_add(T::DbWeight::get().reads(4 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan DocMetaRegistry (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn update_document(h: u32, c: u32, ) -> Weight {
+		Weight::from_ref_time(50_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			// decoding and re-encoding every version in the history
+			.saturating_add(Weight::from_ref_time(1_500_000 as u64).saturating_mul(c as u64))
+			.saturating_add(T::DbWeight::get().reads(5 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan DidIndices (r:2 w:2)
+	// Storage: Samaritan NextDidIndex (r:1 w:1)
+	// Storage: Samaritan IndexedDids (r:0 w:2)
+	// Storage: Samaritan Quorums (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:3 w:3)
+	// Storage: System Account (r:1 w:1)
+	fn update_quorum() -> Weight {
+		Weight::from_ref_time(54_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(9 as u64))
+			.saturating_add(T::DbWeight::get().writes(10 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan Quorums (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn filter_quorum() -> Weight {
+		Weight::from_ref_time(40_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(4 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Samaritan ProfileRegistry (r:0 w:1)
+	fn update_profile(h: u32, ) -> Weight {
+		Weight::from_ref_time(32_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			.saturating_add(T::DbWeight::get().reads(3 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:1)
+	// Storage: Samaritan DidOwners (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Samaritan StorageDeposits (r:2 w:2)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Samaritan DidIndices (r:1 w:1)
+	// Storage: Samaritan NextDidIndex (r:1 w:1)
+	// Storage: Samaritan IndexedDids (r:0 w:1)
+	// Storage: Samaritan DocMetaRegistry (r:0 w:1)
+	fn create_app(h: u32, ) -> Weight {
+		Weight::from_ref_time(70_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			.saturating_add(T::DbWeight::get().reads(8 as u64))
+			.saturating_add(T::DbWeight::get().writes(9 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:1)
+	fn update_app(h: u32, ) -> Weight {
+		Weight::from_ref_time(45_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:1)
+	fn set_app_status() -> Weight {
+		Weight::from_ref_time(28_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:1)
+	// Storage: Samaritan ActivePlans (r:1 w:0)
+	// Storage: Samaritan Sponsorships (r:0 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn retire_app() -> Weight {
+		Weight::from_ref_time(52_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(4 as u64))
+			.saturating_add(T::DbWeight::get().writes(4 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan PairwiseDids (r:1 w:0)
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan Parameters (r:1 w:0)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Samaritan SignUpDataRegistry (r:0 w:1)
+	fn generate_token() -> Weight {
+		Weight::from_ref_time(44_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(6 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan SignUpDataRegistry (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn redeem_token() -> Weight {
+		Weight::from_ref_time(40_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(3 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan SignUpDataRegistry (r:1 w:1)
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan Parameters (r:1 w:0)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn reap_signup_token() -> Weight {
+		Weight::from_ref_time(36_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(5 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan PairwiseDids (r:1 w:0)
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Samaritan ConsentGrants (r:1 w:1)
+	// Storage: Samaritan ConsentHistory (r:1 w:1)
+	fn grant_consent() -> Weight {
+		Weight::from_ref_time(68_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(6 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan ConsentGrants (r:1 w:1)
+	fn revoke_consent() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:1)
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Samaritan DidIndices (r:1 w:1)
+	// Storage: Samaritan NextDidIndex (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Samaritan IndexedDids (r:0 w:1)
+	// Storage: Samaritan PairwiseDids (r:0 w:1)
+	fn register_pairwise_did(h: u32, ) -> Weight {
+		Weight::from_ref_time(43_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			.saturating_add(T::DbWeight::get().reads(7 as u64))
+			.saturating_add(T::DbWeight::get().writes(7 as u64))
+	}
+	// Storage: Samaritan PairwiseDids (r:1 w:0)
+	// Storage: System BlockHash (r:1 w:0)
+	// Storage: Samaritan DidKeys (r:1 w:1)
+	// Storage: Samaritan DidOwners (r:1 w:1)
+	// Storage: Samaritan SamaritanRegistry (r:1 w:0)
+	// Storage: Samaritan DidIndices (r:2 w:0)
+	// Storage: Samaritan Quorums (r:1 w:0)
+	// Storage: Samaritan StorageDeposits (r:2 w:2)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Samaritan PairwiseRecoveries (r:1 w:1)
+	// Storage: Samaritan DidNonces (r:1 w:1)
+	// Storage: Samaritan SessionEpochs (r:1 w:1)
+	fn recover_pairwise_did(k: u32, ) -> Weight {
+		Weight::from_ref_time(62_000_000 as u64)
+			// one signature check per master DID key
+			.saturating_add(Weight::from_ref_time(48_000_000 as u64).saturating_mul(k as u64))
+			.saturating_add(T::DbWeight::get().reads(14 as u64))
+			.saturating_add(T::DbWeight::get().writes(8 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Samaritan PublishedVersions (r:1 w:1)
+	// Storage: Samaritan AppReleases (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn publish_release(h: u32, ) -> Weight {
+		Weight::from_ref_time(84_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			.saturating_add(T::DbWeight::get().reads(6 as u64))
+			.saturating_add(T::DbWeight::get().writes(4 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan AppReleases (r:1 w:1)
+	fn yank_release() -> Weight {
+		Weight::from_ref_time(55_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan AppReleases (r:1 w:1)
+	fn set_release_channel() -> Weight {
+		Weight::from_ref_time(55_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn verify_app() -> Weight {
+		Weight::from_ref_time(25_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn revoke_app_verification() -> Weight {
+		Weight::from_ref_time(25_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan ConsentGrants (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Samaritan DataVaults (r:1 w:1)
+	fn set_vault_pointer(h: u32, ) -> Weight {
+		Weight::from_ref_time(45_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			.saturating_add(T::DbWeight::get().reads(4 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan DataVaults (r:1 w:1)
+	fn erase_vault_pointer() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan ConsentGrants (r:1 w:0)
+	// Storage: Samaritan AccessLog (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn log_access() -> Weight {
+		Weight::from_ref_time(92_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(4 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan PairwiseDids (r:1 w:0)
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan SessionKeys (r:1 w:1)
+	// Storage: Samaritan SessionEpochs (r:1 w:0)
+	// Storage: Samaritan SessionKeyEpochs (r:0 w:1)
+	fn authorize_session_key() -> Weight {
+		Weight::from_ref_time(48_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(5 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Storage: Samaritan SessionKeys (r:1 w:1)
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan SessionKeyEpochs (r:0 w:1)
+	fn revoke_session_key() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Storage: Samaritan SessionKeys (r:1 w:1)
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan ConsentGrants (r:1 w:1)
+	// Storage: Samaritan SessionEpochs (r:1 w:0)
+	// Storage: Samaritan SessionKeyEpochs (r:1 w:1)
+	fn session_call() -> Weight {
+		Weight::from_ref_time(60_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(5 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan NextPlanId (r:1 w:1)
+	// Storage: Samaritan SubscriptionPlans (r:0 w:1)
+	fn create_plan() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan SubscriptionPlans (r:1 w:1)
+	fn retire_plan() -> Weight {
+		Weight::from_ref_time(28_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan PairwiseDids (r:1 w:0)
+	// Storage: Samaritan Subscriptions (r:1 w:1)
+	// Storage: Samaritan SubscriptionPlans (r:1 w:0)
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan ChargeAgenda (r:1 w:1)
+	// Storage: System Account (r:1 w:2)
+	fn subscribe() -> Weight {
+		Weight::from_ref_time(75_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(7 as u64))
+			.saturating_add(T::DbWeight::get().writes(4 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan Subscriptions (r:1 w:1)
+	fn cancel_subscription() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan Sponsorships (r:0 w:1)
+	fn set_sponsorship() -> Weight {
+		Weight::from_ref_time(35_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan Sponsorships (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	fn fund_sponsorship() -> Weight {
+		Weight::from_ref_time(50_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(3 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	fn withdraw_sponsorship() -> Weight {
+		Weight::from_ref_time(50_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(3 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan DidKeys (r:1 w:1)
+	fn add_did_key() -> Weight {
+		Weight::from_ref_time(32_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan DidKeys (r:1 w:1)
+	fn remove_did_key() -> Weight {
+		Weight::from_ref_time(32_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan DidNonces (r:1 w:1)
+	// Storage: Samaritan DidKeys (r:1 w:0)
+	// Storage: System BlockHash (r:1 w:0)
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan ConsentGrants (r:1 w:1)
+	fn submit_did_call(k: u32, ) -> Weight {
+		Weight::from_ref_time(70_000_000 as u64)
+			// one signature check per DID key
+			.saturating_add(Weight::from_ref_time(48_000_000 as u64).saturating_mul(k as u64))
+			.saturating_add(T::DbWeight::get().reads(5 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan ChargeAgenda (r:2 w:2)
+	// Storage: Samaritan Subscriptions (r:1 w:1)
+	// Storage: Samaritan SubscriptionPlans (r:1 w:0)
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: System Account (r:1 w:2)
+	fn charge_subscription() -> Weight {
+		Weight::from_ref_time(60_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(6 as u64))
+			.saturating_add(T::DbWeight::get().writes(5 as u64))
+	}
+	// Storage: Samaritan Parameters (r:0 w:1)
+	fn set_parameters() -> Weight {
+		Weight::from_ref_time(15_000_000 as u64)
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan PairwiseRecoveries (r:1 w:1)
+	// Storage: Samaritan SamaritanRegistry (r:1 w:0)
+	// Storage: Samaritan DidIndices (r:2 w:0)
+	// Storage: Samaritan Quorums (r:1 w:0)
+	// Storage: Samaritan StorageDeposits (r:2 w:2)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Samaritan DidOwners (r:0 w:1)
+	// Storage: Samaritan DidNonces (r:1 w:1)
+	// Storage: Samaritan DidKeys (r:0 w:1)
+	// Storage: Samaritan SessionEpochs (r:1 w:1)
+	fn approve_pairwise_recovery() -> Weight {
+		Weight::from_ref_time(41_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(10 as u64))
+			.saturating_add(T::DbWeight::get().writes(8 as u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	// Storage: Samaritan SamaritanRegistry (r:0 w:1)
+	// Storage: Samaritan DidOwners (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:3 w:3)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Samaritan DidIndices (r:1 w:1)
+	// Storage: Samaritan NextDidIndex (r:1 w:1)
+	// Storage: Samaritan IndexedDids (r:0 w:1)
+	// Storage: Samaritan DocMetaRegistry (r:0 w:1)
+	fn create_samaritan(h: u32, ) -> Weight {
+		Weight::from_ref_time(54_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			.saturating_add(RocksDbWeight::get().reads(8 as u64))
+			.saturating_add(RocksDbWeight::get().writes(10 as u64))
+	}
+	// Storage: Samaritan SamaritanRegistry (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn rename_samaritan() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(4 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan DocMetaRegistry (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn alter_state(c: u32, ) -> Weight {
+		Weight::from_ref_time(40_000_000 as u64)
+			// decoding and re-encoding every version in the history
+			.saturating_add(Weight::from_ref_time(1_500_000 as u64).saturating_mul(c as u64))
+			.saturating_add(RocksDbWeight::get().reads(4 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan DocMetaRegistry (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn update_document(h: u32, c: u32, ) -> Weight {
+		Weight::from_ref_time(50_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			// decoding and re-encoding every version in the history
+			.saturating_add(Weight::from_ref_time(1_500_000 as u64).saturating_mul(c as u64))
+			.saturating_add(RocksDbWeight::get().reads(5 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan DidIndices (r:2 w:2)
+	// Storage: Samaritan NextDidIndex (r:1 w:1)
+	// Storage: Samaritan IndexedDids (r:0 w:2)
+	// Storage: Samaritan Quorums (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:3 w:3)
+	// Storage: System Account (r:1 w:1)
+	fn update_quorum() -> Weight {
+		Weight::from_ref_time(54_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(9 as u64))
+			.saturating_add(RocksDbWeight::get().writes(10 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan Quorums (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn filter_quorum() -> Weight {
+		Weight::from_ref_time(40_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(4 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Samaritan ProfileRegistry (r:0 w:1)
+	fn update_profile(h: u32, ) -> Weight {
+		Weight::from_ref_time(32_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			.saturating_add(RocksDbWeight::get().reads(3 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:1)
+	// Storage: Samaritan DidOwners (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Samaritan StorageDeposits (r:2 w:2)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Samaritan DidIndices (r:1 w:1)
+	// Storage: Samaritan NextDidIndex (r:1 w:1)
+	// Storage: Samaritan IndexedDids (r:0 w:1)
+	// Storage: Samaritan DocMetaRegistry (r:0 w:1)
+	fn create_app(h: u32, ) -> Weight {
+		Weight::from_ref_time(70_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			.saturating_add(RocksDbWeight::get().reads(8 as u64))
+			.saturating_add(RocksDbWeight::get().writes(9 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:1)
+	fn update_app(h: u32, ) -> Weight {
+		Weight::from_ref_time(45_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:1)
+	fn set_app_status() -> Weight {
+		Weight::from_ref_time(28_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:1)
+	// Storage: Samaritan ActivePlans (r:1 w:0)
+	// Storage: Samaritan Sponsorships (r:0 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn retire_app() -> Weight {
+		Weight::from_ref_time(52_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(4 as u64))
+			.saturating_add(RocksDbWeight::get().writes(4 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan PairwiseDids (r:1 w:0)
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan Parameters (r:1 w:0)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Samaritan SignUpDataRegistry (r:0 w:1)
+	fn generate_token() -> Weight {
+		Weight::from_ref_time(44_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(6 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan SignUpDataRegistry (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn redeem_token() -> Weight {
+		Weight::from_ref_time(40_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(3 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan SignUpDataRegistry (r:1 w:1)
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan Parameters (r:1 w:0)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn reap_signup_token() -> Weight {
+		Weight::from_ref_time(36_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(5 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan PairwiseDids (r:1 w:0)
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Samaritan ConsentGrants (r:1 w:1)
+	// Storage: Samaritan ConsentHistory (r:1 w:1)
+	fn grant_consent() -> Weight {
+		Weight::from_ref_time(68_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(6 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan ConsentGrants (r:1 w:1)
+	fn revoke_consent() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:1)
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Samaritan DidIndices (r:1 w:1)
+	// Storage: Samaritan NextDidIndex (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Samaritan IndexedDids (r:0 w:1)
+	// Storage: Samaritan PairwiseDids (r:0 w:1)
+	fn register_pairwise_did(h: u32, ) -> Weight {
+		Weight::from_ref_time(43_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			.saturating_add(RocksDbWeight::get().reads(7 as u64))
+			.saturating_add(RocksDbWeight::get().writes(7 as u64))
+	}
+	// Storage: Samaritan PairwiseDids (r:1 w:0)
+	// Storage: System BlockHash (r:1 w:0)
+	// Storage: Samaritan DidKeys (r:1 w:1)
+	// Storage: Samaritan DidOwners (r:1 w:1)
+	// Storage: Samaritan SamaritanRegistry (r:1 w:0)
+	// Storage: Samaritan DidIndices (r:2 w:0)
+	// Storage: Samaritan Quorums (r:1 w:0)
+	// Storage: Samaritan StorageDeposits (r:2 w:2)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Samaritan PairwiseRecoveries (r:1 w:1)
+	// Storage: Samaritan DidNonces (r:1 w:1)
+	// Storage: Samaritan SessionEpochs (r:1 w:1)
+	fn recover_pairwise_did(k: u32, ) -> Weight {
+		Weight::from_ref_time(62_000_000 as u64)
+			// one signature check per master DID key
+			.saturating_add(Weight::from_ref_time(48_000_000 as u64).saturating_mul(k as u64))
+			.saturating_add(RocksDbWeight::get().reads(14 as u64))
+			.saturating_add(RocksDbWeight::get().writes(8 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Samaritan PublishedVersions (r:1 w:1)
+	// Storage: Samaritan AppReleases (r:1 w:1)
+	// Storage: Samaritan StorageDeposits (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn publish_release(h: u32, ) -> Weight {
+		Weight::from_ref_time(84_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			.saturating_add(RocksDbWeight::get().reads(6 as u64))
+			.saturating_add(RocksDbWeight::get().writes(4 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan AppReleases (r:1 w:1)
+	fn yank_release() -> Weight {
+		Weight::from_ref_time(55_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan AppReleases (r:1 w:1)
+	fn set_release_channel() -> Weight {
+		Weight::from_ref_time(55_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn verify_app() -> Weight {
+		Weight::from_ref_time(25_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn revoke_app_verification() -> Weight {
+		Weight::from_ref_time(25_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan ConsentGrants (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Samaritan DataVaults (r:1 w:1)
+	fn set_vault_pointer(h: u32, ) -> Weight {
+		Weight::from_ref_time(45_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(h as u64))
+			.saturating_add(RocksDbWeight::get().reads(4 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan DataVaults (r:1 w:1)
+	fn erase_vault_pointer() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan ConsentGrants (r:1 w:0)
+	// Storage: Samaritan AccessLog (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn log_access() -> Weight {
+		Weight::from_ref_time(92_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(4 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan PairwiseDids (r:1 w:0)
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan SessionKeys (r:1 w:1)
+	// Storage: Samaritan SessionEpochs (r:1 w:0)
+	// Storage: Samaritan SessionKeyEpochs (r:0 w:1)
+	fn authorize_session_key() -> Weight {
+		Weight::from_ref_time(48_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(5 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	// Storage: Samaritan SessionKeys (r:1 w:1)
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan SessionKeyEpochs (r:0 w:1)
+	fn revoke_session_key() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	// Storage: Samaritan SessionKeys (r:1 w:1)
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan ConsentGrants (r:1 w:1)
+	// Storage: Samaritan SessionEpochs (r:1 w:0)
+	// Storage: Samaritan SessionKeyEpochs (r:1 w:1)
+	fn session_call() -> Weight {
+		Weight::from_ref_time(60_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(5 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan NextPlanId (r:1 w:1)
+	// Storage: Samaritan SubscriptionPlans (r:0 w:1)
+	fn create_plan() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan SubscriptionPlans (r:1 w:1)
+	fn retire_plan() -> Weight {
+		Weight::from_ref_time(28_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan PairwiseDids (r:1 w:0)
+	// Storage: Samaritan Subscriptions (r:1 w:1)
+	// Storage: Samaritan SubscriptionPlans (r:1 w:0)
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan ChargeAgenda (r:1 w:1)
+	// Storage: System Account (r:1 w:2)
+	fn subscribe() -> Weight {
+		Weight::from_ref_time(75_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(7 as u64))
+			.saturating_add(RocksDbWeight::get().writes(4 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan Subscriptions (r:1 w:1)
+	fn cancel_subscription() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: Samaritan Sponsorships (r:0 w:1)
+	fn set_sponsorship() -> Weight {
+		Weight::from_ref_time(35_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan Sponsorships (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	fn fund_sponsorship() -> Weight {
+		Weight::from_ref_time(50_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(3 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	fn withdraw_sponsorship() -> Weight {
+		Weight::from_ref_time(50_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(3 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan DidKeys (r:1 w:1)
+	fn add_did_key() -> Weight {
+		Weight::from_ref_time(32_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan DidKeys (r:1 w:1)
+	fn remove_did_key() -> Weight {
+		Weight::from_ref_time(32_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan DidNonces (r:1 w:1)
+	// Storage: Samaritan DidKeys (r:1 w:0)
+	// Storage: System BlockHash (r:1 w:0)
+	// Storage: Samaritan DidOwners (r:1 w:0)
+	// Storage: Samaritan ConsentGrants (r:1 w:1)
+	fn submit_did_call(k: u32, ) -> Weight {
+		Weight::from_ref_time(70_000_000 as u64)
+			// one signature check per DID key
+			.saturating_add(Weight::from_ref_time(48_000_000 as u64).saturating_mul(k as u64))
+			.saturating_add(RocksDbWeight::get().reads(5 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: Samaritan ChargeAgenda (r:2 w:2)
+	// Storage: Samaritan Subscriptions (r:1 w:1)
+	// Storage: Samaritan SubscriptionPlans (r:1 w:0)
+	// Storage: Samaritan AppRegistry (r:1 w:0)
+	// Storage: System Account (r:1 w:2)
+	fn charge_subscription() -> Weight {
+		Weight::from_ref_time(60_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(6 as u64))
+			.saturating_add(RocksDbWeight::get().writes(5 as u64))
+	}
+	// Storage: Samaritan Parameters (r:0 w:1)
+	fn set_parameters() -> Weight {
+		Weight::from_ref_time(15_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Samaritan PairwiseRecoveries (r:1 w:1)
+	// Storage: Samaritan SamaritanRegistry (r:1 w:0)
+	// Storage: Samaritan DidIndices (r:2 w:0)
+	// Storage: Samaritan Quorums (r:1 w:0)
+	// Storage: Samaritan StorageDeposits (r:2 w:2)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Samaritan DidOwners (r:0 w:1)
+	// Storage: Samaritan DidNonces (r:1 w:1)
+	// Storage: Samaritan DidKeys (r:0 w:1)
+	// Storage: Samaritan SessionEpochs (r:1 w:1)
+	fn approve_pairwise_recovery() -> Weight {
+		Weight::from_ref_time(41_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(10 as u64))
+			.saturating_add(RocksDbWeight::get().writes(8 as u64))
+	}
+}
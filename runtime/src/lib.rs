@@ -29,7 +29,7 @@ use frame_support::{
 	construct_runtime,
 	dispatch::DispatchClass,
 	parameter_types,
-	traits::{ConstU32, Everything},
+	traits::ConstU32,
 	weights::{
 		constants::WEIGHT_PER_SECOND, ConstantMultiplier, Weight, WeightToFeeCoefficient,
 		WeightToFeeCoefficients, WeightToFeePolynomial,
@@ -105,7 +105,7 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_samaritan::ChargeSponsoredTransactionPayment<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -115,6 +115,16 @@ pub type UncheckedExtrinsic =
 /// Extrinsic type that has already been checked.
 pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, RuntimeCall, SignedExtra>;
 
+/// Storage migrations run on runtime upgrade, each is a no-op once applied.
+pub type Migrations = (
+	pallet_samaritan::migrations::v1::MigrateToV1<Runtime>,
+	pallet_samaritan::migrations::v2::MigrateToV2<Runtime>,
+	pallet_samaritan::migrations::v3::MigrateToV3<Runtime>,
+	pallet_samaritan::migrations::v4::MigrateToV4<Runtime>,
+	pallet_samaritan::migrations::v5::MigrateToV5<Runtime>,
+	pallet_samaritan::migrations::v6::MigrateToV6<Runtime>,
+);
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -122,6 +132,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 /// Handles converting a weight scalar to a fee value, based on the scale and granularity of the
@@ -295,8 +306,8 @@ impl frame_system::Config for Runtime {
 	type OnKilledAccount = ();
 	/// The weight of database operations that the runtime can invoke.
 	type DbWeight = RocksDbWeight;
-	/// The basic call filter to use in dispatchable.
-	type BaseCallFilter = Everything;
+	/// The basic call filter to use in dispatchable, pausing samaritan calls while its storage is migrated.
+	type BaseCallFilter = pallet_samaritan::migrations::PauseWhileMigrating<Runtime>;
 	/// Weight information for the extrinsics of this pallet.
 	type SystemWeightInfo = ();
 	/// Block & extrinsics weights: base values and limits.
@@ -354,8 +365,9 @@ impl pallet_balances::Config for Runtime {
 }
 
 parameter_types! {
-	/// Relay Chain `TransactionByteFee` / 10
-	pub const TransactionByteFee: Balance = 10 * MICROUNIT;
+	/// Relay Chain `TransactionByteFee` / 100, samaritan call input is bounded at decode
+	/// so the length fee no longer has to price megabyte payloads, storage is paid by deposit
+	pub const TransactionByteFee: Balance = MICROUNIT;
 	pub const OperationalFeeMultiplier: u8 = 5;
 }
 
@@ -465,16 +477,55 @@ impl pallet_template::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 }
 
+parameter_types! {
+	pub const SamaritanPalletId: PalletId = PalletId(*b"sam/spon");
+	pub const SamaritanDepositBase: Balance = 10 * MILLIUNIT;
+	pub const SamaritanDepositPerByte: Balance = 10 * MICROUNIT;
+	pub const SamaritanMaxFreeCallWeight: Weight = MAXIMUM_BLOCK_WEIGHT.saturating_div(100);
+}
+
 /// The Samaritan pallet manages samaritan DIDs and
 /// provides core functionality for the SamOS.
 impl pallet_samaritan::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
 	type TimeProvider = Timestamp;
+	type VerifierOrigin = EnsureRoot<AccountId>;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	type WeightInfo = pallet_samaritan::weights::SubstrateWeight<Runtime>;
+	type Currency = Balances;
+	type DepositBase = SamaritanDepositBase;
+	type DepositPerByte = SamaritanDepositPerByte;
 	type MaxDIDLength = ConstU32<128>;
 	type MaxNameLength = ConstU32<128>;
 	type MaxCacheLength = ConstU32<128>;
-	type MaxQuorumMembersCount = ConstU32<3>;
+	// governance can raise the quorum size up to the cap through `Parameters`
+	type MaxQuorumMembersCount = ConstU32<16>;
+	type DefaultQuorumCap = ConstU32<3>;
+	// stored hashes were written under this bound, lowering it needs a migration of the entries above it
 	type MaxHashLength = ConstU32<9999999>;
+	// new hashes are CIDs or digests, calls reject anything longer at decode
+	type MaxCallHashLength = ConstU32<128>;
+	type MaxUriLength = ConstU32<256>;
+	type MaxRedirectUris = ConstU32<8>;
+	type MaxScopeLength = ConstU32<64>;
+	type MaxScopes = ConstU32<16>;
+	type MaxVersionLength = ConstU32<32>;
+	type MaxReleases = ConstU32<32>;
+	type MaxAccessRecords = ConstU32<256>;
+	type MaxCallNameLength = ConstU32<64>;
+	type MaxSessionCalls = ConstU32<16>;
+	type MaxChargesPerBlock = ConstU32<64>;
+	type PalletId = SamaritanPalletId;
+	type MaxSponsoredCalls = ConstU32<16>;
+	type FreeCallsPerPeriod = ConstU32<10>;
+	type FreeCallPeriod = ConstU32<DAYS>;
+	type MaxFreeCallWeight = SamaritanMaxFreeCallWeight;
+	type MaxFreeCallLength = ConstU32<2048>;
+	type DidSignature = Signature;
+	type DidSigner = <Signature as Verify>::Signer;
+	type MaxDidKeys = ConstU32<8>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -529,6 +580,7 @@ mod benches {
 		[pallet_session, SessionBench::<Runtime>]
 		[pallet_timestamp, Timestamp]
 		[pallet_collator_selection, CollatorSelection]
+		[pallet_samaritan, Samaritan]
 		[cumulus_pallet_xcmp_queue, XcmpQueue]
 	);
 }
@@ -651,6 +703,55 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_samaritan_runtime_api::SamaritanApi<
+		Block,
+		pallet_samaritan::AppInfo<Runtime>,
+		pallet_samaritan::ConsentGrant<Runtime>,
+		pallet_samaritan::Release<Runtime>,
+		pallet_samaritan::AccessRecord<Runtime>,
+		Signature,
+	> for Runtime {
+		fn verify_signup_token(app_did: Vec<u8>, did: Vec<u8>, token: Hash) -> bool {
+			Samaritan::verify_signup_token(app_did, did, token)
+		}
+
+		fn app(app_did: Vec<u8>) -> Option<pallet_samaritan::AppInfo<Runtime>> {
+			Samaritan::app_info(app_did)
+		}
+
+		fn consent(did: Vec<u8>, app_did: Vec<u8>) -> Option<pallet_samaritan::ConsentGrant<Runtime>> {
+			Samaritan::consent(did, app_did)
+		}
+
+		fn has_consent(did: Vec<u8>, app_did: Vec<u8>, scope: Vec<u8>) -> bool {
+			Samaritan::has_consent(did, app_did, scope)
+		}
+
+		fn verify_pairwise_link(did: Vec<u8>, master_did: Vec<u8>, master_signature: Signature) -> bool {
+			Samaritan::verify_pairwise_link(did, master_did, master_signature)
+		}
+
+		fn latest_stable_release(app_did: Vec<u8>) -> Option<pallet_samaritan::Release<Runtime>> {
+			Samaritan::latest_release(app_did, pallet_samaritan::ReleaseChannel::Stable)
+		}
+
+		fn access_log(did: Vec<u8>, from: u64, limit: u32) -> Vec<pallet_samaritan::AccessRecord<Runtime>> {
+			Samaritan::access_log(did, from, limit)
+		}
+
+		fn did_nonce(did: Vec<u8>) -> u64 {
+			Samaritan::did_nonce_of(did)
+		}
+
+		fn did_index(did: Vec<u8>) -> Option<u64> {
+			Samaritan::did_index_of(did)
+		}
+
+		fn trust_quorum(did: Vec<u8>) -> Vec<Vec<u8>> {
+			Samaritan::trust_quorum(did)
+		}
+	}
+
 	impl cumulus_primitives_core::CollectCollationInfo<Block> for Runtime {
 		fn collect_collation_info(header: &<Block as BlockT>::Header) -> cumulus_primitives_core::CollationInfo {
 			ParachainSystem::collect_collation_info(header)